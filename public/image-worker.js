@@ -0,0 +1,22 @@
+// Image work of the proxy sheets, away from the page.
+// The first message has the app module, the next ones are requests for the app.
+let app;
+
+self.onmessage = async (event) => {
+    if (event.data.init) {
+        const { url, module } = event.data.init;
+        app = import(url).then(async (glue) => {
+            await glue.default({ module_or_path: module });
+            return glue;
+        });
+        return;
+    }
+
+    try {
+        const glue = await app;
+        const reply = glue.proxy_image_worker(event.data, () => self.postMessage({ progress: true }));
+        self.postMessage(reply, reply.bytes ? [reply.bytes.buffer] : []);
+    } catch (error) {
+        self.postMessage({ error: String(error) });
+    }
+};
@@ -32,7 +32,8 @@ const ASSETS = [
   BASE_PATH + '/assets/site.webmanifest',
   BASE_PATH + '/assets/style.css',
   BASE_PATH + '/assets/untrack.html',
-  BASE_PATH + '/favicon.ico'
+  BASE_PATH + '/favicon.ico',
+  BASE_PATH + '/image-worker.js'
 ];
 
 const INDEX_URL = BASE_PATH + '/index.html';
@@ -0,0 +1,172 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use jiff::Zoned;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+// version 2.0, needed for directories
+const ZIP_VERSION: u16 = 20;
+// bit 11: file names are encoded in UTF-8
+const UTF8_FLAG: u16 = 1 << 11;
+
+struct ZipEntry {
+    name: String,
+    crc32: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Simple ZIP writer, used to bundle multiple files into a single download.
+///
+/// The files are stored without compression, most of our files are small
+/// or already compressed (PDF, PNG, WebP).
+#[derive(Default)]
+pub struct ZipArchive {
+    entries: Vec<ZipEntry>,
+    names: HashSet<String>,
+    data: Vec<u8>,
+}
+
+impl ZipArchive {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a file to the archive, and returns the name used in the archive.
+    ///
+    /// Duplicated names get a numbered suffix, e.g. `deck.json` -> `deck (2).json`.
+    pub fn add_file(&mut self, name: &str, contents: &[u8]) -> String {
+        let name = self.unique_name(name);
+        let (time, date) = dos_date_time();
+        let crc32 = crc32(contents);
+
+        let entry = ZipEntry {
+            name: name.clone(),
+            crc32,
+            size: contents.len() as u32,
+            offset: self.data.len() as u32,
+        };
+
+        // local file header
+        self.data
+            .extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        self.data.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        self.data.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.data.extend_from_slice(&time.to_le_bytes());
+        self.data.extend_from_slice(&date.to_le_bytes());
+        self.data.extend_from_slice(&entry.crc32.to_le_bytes());
+        self.data.extend_from_slice(&entry.size.to_le_bytes()); // compressed
+        self.data.extend_from_slice(&entry.size.to_le_bytes()); // uncompressed
+        self.data
+            .extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // extra field
+        self.data.extend_from_slice(entry.name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        self.names.insert(name.clone());
+        self.entries.push(entry);
+        name
+    }
+
+    /// Writes the central directory and returns the content of the archive.
+    pub fn finish(mut self) -> Vec<u8> {
+        let (time, date) = dos_date_time();
+        let central_directory_offset = self.data.len() as u32;
+
+        for entry in &self.entries {
+            self.data
+                .extend_from_slice(&CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+            self.data.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // made by
+            self.data.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // needed
+            self.data.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+            self.data.extend_from_slice(&0u16.to_le_bytes()); // stored
+            self.data.extend_from_slice(&time.to_le_bytes());
+            self.data.extend_from_slice(&date.to_le_bytes());
+            self.data.extend_from_slice(&entry.crc32.to_le_bytes());
+            self.data.extend_from_slice(&entry.size.to_le_bytes());
+            self.data.extend_from_slice(&entry.size.to_le_bytes());
+            self.data
+                .extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            self.data.extend_from_slice(&0u16.to_le_bytes()); // extra field
+            self.data.extend_from_slice(&0u16.to_le_bytes()); // comment
+            self.data.extend_from_slice(&0u16.to_le_bytes()); // disk number
+            self.data.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            self.data.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            self.data.extend_from_slice(&entry.offset.to_le_bytes());
+            self.data.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let central_directory_size = self.data.len() as u32 - central_directory_offset;
+
+        // end of central directory record
+        self.data
+            .extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // disk with central directory
+        self.data
+            .extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        self.data
+            .extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        self.data
+            .extend_from_slice(&central_directory_size.to_le_bytes());
+        self.data
+            .extend_from_slice(&central_directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // comment
+
+        self.data
+    }
+
+    fn unique_name(&self, name: &str) -> String {
+        if !self.names.contains(name) {
+            return name.to_string();
+        }
+
+        // keep the full extension, e.g. ".holodelta.json"
+        let file_start = name.rfind('/').map_or(0, |i| i + 1);
+        let (stem, ext) = match name[file_start..].find('.') {
+            Some(i) => name.split_at(file_start + i),
+            None => (name, ""),
+        };
+
+        (2..)
+            .map(|i| format!("{stem} ({i}){ext}"))
+            .find(|n| !self.names.contains(n))
+            .expect("there is always a free name")
+    }
+}
+
+fn dos_date_time() -> (u16, u16) {
+    let now = Zoned::now();
+    // the DOS format starts at 1980
+    let year = (now.year().max(1980) - 1980) as u16;
+    let time = ((now.hour() as u16) << 11)
+        | ((now.minute() as u16) << 5)
+        | ((now.second() as u16) / 2);
+    let date = (year << 9) | ((now.month() as u16) << 5) | (now.day() as u16);
+    (time, date)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut crc = i as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    0xEDB88320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+            }
+            *entry = crc;
+        }
+        table
+    });
+
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        table[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
@@ -6,9 +6,7 @@ use crate::{
     AUTO_SAVE_DECK, CARDS_PRICES, CardLanguage, CardType, EXPORT_FORMAT, FREE_BASIC_CHEERS,
     PREVIEW_CARD_LANG, PRICE_SERVICE,
     components::modal_popup::{Popup, show_popup},
-    sources::{
-        CommonCard, DeckLike, DeckOrPile, DeckType, ImageOptions, price_check::PriceCheckService,
-    },
+    sources::{CommonCard, DeckLike, DeckOrPile, DeckType, ImageOptions},
     tracker::{EventType, TrackEvent, track_event, track_external_url},
 };
 
@@ -66,10 +64,7 @@ pub fn Card(
         )
         .unwrap_or("?".into());
     let price_url = card.price_url(&db.read(), price_service);
-    let price_name = match price_service {
-        PriceCheckService::Yuyutei => "Yuyutei",
-        PriceCheckService::TcgPlayer => "TCGplayer",
-    };
+    let price_name = price_service.provider().name();
 
     // verify card amount
     let total_amount = if let Some(common_deck) = common_deck {
@@ -111,6 +106,7 @@ pub fn Card(
         Some(DeckType::HoloDelta) => is_unknown,
         Some(DeckType::HoloDuel) => is_unknown || is_unreleased,
         Some(DeckType::TabletopSim) => is_unknown || is_unreleased,
+        Some(DeckType::Decklist) => is_unknown || is_unreleased,
         Some(DeckType::ProxySheets) => {
             card.card_type(&db.read()) != Some(CardType::Cheer)
                 && card
@@ -125,13 +121,9 @@ pub fn Card(
                     )
                     .is_none()
         }
-        Some(DeckType::PriceCheck) => {
-            card.card_illustration(&db.read())
-                .is_none_or(|i| match *PRICE_SERVICE.read() {
-                    PriceCheckService::Yuyutei => i.yuyutei_sell_url.is_none(),
-                    PriceCheckService::TcgPlayer => i.tcgplayer_product_id.is_none(),
-                })
-        }
+        Some(DeckType::PriceCheck) => card
+            .card_illustration(&db.read())
+            .is_none_or(|i| PRICE_SERVICE.read().provider().cache_key(i).is_none()),
         _ => false,
     };
 
@@ -239,7 +231,7 @@ pub fn Card(
                                 title: "Go to {price_name} for {card.card_number}",
                                 href: "{price_url}",
                                 target: "_blank",
-                                onclick: |_| { track_external_url(price_name) },
+                                onclick: move |_| { track_external_url(&price_service.provider().name()) },
                                 i { class: "fa-solid fa-arrow-up-right-from-square" }
                             }
                         }
@@ -17,9 +17,7 @@ use crate::{
         modal_popup::{ModelPopup, Popup, show_popup},
         tooltip::{Tooltip, TooltipPlacement},
     },
-    sources::{
-        CommonCard, DeckLike, DeckOrPile, DeckType, ImageOptions, price_check::PriceCheckService,
-    },
+    sources::{CommonCard, DeckLike, DeckOrPile, DeckType, ImageOptions},
     tracker::{EventType, TrackEvent, track_event, track_external_url},
 };
 
@@ -132,10 +130,10 @@ pub fn CardDetailsTitle(card: Signal<CommonCard>, db: Signal<CardsDatabase>) ->
             return vec![];
         };
 
-        let mut warnings = vec![];
+        let mut warnings: Vec<String> = vec![];
 
         if *is_unknown.read() {
-            return vec!["This card is unknown."];
+            return vec!["This card is unknown.".into()];
         }
 
         if matches!(
@@ -144,11 +142,15 @@ pub fn CardDetailsTitle(card: Signal<CommonCard>, db: Signal<CardsDatabase>) ->
         ) && *is_unreleased.read()
         {
             if illust.is_none_or(|i| !i.manage_id.has_value()) {
-                warnings.push("This card is unreleased.");
+                warnings.push("This card is unreleased.".into());
             } else {
                 match *PREVIEW_CARD_LANG.read() {
-                    CardLanguage::Japanese => warnings.push("This card is unreleased in Japanese."),
-                    CardLanguage::English => warnings.push("This card is unreleased in English."),
+                    CardLanguage::Japanese => {
+                        warnings.push("This card is unreleased in Japanese.".into())
+                    }
+                    CardLanguage::English => {
+                        warnings.push("This card is unreleased in English.".into())
+                    }
                 }
             }
         }
@@ -165,33 +167,25 @@ pub fn CardDetailsTitle(card: Signal<CommonCard>, db: Signal<CardsDatabase>) ->
                 .is_none()
         {
             match *PREVIEW_CARD_LANG.read() {
-                CardLanguage::Japanese => warnings.push("Missing Japanese proxy."),
-                CardLanguage::English => warnings.push("Missing English proxy."),
+                CardLanguage::Japanese => warnings.push("Missing Japanese proxy.".into()),
+                CardLanguage::English => warnings.push("Missing English proxy.".into()),
             }
         }
 
         if format == DeckType::PriceCheck {
-            match *PRICE_SERVICE.read() {
-                PriceCheckService::Yuyutei => {
-                    if illust.is_none_or(|i| i.yuyutei_sell_url.is_none()) {
-                        warnings.push("No price data on Yuyutei.");
-                        if *is_unreleased.read() {
-                            if illust.is_none_or(|i| !i.manage_id.has_value()) {
-                                warnings.push("This card is unreleased.");
-                            } else {
-                                warnings.push("This card is unreleased in Japanese.");
+            let provider = PRICE_SERVICE.read().provider();
+            if illust.is_none_or(|i| provider.cache_key(i).is_none()) {
+                warnings.push(format!("No price data on {}.", provider.name()));
+                if *is_unreleased.read() {
+                    if illust.is_none_or(|i| !i.manage_id.has_value()) {
+                        warnings.push("This card is unreleased.".into());
+                    } else {
+                        match provider.card_lang() {
+                            CardLanguage::Japanese => {
+                                warnings.push("This card is unreleased in Japanese.".into())
                             }
-                        }
-                    }
-                }
-                PriceCheckService::TcgPlayer => {
-                    if illust.is_none_or(|i| i.tcgplayer_product_id.is_none()) {
-                        warnings.push("No price data on TCGPlayer.");
-                        if *is_unreleased.read() {
-                            if illust.is_none_or(|i| !i.manage_id.has_value()) {
-                                warnings.push("This card is unreleased.");
-                            } else {
-                                warnings.push("This card is unreleased in English.");
+                            CardLanguage::English => {
+                                warnings.push("This card is unreleased in English.".into())
                             }
                         }
                     }
@@ -200,7 +200,8 @@ pub fn DeckPreview(
 
     let prices = CARDS_PRICES.read();
     let price_service = *PRICE_SERVICE.read();
-    let show_price = *show_price.read();
+    // there is no total without prices, the cards still link to the service
+    let show_price = *show_price.read() && price_service.provider().has_prices();
     let free_basic_cheers = *FREE_BASIC_CHEERS.read();
     let price = if show_price {
         deck.price_display(&db, &prices, price_service, free_basic_cheers)
@@ -1,11 +1,15 @@
-use std::{cmp::Reverse, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 
 use dioxus::{prelude::*, web::WebEventExt};
 use dioxus_sdk_time::use_debounce;
 use gloo::utils::window;
-use hocg_fan_sim_assets_model::{CardIllustration, CardReference, CardsDatabase};
+use hocg_fan_sim_assets_model::{self as hocg, CardIllustration, CardReference, CardsDatabase};
 use itertools::Itertools;
-use jiff::Timestamp;
+use jiff::{Timestamp, Zoned};
 use js_sys::Date;
 use rexie::{ObjectStore, Rexie, TransactionMode};
 use serde::{Deserialize, Serialize};
@@ -13,17 +17,38 @@ use serde_wasm_bindgen::{from_value, to_value};
 use wasm_bindgen::JsValue;
 
 use crate::{
-    AUTO_SAVE_DECK, CURRENT_PAGE, CardLanguage, EDIT_DECK, IMPORT_FORMAT, PREVIEW_IMAGE_OPTIONS,
-    Page, SHOW_PRICE, VERSION, download_file,
+    AUTO_SAVE_DECK, CURRENT_PAGE, CardLanguage, CardType, EDIT_DECK, IMPORT_FORMAT,
+    PREVIEW_CARD_LANG, PREVIEW_IMAGE_OPTIONS, Page, SHOW_PRICE, VERSION,
+    components::card_search::FilterColor,
+    download_file,
     sources::{CommonCard, CommonDeck, DeckLike, DeckOrPile, DeckType, ImageOptions, PileOfCards},
+    sync::{HttpSyncBackend, SyncBackend},
     tracker::{EventType, TrackEvent, track_event, track_internal_url},
 };
 
 const SAVE_DB_NAME: &str = "hocg-deck-convert";
 const SAVE_STORE_NAME: &str = "saved_decks";
+/// The oldest revisions of a save are dropped past this
+const MAX_REVISIONS: usize = 50;
+// 1: initial version
+// 2: saved cards keep their card number and printing, to find them again
+const SAVE_SCHEMA_VERSION: u32 = 2;
 const AUTO_SAVE_KEY: &str = "hocg-deck-convert.auto_saved_deck";
 const AUTO_SAVE_DEBOUNCE_MS: u64 = 500;
+const SETTINGS_KEY_PREFIX: &str = "hocg-deck-convert.";
+const SYNC_SETTINGS_KEY: &str = "hocg-deck-convert.sync_settings";
+const SYNC_STATE_KEY: &str = "hocg-deck-convert.sync_state";
+// device specific, or credentials
+const SETTINGS_EXCLUDED_KEYS: [&str; 3] = [AUTO_SAVE_KEY, SYNC_SETTINGS_KEY, SYNC_STATE_KEY];
+const BACKUP_FORMAT: &str = "hocg-deck-convert.library_backup";
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+// the saved deck that the current deck was loaded from
+static LOADED_SAVE_ID: GlobalSignal<Option<String>> = Signal::global(|| None);
+// the sync password is not saved, it's only kept until the page is closed
+static SYNC_PASSWORD: GlobalSignal<String> = Signal::global(String::new);
 
+#[derive(Debug, Clone, PartialEq)]
 enum SavedResult {
     Ok(SaveData),
     Err { id: String, error: String },
@@ -38,33 +63,125 @@ impl SavedResult {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 struct SaveData {
     id: String,
     name: String,
+    #[serde(default = "initial_schema_version")]
+    schema_version: u32,
     app_version: String,
     saved_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    deck: SaveDeckOrPile,
+    /// Previous states of the deck, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    revisions: Vec<SaveRevision>,
+}
+
+fn initial_schema_version() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct SaveRevision {
+    saved_at: String,
+    app_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
     deck: SaveDeckOrPile,
 }
 
 impl SaveData {
     fn new(deck: SaveDeckOrPile) -> Self {
-        let name = deck
-            .name()
-            .cloned()
-            .filter(|name| !name.trim().is_empty())
-            .unwrap_or_else(|| match deck {
-                SaveDeckOrPile::Deck(_) => format!("Saved deck {}", Timestamp::now()),
-                SaveDeckOrPile::Pile(_) => format!("Saved pile {}", Timestamp::now()),
-            });
         Self {
             id: uuid::Uuid::now_v7().to_string(),
-            name,
+            name: deck.save_name(),
+            schema_version: SAVE_SCHEMA_VERSION,
             app_version: VERSION.into(),
             saved_at: Timestamp::now().to_string(),
+            folder: None,
+            tags: vec![],
+            note: None,
             deck,
+            revisions: vec![],
+        }
+    }
+
+    fn needs_migration(&self) -> bool {
+        self.schema_version < SAVE_SCHEMA_VERSION
+    }
+
+    fn is_from_newer_version(&self) -> bool {
+        self.schema_version > SAVE_SCHEMA_VERSION
+    }
+
+    /// Upgrades the save to the current schema, one version at a time
+    fn migrate(mut self, db: &CardsDatabase) -> Self {
+        while self.schema_version < SAVE_SCHEMA_VERSION {
+            match self.schema_version {
+                1 => {
+                    self.deck.backfill(db);
+                    for revision in &mut self.revisions {
+                        revision.deck.backfill(db);
+                    }
+                }
+                _ => unreachable!("missing migration for schema {}", self.schema_version),
+            }
+            self.schema_version += 1;
+        }
+        self
+    }
+
+    /// The current state of the deck, as a revision
+    fn head(&self) -> SaveRevision {
+        SaveRevision {
+            saved_at: self.saved_at.clone(),
+            app_version: self.app_version.clone(),
+            note: self.note.clone(),
+            deck: self.deck.clone(),
+        }
+    }
+
+    /// Replaces the current state of the deck, keeping the previous one in the history
+    fn with_new_revision(&self, deck: SaveDeckOrPile, note: Option<String>) -> Self {
+        let mut save = self.clone();
+        let deck_changed = self.deck != deck;
+        let note_changed = note.is_some() && note != self.note;
+        // only keep a snapshot when something changed, a previous note is kept with its deck
+        if deck_changed || (note_changed && self.note.is_some()) {
+            save.revisions.push(self.head());
+            let extra = save.revisions.len().saturating_sub(MAX_REVISIONS);
+            save.revisions.drain(..extra);
         }
+        save.name = deck.save_name();
+        save.app_version = VERSION.into();
+        save.saved_at = Timestamp::now().to_string();
+        if deck_changed || note.is_some() {
+            save.note = note;
+        }
+        save.deck = deck;
+        save
+    }
+
+    /// The sync compares the save times, so editing the folder or tags counts as a change
+    fn with_folder_and_tags(&self, folder: Option<String>, tags: Vec<String>) -> Self {
+        let mut save = self.clone();
+        save.saved_at = Timestamp::now().to_string();
+        save.folder = folder;
+        save.tags = tags;
+        save
+    }
+
+    /// Previous revisions with their index, newest first
+    fn history(&self) -> impl Iterator<Item = (usize, &SaveRevision)> {
+        self.revisions.iter().enumerate().rev()
     }
 
     fn from_deck_or_pile(deck: DeckOrPile, db: &CardsDatabase) -> Self {
@@ -94,6 +211,244 @@ impl SaveData {
                 .unwrap_or("/hocg-deck-convert/assets/card-back.webp".to_string()),
         }
     }
+
+    fn summary(&self, db: &CardsDatabase, card_lang: CardLanguage) -> SaveSummary {
+        let deck = self.to_deck_or_pile(db);
+
+        let mut search_text = String::new();
+        search_text.push_str(&self.name);
+        search_text.push('\n');
+        if let Some(folder) = &self.folder {
+            search_text.push_str(folder);
+            search_text.push('\n');
+        }
+        for tag in &self.tags {
+            search_text.push_str(tag);
+            search_text.push('\n');
+        }
+        let mut colors = vec![];
+        for card in deck.all_cards() {
+            search_text.push_str(&card.card_number);
+            search_text.push('\n');
+            let Some(info) = card.card_info(db) else {
+                continue;
+            };
+            search_text.push_str(info.name.japanese.as_deref().unwrap_or_default());
+            search_text.push('\n');
+            search_text.push_str(info.name.english.as_deref().unwrap_or_default());
+            search_text.push('\n');
+            if card.card_type(db) != Some(CardType::Cheer) {
+                for color in &info.colors {
+                    if !colors.contains(color) {
+                        colors.push(*color);
+                    }
+                }
+            }
+        }
+
+        let (oshi, validity) = match &deck {
+            DeckOrPile::Deck(d) => (
+                d.oshi.as_ref().map(|oshi| oshi.card_number.clone()),
+                if deck.validate(db, true, card_lang).is_empty() {
+                    SaveValidity::Valid
+                } else {
+                    SaveValidity::Invalid
+                },
+            ),
+            DeckOrPile::Pile(_) => (None, SaveValidity::Pile),
+        };
+
+        SaveSummary {
+            search_text: search_text.to_lowercase(),
+            oshi,
+            colors,
+            validity,
+            card_repairs: self.deck.card_repairs(db).len(),
+        }
+    }
+}
+
+/// Searchable information about a saved deck, computed when the list changes
+#[derive(Debug, Clone, PartialEq)]
+struct SaveSummary {
+    search_text: String,
+    oshi: Option<String>,
+    colors: Vec<hocg::Color>,
+    validity: SaveValidity,
+    card_repairs: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveValidity {
+    Valid,
+    Invalid,
+    Pile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+enum FolderFilter {
+    #[default]
+    All,
+    Unfiled,
+    Folder(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum LibrarySort {
+    #[default]
+    Newest,
+    Oldest,
+    LastSaved,
+    Name,
+}
+
+#[derive(Clone, PartialEq, Default)]
+struct LibraryFilters {
+    search: String,
+    folder: FolderFilter,
+    tag: Option<String>,
+    oshi: Option<String>,
+    color: FilterColor,
+    validity: Option<SaveValidity>,
+    sort: LibrarySort,
+}
+
+impl LibraryFilters {
+    fn is_active(&self) -> bool {
+        !self.search.trim().is_empty()
+            || self.folder != FolderFilter::All
+            || self.tag.is_some()
+            || self.oshi.is_some()
+            || self.color != FilterColor::All
+            || self.validity.is_some()
+    }
+
+    fn matches(&self, save: &SaveData, summary: &SaveSummary) -> bool {
+        let search = self.search.to_lowercase();
+        let color = match self.color {
+            FilterColor::All => None,
+            FilterColor::White => Some(hocg::Color::White),
+            FilterColor::Green => Some(hocg::Color::Green),
+            FilterColor::Red => Some(hocg::Color::Red),
+            FilterColor::Blue => Some(hocg::Color::Blue),
+            FilterColor::Purple => Some(hocg::Color::Purple),
+            FilterColor::Yellow => Some(hocg::Color::Yellow),
+            FilterColor::Colorless => Some(hocg::Color::Colorless),
+        };
+
+        search
+            .split_whitespace()
+            .all(|term| summary.search_text.contains(term))
+            && match &self.folder {
+                FolderFilter::All => true,
+                FolderFilter::Unfiled => save.folder.is_none(),
+                FolderFilter::Folder(folder) => save.folder.as_ref() == Some(folder),
+            }
+            && self.tag.as_ref().is_none_or(|tag| save.tags.contains(tag))
+            && self
+                .oshi
+                .as_ref()
+                .is_none_or(|oshi| summary.oshi.as_ref() == Some(oshi))
+            && color.is_none_or(|color| summary.colors.contains(&color))
+            && self
+                .validity
+                .is_none_or(|validity| summary.validity == validity)
+    }
+
+    fn apply(
+        &self,
+        saves: &[SavedResult],
+        summaries: &HashMap<String, SaveSummary>,
+    ) -> Vec<SavedResult> {
+        let mut saves = saves
+            .iter()
+            .filter(|save| match save {
+                SavedResult::Ok(save) => summaries
+                    .get(&save.id)
+                    .is_some_and(|summary| self.matches(save, summary)),
+                // keep the errors visible, unless filtering
+                SavedResult::Err { .. } => !self.is_active(),
+            })
+            .cloned()
+            .collect_vec();
+
+        match self.sort {
+            // already sorted by save time uuid v7
+            LibrarySort::Newest => {}
+            LibrarySort::Oldest => saves.reverse(),
+            LibrarySort::LastSaved => saves.sort_by_key(|save| {
+                Reverse(match save {
+                    SavedResult::Ok(save) => save.saved_at.parse::<Timestamp>().ok(),
+                    SavedResult::Err { .. } => None,
+                })
+            }),
+            LibrarySort::Name => saves.sort_by_key(|save| match save {
+                SavedResult::Ok(save) => (false, save.name.to_lowercase()),
+                SavedResult::Err { .. } => (true, String::new()),
+            }),
+        }
+
+        saves
+    }
+}
+
+/// Split a comma separated list of tags
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .unique()
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RevisionDiff {
+    /// The card number with the rarity and art of the printing
+    printing: String,
+    name: String,
+    change: i64,
+}
+
+/// Cards added or removed between two revisions, by printing
+fn revision_diff(
+    old: &SaveDeckOrPile,
+    new: &SaveDeckOrPile,
+    db: &CardsDatabase,
+) -> Vec<RevisionDiff> {
+    let mut changes = HashMap::<(String, Option<usize>), i64>::new();
+    for card in old.to_deck_or_pile(db).all_cards() {
+        *changes
+            .entry((card.card_number.clone(), card.illustration_idx))
+            .or_default() -= card.amount as i64;
+    }
+    for card in new.to_deck_or_pile(db).all_cards() {
+        *changes
+            .entry((card.card_number.clone(), card.illustration_idx))
+            .or_default() += card.amount as i64;
+    }
+
+    changes
+        .into_iter()
+        .filter(|(_, change)| *change != 0)
+        .sorted()
+        .map(|((card_number, illustration_idx), change)| {
+            let card = db.get(&card_number);
+            let name = card
+                .and_then(|card| card.name.english.clone().or(card.name.japanese.clone()))
+                .unwrap_or_else(|| "Unknown".into());
+            let printing = illustration_idx
+                .and_then(|idx| Some((idx, card?.illustrations.get(idx)?)))
+                .map(|(idx, illustration)| {
+                    format!("{card_number} ({}, art {})", illustration.rarity, idx + 1)
+                })
+                .unwrap_or(card_number);
+            RevisionDiff {
+                printing,
+                name,
+                change,
+            }
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -111,6 +466,16 @@ impl SaveDeckOrPile {
         }
     }
 
+    fn save_name(&self) -> String {
+        self.name()
+            .cloned()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| match self {
+                SaveDeckOrPile::Deck(_) => format!("Saved deck {}", Timestamp::now()),
+                SaveDeckOrPile::Pile(_) => format!("Saved pile {}", Timestamp::now()),
+            })
+    }
+
     fn kind(&self) -> &'static str {
         match self {
             SaveDeckOrPile::Deck(_) => "deck",
@@ -118,7 +483,81 @@ impl SaveDeckOrPile {
         }
     }
 
-    fn from_deck_or_pile(deck_or_pile: &DeckOrPile, db: &CardsDatabase) -> Self {
+    fn cards(&self) -> Box<dyn Iterator<Item = &SaveCard> + '_> {
+        match self {
+            SaveDeckOrPile::Deck(save_deck) => Box::new(
+                save_deck
+                    .oshi
+                    .iter()
+                    .chain(save_deck.main_deck.iter())
+                    .chain(save_deck.cheer_deck.iter()),
+            ),
+            SaveDeckOrPile::Pile(save_pile) => Box::new(save_pile.cards.iter()),
+        }
+    }
+
+    fn cards_mut(&mut self) -> Box<dyn Iterator<Item = &mut SaveCard> + '_> {
+        match self {
+            SaveDeckOrPile::Deck(save_deck) => Box::new(
+                save_deck
+                    .oshi
+                    .iter_mut()
+                    .chain(save_deck.main_deck.iter_mut())
+                    .chain(save_deck.cheer_deck.iter_mut()),
+            ),
+            SaveDeckOrPile::Pile(save_pile) => Box::new(save_pile.cards.iter_mut()),
+        }
+    }
+
+    /// Stores the card number and printing of the cards that can be found
+    fn backfill(&mut self, db: &CardsDatabase) {
+        for save_card in self.cards_mut() {
+            if let Some(card) = save_card.card.find_in(db) {
+                save_card
+                    .card_number
+                    .get_or_insert_with(|| card.card_number.clone());
+                if save_card.delta_art_index.is_none() {
+                    save_card.delta_art_index = card.delta_art_index;
+                }
+            }
+        }
+    }
+
+    /// The cards that cannot be found with their reference anymore
+    fn card_repairs(&self, db: &CardsDatabase) -> Vec<CardRepair> {
+        self.cards()
+            .filter_map(|save_card| {
+                let result = match save_card.resolve(db) {
+                    CardResolution::Found(_) => return None,
+                    CardResolution::Repaired {
+                        card,
+                        exact_printing,
+                    } => Some((card, exact_printing)),
+                    CardResolution::Unresolved => None,
+                };
+                Some(CardRepair {
+                    reference: save_card.describe(),
+                    amount: save_card.amount,
+                    result,
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces the outdated references with the cards found again, unresolved cards are kept as is
+    fn repaired(&self, db: &CardsDatabase) -> Self {
+        let mut repaired = self.clone();
+        for save_card in repaired.cards_mut() {
+            if let CardResolution::Repaired { card, .. } = save_card.resolve(db)
+                && let Some(new_card) = SaveCard::from_card(&card, db)
+            {
+                *save_card = new_card;
+            }
+        }
+        repaired
+    }
+
+    pub fn from_deck_or_pile(deck_or_pile: &DeckOrPile, db: &CardsDatabase) -> Self {
         match deck_or_pile {
             DeckOrPile::Deck(deck) => SaveDeckOrPile::Deck(SaveDeck::from_deck(deck, db)),
             DeckOrPile::Pile(pile) => SaveDeckOrPile::Pile(SavePileOfCards::from_pile(pile, db)),
@@ -200,16 +639,16 @@ impl SaveDeck {
             oshi: self
                 .oshi
                 .as_ref()
-                .and_then(|save_card| save_card.to_common_card(db)),
+                .map(|save_card| save_card.to_common_card_or_unknown(db)),
             main_deck: self
                 .main_deck
                 .iter()
-                .filter_map(|save_card| save_card.to_common_card(db))
+                .map(|save_card| save_card.to_common_card_or_unknown(db))
                 .collect(),
             cheer_deck: self
                 .cheer_deck
                 .iter()
-                .filter_map(|save_card| save_card.to_common_card(db))
+                .map(|save_card| save_card.to_common_card_or_unknown(db))
                 .collect(),
         }
     }
@@ -238,7 +677,7 @@ impl SavePileOfCards {
         let cards = self
             .cards
             .iter()
-            .filter_map(|save_card| save_card.to_common_card(db))
+            .map(|save_card| save_card.to_common_card_or_unknown(db))
             .collect();
         PileOfCards {
             name: self.name.clone(),
@@ -252,22 +691,117 @@ impl SavePileOfCards {
 pub struct SaveCard {
     pub card: CardReference,
     pub amount: u32,
+    // used to find the card again when the reference is outdated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_art_index: Option<u32>,
+}
+
+/// How a saved card is found in the current cards database
+enum CardResolution {
+    Found(CommonCard),
+    /// The reference is outdated, but the card was found by card number and printing
+    Repaired {
+        card: CommonCard,
+        exact_printing: bool,
+    },
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CardRepair {
+    reference: String,
+    amount: u32,
+    result: Option<(CommonCard, bool)>,
 }
 
 impl SaveCard {
     fn from_card(card: &CommonCard, db: &CardsDatabase) -> Option<Self> {
+        let Some(illustration) = card.card_illustration(db) else {
+            // an unresolved card keeps its original save, with the current amount
+            let mut save_card: Self = serde_json::from_str(card.saved_card.as_ref()?).ok()?;
+            save_card.amount = card.amount;
+            return Some(save_card);
+        };
         Some(Self {
-            card: CardIllustration::to_card_ref(card.card_illustration(db)?)?,
+            card: CardIllustration::to_card_ref(illustration)?,
             amount: card.amount,
+            card_number: Some(illustration.card_number.clone()),
+            delta_art_index: illustration.delta_art_index,
+        })
+    }
+
+    fn fallback_card_number(&self) -> Option<String> {
+        self.card_number.clone().or_else(|| {
+            // older saves only have the reference, which should include the card number
+            serde_json::to_value(&self.card)
+                .ok()?
+                .get("card_number")?
+                .as_str()
+                .map(String::from)
         })
     }
 
+    fn describe(&self) -> String {
+        match (self.fallback_card_number(), self.delta_art_index) {
+            (Some(card_number), Some(delta_art_index)) => {
+                format!("{card_number} (printing {delta_art_index})")
+            }
+            (Some(card_number), None) => card_number,
+            (None, _) => serde_json::to_string(&self.card).unwrap_or_else(|_| "Unknown".into()),
+        }
+    }
+
+    fn resolve(&self, db: &CardsDatabase) -> CardResolution {
+        if let Some(card) = self.card.find_in(db) {
+            return CardResolution::Found(CommonCard::from_card_illustration(
+                card,
+                self.amount,
+                db,
+            ));
+        }
+
+        let Some(card_number) = self.fallback_card_number() else {
+            return CardResolution::Unresolved;
+        };
+        let card = match self.delta_art_index {
+            Some(delta_art_index) => CommonCard::from_card_number_and_delta_art_index(
+                card_number,
+                delta_art_index,
+                self.amount,
+                db,
+            ),
+            None => CommonCard::from_card_number(card_number, self.amount, db),
+        };
+        let Some(illustration) = card.card_illustration(db) else {
+            return CardResolution::Unresolved;
+        };
+        let exact_printing =
+            self.delta_art_index.is_some() && illustration.delta_art_index == self.delta_art_index;
+        CardResolution::Repaired {
+            card,
+            exact_printing,
+        }
+    }
+
     fn to_common_card(&self, db: &CardsDatabase) -> Option<CommonCard> {
-        Some(CommonCard::from_card_illustration(
-            self.card.find_in(db)?,
-            self.amount,
-            db,
-        ))
+        match self.resolve(db) {
+            CardResolution::Found(card) | CardResolution::Repaired { card, .. } => Some(card),
+            CardResolution::Unresolved => None,
+        }
+    }
+
+    /// Unresolved cards are kept as unknown cards, instead of being dropped
+    fn to_common_card_or_unknown(&self, db: &CardsDatabase) -> CommonCard {
+        self.to_common_card(db).unwrap_or_else(|| CommonCard {
+            card_number: self
+                .fallback_card_number()
+                .unwrap_or_else(|| "UNKNOWN".into()),
+            illustration_idx: None,
+            amount: self.amount,
+            saved_card: serde_json::to_string(self).ok(),
+        })
     }
 }
 
@@ -361,6 +895,30 @@ async fn list_saved_decks() -> Result<Vec<SavedResult>, String> {
     Ok(values)
 }
 
+/// A deck or pile of the library, for the exports that take several decks
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryDeck {
+    pub id: String,
+    pub name: String,
+    pub deck: SaveDeckOrPile,
+}
+
+/// The saved decks that can be read, newest first
+pub async fn list_library_decks() -> Result<Vec<LibraryDeck>, String> {
+    Ok(list_saved_decks()
+        .await?
+        .into_iter()
+        .filter_map(|save| match save {
+            SavedResult::Ok(save) => Some(LibraryDeck {
+                id: save.id,
+                name: save.name,
+                deck: save.deck,
+            }),
+            SavedResult::Err { .. } => None,
+        })
+        .collect())
+}
+
 async fn save_deck(save: &SaveData) -> Result<(), String> {
     let db = open_save_db().await?;
     let transaction = db
@@ -400,70 +958,712 @@ async fn delete_saved_deck(id: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn scroll_to_top(container: &mut web_sys::Element) {
-    container.set_scroll_top(0);
+/// Writes all the saved decks in a single transaction, after deleting the `deleted` ids
+async fn put_saved_decks(saves: &[SaveData], deleted: &[String]) -> Result<(), String> {
+    let db = open_save_db().await?;
+    let transaction = db
+        .transaction(&[SAVE_STORE_NAME], TransactionMode::ReadWrite)
+        .map_err(|err| format!("Could not open database transaction: {err}"))?;
+    let store = transaction
+        .store(SAVE_STORE_NAME)
+        .map_err(|err| format!("Could not open save store: {err}"))?;
+    for id in deleted {
+        store
+            .delete(id.into())
+            .await
+            .map_err(|err| format!("Could not delete saved deck: {err}"))?;
+    }
+    for save in saves {
+        let value = to_value(save).map_err(|err| format!("Could not encode saved deck: {err}"))?;
+        store
+            .put(&value, None)
+            .await
+            .map_err(|err| format!("Could not save deck: {err}"))?;
+    }
+    transaction
+        .done()
+        .await
+        .map_err(|err| format!("Database write transaction failed: {err}"))?;
+    Ok(())
 }
 
-#[derive(Serialize)]
-struct SaveLoadEventData {
-    action: &'static str,
-    item_kind: &'static str,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
+fn is_setting_key(key: &str) -> bool {
+    key.starts_with(SETTINGS_KEY_PREFIX) && !SETTINGS_EXCLUDED_KEYS.contains(&key)
 }
 
-impl TrackEvent for SaveLoadEventData {}
+/// The settings are all the values in local storage with our prefix, except the auto-save and sync
+fn read_settings() -> BTreeMap<String, String> {
+    let Some(ls) = window().local_storage().ok().flatten() else {
+        return Default::default();
+    };
+    let len = ls.length().unwrap_or_default();
+    (0..len)
+        .filter_map(|i| ls.key(i).ok().flatten())
+        .filter(|key| is_setting_key(key))
+        .filter_map(|key| {
+            let value = ls.get_item(&key).ok().flatten()?;
+            Some((key, value))
+        })
+        .collect()
+}
 
-#[component]
-pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
-    let mut deck_error = use_signal(String::new);
-    let mut is_error_from_file = use_signal(|| false);
-    let deck_success = use_signal(String::new);
-    let mut is_loading = use_signal(|| true);
-    let mut saved_decks = use_signal(Vec::<SavedResult>::new);
-    let mut auto_save = use_signal(|| None::<SaveData>);
-    let pending_overwrite = use_signal(|| None::<String>);
-    let pending_delete = use_signal(|| None::<String>);
-    let mut container_ref = use_signal(|| None::<web_sys::Element>);
+fn write_settings(settings: &BTreeMap<String, String>, replace: bool) -> Option<()> {
+    let ls = window().local_storage().ok()??;
+    let current = read_settings();
+    if replace {
+        for key in current.keys().filter(|key| !settings.contains_key(*key)) {
+            ls.remove_item(key).ok()?;
+        }
+    }
+    for (key, value) in settings {
+        if !is_setting_key(key) {
+            continue;
+        }
+        if replace || !current.contains_key(key) {
+            ls.set_item(key, value).ok()?;
+        }
+    }
+    Some(())
+}
 
-    use_effect(move || {
-        spawn(async move {
-            match list_saved_decks().await {
-                Ok(saves) => {
-                    saved_decks.set(saves);
-                }
-                Err(err) => {
-                    *deck_error.write() = err;
+/// Full backup of the library, in a single file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct LibraryBackup {
+    format: String,
+    version: u32,
+    app_version: String,
+    exported_at: String,
+    saved_decks: Vec<SaveData>,
+    #[serde(default)]
+    auto_save: Option<SaveData>,
+    #[serde(default)]
+    settings: BTreeMap<String, String>,
+}
+
+impl LibraryBackup {
+    fn new(saved_decks: Vec<SaveData>, auto_save: Option<SaveData>) -> Self {
+        Self {
+            format: BACKUP_FORMAT.into(),
+            version: BACKUP_FORMAT_VERSION,
+            app_version: VERSION.into(),
+            exported_at: Timestamp::now().to_string(),
+            saved_decks,
+            auto_save,
+            settings: read_settings(),
+        }
+    }
+
+    fn from_file(contents: &[u8]) -> Result<Self, String> {
+        let backup: LibraryBackup = serde_json::from_slice(contents)
+            .map_err(|err| format!("Could not decode backup file: {err}"))?;
+        if backup.format != BACKUP_FORMAT {
+            return Err("This is not a library backup file.".into());
+        }
+        if backup.version > BACKUP_FORMAT_VERSION {
+            return Err(format!(
+                "This backup was made by a newer version of the app ({}).",
+                backup.app_version
+            ));
+        }
+        Ok(backup)
+    }
+
+    fn file_name() -> String {
+        format!(
+            "hocg-deck-convert.library_backup.{}.json",
+            Zoned::now().date()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+enum RestoreMode {
+    Merge,
+    Replace,
+}
+
+/// What to do when a saved deck from the backup has the same id as one in the library
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+enum RestoreConflict {
+    KeepBoth,
+    KeepNewest,
+    UseBackup,
+    UseLibrary,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RestoreReport {
+    added: usize,
+    updated: usize,
+    copied: usize,
+    skipped: usize,
+}
+
+impl std::fmt::Display for RestoreReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} kept as copies, {} unchanged or skipped",
+            self.added, self.updated, self.copied, self.skipped
+        )
+    }
+}
+
+/// Find which saved decks from the backup need to be written in the library
+fn merge_library(
+    library: &[SaveData],
+    backup: &[SaveData],
+    conflict: RestoreConflict,
+) -> (Vec<SaveData>, RestoreReport) {
+    let mut report = RestoreReport::default();
+    let mut saves = vec![];
+
+    for save in backup {
+        let Some(current) = library.iter().find(|current| current.id == save.id) else {
+            report.added += 1;
+            saves.push(save.clone());
+            continue;
+        };
+        if current == save {
+            report.skipped += 1;
+            continue;
+        }
+
+        match conflict {
+            RestoreConflict::KeepBoth => {
+                let mut copy = save.clone();
+                copy.id = uuid::Uuid::now_v7().to_string();
+                copy.name = format!("{} (restored)", save.name);
+                report.copied += 1;
+                saves.push(copy);
+            }
+            RestoreConflict::KeepNewest => {
+                let saved_at = save.saved_at.parse::<Timestamp>().ok();
+                let current_saved_at = current.saved_at.parse::<Timestamp>().ok();
+                if saved_at > current_saved_at {
+                    report.updated += 1;
+                    saves.push(save.clone());
+                } else {
+                    report.skipped += 1;
                 }
             }
-            is_loading.set(false);
-        });
-    });
-
-    // Create a debounce that waits 500ms after the last edit before executing
-    let mut debounced_save =
-        use_debounce(Duration::from_millis(AUTO_SAVE_DEBOUNCE_MS), move |()| {
-            if let Some(auto_save) = auto_save.read().as_ref() {
-                auto_save_deck(auto_save);
+            RestoreConflict::UseBackup => {
+                report.updated += 1;
+                saves.push(save.clone());
             }
-        });
-    use_effect(move || {
-        if let Some(deck) = AUTO_SAVE_DECK.read().as_ref()
-            && let save = SaveData::from_deck_or_pile(deck.clone(), &db.read())
-            && !save.to_deck_or_pile(&db.read()).is_empty()
-        {
-            *auto_save.write() = Some(save);
-            debounced_save.action(());
+            RestoreConflict::UseLibrary => report.skipped += 1,
         }
-    });
-    // first auto-save load
-    use_effect(move || {
-        let save = load_auto_saved_deck();
-        *AUTO_SAVE_DECK.write() = save
-            .as_ref()
-            .map(|save_data| save_data.to_deck_or_pile(&db.read()));
-        *auto_save.write() = save;
-    });
+    }
+
+    (saves, report)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct SyncSettings {
+    url: String,
+    #[serde(default)]
+    username: String,
+    /// Only kept for the session, it's asked again after a reload
+    #[serde(skip)]
+    password: String,
+}
+
+impl SyncSettings {
+    fn load() -> Self {
+        let Some(json) = window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|ls| ls.get_item(SYNC_SETTINGS_KEY).ok().flatten())
+        else {
+            return Default::default();
+        };
+        let settings: Self = serde_json::from_str(&json).unwrap_or_default();
+        // older versions stored the password, remove it from local storage
+        if json.contains("\"password\"") {
+            settings.store();
+        }
+        settings
+    }
+
+    fn store(&self) -> Option<()> {
+        let ls = window().local_storage().ok()??;
+        let json = serde_json::to_string(self).ok()?;
+        ls.set_item(SYNC_SETTINGS_KEY, &json).ok()
+    }
+
+    fn backend(&self) -> HttpSyncBackend {
+        let non_empty = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        HttpSyncBackend::new(
+            self.url.trim().to_string(),
+            non_empty(&self.username),
+            non_empty(&self.password),
+        )
+    }
+}
+
+/// The saved decks as of the last sync, used to find what changed on each side
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct SyncState {
+    /// The sync location, another one starts over
+    #[serde(default)]
+    url: String,
+    last_synced_at: Option<String>,
+    saved_at: BTreeMap<String, String>,
+}
+
+impl SyncState {
+    fn load(url: &str) -> Self {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|ls| ls.get_item(SYNC_STATE_KEY).ok().flatten())
+            .and_then(|json| serde_json::from_str::<SyncState>(&json).ok())
+            .filter(|state| state.url == url)
+            .unwrap_or_default()
+    }
+
+    fn store(&self) -> Option<()> {
+        let ls = window().local_storage().ok()??;
+        let json = serde_json::to_string(self).ok()?;
+        ls.set_item(SYNC_STATE_KEY, &json).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SyncReport {
+    pulled: usize,
+    pushed: usize,
+    deleted: usize,
+    conflicts: usize,
+}
+
+impl std::fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} downloaded, {} uploaded, {} deleted, {} conflicts",
+            self.pulled, self.pushed, self.deleted, self.conflicts
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SyncPlan {
+    local_puts: Vec<SaveData>,
+    local_deletes: Vec<String>,
+    remote: Vec<SaveData>,
+    remote_changed: bool,
+    report: SyncReport,
+}
+
+/// Two-way merge of the saved decks. When both sides changed, the last saved wins
+/// and the other one is kept as a conflict copy. Without a remote file, nothing was
+/// deleted on another device and all the saved decks are uploaded.
+fn plan_sync(local: &[SaveData], remote: Option<&[SaveData]>, state: &SyncState) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+    let never_synced = SyncState::default();
+    let (remote, state) = match remote {
+        Some(remote) => (remote, state),
+        None => (&[][..], &never_synced),
+    };
+    let conflict_copy = |save: &SaveData| {
+        let mut copy = save.clone();
+        copy.id = uuid::Uuid::now_v7().to_string();
+        copy.name = format!("{} (conflict copy)", save.name);
+        copy
+    };
+
+    let ids = local
+        .iter()
+        .chain(remote)
+        .map(|save| save.id.clone())
+        .unique()
+        .sorted()
+        .collect_vec();
+    for id in ids {
+        let base = state.saved_at.get(&id);
+        let local = local.iter().find(|save| save.id == id);
+        let remote = remote.iter().find(|save| save.id == id);
+
+        match (local, remote) {
+            (Some(local), Some(remote)) if local == remote => plan.remote.push(local.clone()),
+            (Some(local), Some(remote)) => {
+                let local_changed = base != Some(&local.saved_at);
+                let remote_changed = base != Some(&remote.saved_at);
+                match (local_changed, remote_changed) {
+                    (false, true) => {
+                        plan.local_puts.push(remote.clone());
+                        plan.remote.push(remote.clone());
+                        plan.report.pulled += 1;
+                    }
+                    (true, false) => {
+                        plan.remote.push(local.clone());
+                        plan.remote_changed = true;
+                        plan.report.pushed += 1;
+                    }
+                    _ => {
+                        let local_wins = local.saved_at.parse::<Timestamp>().ok()
+                            >= remote.saved_at.parse::<Timestamp>().ok();
+                        let (winner, loser) = if local_wins {
+                            (local, remote)
+                        } else {
+                            (remote, local)
+                        };
+                        let copy = conflict_copy(loser);
+                        if !local_wins {
+                            plan.local_puts.push(winner.clone());
+                        }
+                        plan.local_puts.push(copy.clone());
+                        plan.remote.push(winner.clone());
+                        plan.remote.push(copy);
+                        plan.remote_changed = true;
+                        plan.report.conflicts += 1;
+                    }
+                }
+            }
+            (Some(local), None) => {
+                if base == Some(&local.saved_at) {
+                    // deleted on another device
+                    plan.local_deletes.push(id);
+                    plan.report.deleted += 1;
+                } else {
+                    plan.remote.push(local.clone());
+                    plan.remote_changed = true;
+                    plan.report.pushed += 1;
+                }
+            }
+            (None, Some(remote)) => {
+                if base == Some(&remote.saved_at) {
+                    // deleted on this device
+                    plan.remote_changed = true;
+                    plan.report.deleted += 1;
+                } else {
+                    plan.local_puts.push(remote.clone());
+                    plan.remote.push(remote.clone());
+                    plan.report.pulled += 1;
+                }
+            }
+            (None, None) => unreachable!("the id comes from one of the sides"),
+        }
+    }
+
+    plan
+}
+
+async fn sync_library(backend: &impl SyncBackend) -> Result<SyncReport, String> {
+    let remote_file = backend.pull().await?;
+    let remote = match &remote_file {
+        Some(file) => Some(LibraryBackup::from_file(&file.contents)?.saved_decks),
+        None => None,
+    };
+    let local = list_saved_decks()
+        .await?
+        .into_iter()
+        .filter_map(|save| match save {
+            SavedResult::Ok(save) => Some(save),
+            SavedResult::Err { .. } => None,
+        })
+        .collect_vec();
+
+    let plan = plan_sync(
+        &local,
+        remote.as_deref(),
+        &SyncState::load(backend.location()),
+    );
+
+    // upload first, so nothing changes locally if another device synced in between
+    if plan.remote_changed || remote_file.is_none() {
+        let document = LibraryBackup {
+            settings: Default::default(),
+            ..LibraryBackup::new(plan.remote.clone(), None)
+        };
+        let contents = serde_json::to_vec_pretty(&document)
+            .map_err(|err| format!("Could not encode the library: {err}"))?;
+        backend.push(contents, remote_file.as_ref()).await?;
+    }
+    put_saved_decks(&plan.local_puts, &plan.local_deletes).await?;
+
+    SyncState {
+        url: backend.location().to_string(),
+        last_synced_at: Some(Timestamp::now().to_string()),
+        saved_at: plan
+            .remote
+            .iter()
+            .map(|save| (save.id.clone(), save.saved_at.clone()))
+            .collect(),
+    }
+    .store();
+
+    Ok(plan.report)
+}
+
+/// Unknown cards have no reference, so they are not kept in the save, unless they were loaded from one
+fn unsaved_cards_warning(deck: &DeckOrPile, db: &CardsDatabase) -> Option<String> {
+    let count: u32 = deck
+        .all_cards()
+        .filter(|card| card.card_illustration(db).is_none() && card.saved_card.is_none())
+        .map(|card| card.amount)
+        .sum();
+    (count > 0).then(|| format!("{count} unknown cards could not be saved."))
+}
+
+fn scroll_to_top(container: &mut web_sys::Element) {
+    container.set_scroll_top(0);
+}
+
+#[derive(Serialize)]
+struct SaveLoadEventData {
+    action: &'static str,
+    item_kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl TrackEvent for SaveLoadEventData {}
+
+#[component]
+pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
+    let mut deck_error = use_signal(String::new);
+    let mut is_error_from_file = use_signal(|| false);
+    let deck_success = use_signal(String::new);
+    let mut is_loading = use_signal(|| true);
+    let mut saved_decks = use_signal(Vec::<SavedResult>::new);
+    let mut auto_save = use_signal(|| None::<SaveData>);
+    let pending_overwrite = use_signal(|| None::<String>);
+    let pending_delete = use_signal(|| None::<String>);
+    let mut container_ref = use_signal(|| None::<web_sys::Element>);
+    let mut filters = use_signal(LibraryFilters::default);
+    let mut editing = use_signal(|| None::<String>);
+    let mut edit_folder = use_signal(String::new);
+    let mut edit_tags = use_signal(String::new);
+    let mut change_note = use_signal(String::new);
+    let mut history_open = use_signal(|| None::<String>);
+    let mut history_diff = use_signal(|| None::<(String, usize)>);
+    let mut repair_open = use_signal(|| None::<String>);
+
+    let summaries = use_memo(move || {
+        let card_lang = *PREVIEW_CARD_LANG.read();
+        saved_decks
+            .read()
+            .iter()
+            .filter_map(|save| match save {
+                SavedResult::Ok(save) => {
+                    Some((save.id.clone(), save.summary(&db.read(), card_lang)))
+                }
+                SavedResult::Err { .. } => None,
+            })
+            .collect::<HashMap<_, _>>()
+    });
+    let loaded_save = use_memo(move || {
+        let id = LOADED_SAVE_ID.read().clone()?;
+        saved_decks.read().iter().find_map(|save| match save {
+            SavedResult::Ok(save) if save.id == id => Some(save.clone()),
+            _ => None,
+        })
+    });
+    let visible_saves =
+        use_memo(move || filters.read().apply(&saved_decks.read(), &summaries.read()));
+    let folders = use_memo(move || {
+        saved_decks
+            .read()
+            .iter()
+            .filter_map(|save| match save {
+                SavedResult::Ok(save) => save.folder.clone(),
+                SavedResult::Err { .. } => None,
+            })
+            .unique()
+            .sorted_by_key(|folder| folder.to_lowercase())
+            .collect_vec()
+    });
+    let tags = use_memo(move || {
+        saved_decks
+            .read()
+            .iter()
+            .flat_map(|save| match save {
+                SavedResult::Ok(save) => save.tags.clone(),
+                SavedResult::Err { .. } => vec![],
+            })
+            .unique()
+            .sorted_by_key(|tag| tag.to_lowercase())
+            .collect_vec()
+    });
+    let oshis = use_memo(move || {
+        let db = db.read();
+        summaries
+            .read()
+            .values()
+            .filter_map(|summary| summary.oshi.clone())
+            .unique()
+            .sorted()
+            .map(|card_number| {
+                let name = db
+                    .get(&card_number)
+                    .and_then(|card| card.name.english.clone().or(card.name.japanese.clone()))
+                    .unwrap_or_else(|| "Unknown".into());
+                (card_number, name)
+            })
+            .collect_vec()
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            match list_saved_decks().await {
+                Ok(saves) => {
+                    saved_decks.set(saves);
+                }
+                Err(err) => {
+                    *deck_error.write() = err;
+                }
+            }
+            is_loading.set(false);
+        });
+    });
+
+    // upgrade the saved decks to the current schema, once the cards are loaded
+    use_effect(move || {
+        if db.read().is_empty() {
+            return;
+        }
+        let migrated = saved_decks
+            .read()
+            .iter()
+            .filter_map(|save| match save {
+                SavedResult::Ok(save) if save.needs_migration() => {
+                    Some(save.clone().migrate(&db.read()))
+                }
+                _ => None,
+            })
+            .collect_vec();
+        if migrated.is_empty() {
+            return;
+        }
+        spawn(async move {
+            match put_saved_decks(&migrated, &[]).await {
+                Ok(_) => {
+                    for new_save in migrated {
+                        if let Some(save) = saved_decks
+                            .write()
+                            .iter_mut()
+                            .find(|save| save.id() == new_save.id)
+                        {
+                            *save = SavedResult::Ok(new_save);
+                        }
+                    }
+                }
+                Err(err) => {
+                    *deck_error.write() = err;
+                }
+            }
+        });
+    });
+
+    // Create a debounce that waits 500ms after the last edit before executing
+    let mut debounced_save =
+        use_debounce(Duration::from_millis(AUTO_SAVE_DEBOUNCE_MS), move |()| {
+            if let Some(auto_save) = auto_save.read().as_ref() {
+                auto_save_deck(auto_save);
+            }
+        });
+    use_effect(move || {
+        if let Some(deck) = AUTO_SAVE_DECK.read().as_ref()
+            // keep the unresolved cards until the deck is changed
+            && auto_save
+                .peek()
+                .as_ref()
+                .is_none_or(|save| save.to_deck_or_pile(&db.read()) != *deck)
+            && let save = SaveData::from_deck_or_pile(deck.clone(), &db.read())
+            && !save.to_deck_or_pile(&db.read()).is_empty()
+        {
+            *auto_save.write() = Some(save);
+            debounced_save.action(());
+        }
+    });
+    // first auto-save load
+    use_effect(move || {
+        let save = load_auto_saved_deck().map(|save| {
+            if save.needs_migration() && !db.read().is_empty() {
+                let save = save.migrate(&db.read());
+                auto_save_deck(&save);
+                save
+            } else {
+                save
+            }
+        });
+        *AUTO_SAVE_DECK.write() = save
+            .as_ref()
+            .map(|save_data| save_data.to_deck_or_pile(&db.read()));
+        *auto_save.write() = save;
+    });
+
+    // replace the saved deck, the previous state is kept in its history
+    let update_saved_deck = move |new_save: SaveData,
+                                  action: &'static str,
+                                  success: String,
+                                  warning: Option<String>| {
+        let mut deck_error = deck_error;
+        let mut deck_success = deck_success;
+        let mut pending_overwrite = pending_overwrite;
+        let mut pending_delete = pending_delete;
+        let mut saved_decks = saved_decks;
+        spawn(async move {
+            *deck_error.write() = String::new();
+            is_error_from_file.set(false);
+            *deck_success.write() = String::new();
+            pending_overwrite.set(None);
+            pending_delete.set(None);
+            match save_deck(&new_save).await {
+                Ok(_) => {
+                    if let Some(save) = saved_decks
+                        .write()
+                        .iter_mut()
+                        .find(|save| save.id() == new_save.id)
+                    {
+                        *save = SavedResult::Ok(new_save.clone());
+                    }
+                    change_note.set(String::new());
+                    history_diff.set(None);
+                    *deck_success.write() = success;
+                    if let Some(warning) = warning {
+                        *deck_error.write() = warning;
+                    }
+                    track_event(
+                        EventType::SaveLoad,
+                        SaveLoadEventData {
+                            action,
+                            item_kind: new_save.deck.kind(),
+                            error: None,
+                        },
+                    );
+                }
+                Err(err) => {
+                    track_event(
+                        EventType::SaveLoad,
+                        SaveLoadEventData {
+                            action,
+                            item_kind: new_save.deck.kind(),
+                            error: Some(err.clone()),
+                        },
+                    );
+                    *deck_error.write() = err;
+                }
+            }
+        });
+    };
+
+    let save_revision = move |save: &SaveData| {
+        let deck = SaveDeckOrPile::from_deck_or_pile(&common_deck.read(), &db.read());
+        let note = Some(change_note.read().trim().to_string()).filter(|note| !note.is_empty());
+        let new_save = save.with_new_revision(deck, note);
+        *LOADED_SAVE_ID.write() = Some(new_save.id.clone());
+        let success = format!("Saved a new revision of '{}'.", new_save.name);
+        let warning = unsaved_cards_warning(&common_deck.read(), &db.read());
+        update_saved_deck(new_save, "Save revision", success, warning);
+    };
 
     let save_to_browser = move |_| {
         let mut deck_error = deck_error;
@@ -472,7 +1672,12 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
         let mut pending_delete = pending_delete;
         let mut saved_decks = saved_decks;
         let mut container_ref = container_ref;
-        let save = SaveData::from_deck_or_pile(common_deck.read().clone(), &db.read());
+        let mut save = SaveData::from_deck_or_pile(common_deck.read().clone(), &db.read());
+        let warning = unsaved_cards_warning(&common_deck.read(), &db.read());
+        // save directly in the folder being viewed
+        if let FolderFilter::Folder(folder) = &filters.read().folder {
+            save.folder = Some(folder.clone());
+        }
         spawn(async move {
             *deck_error.write() = String::new();
             is_error_from_file.set(false);
@@ -485,7 +1690,11 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                     if let Some(container) = container_ref.write().as_mut() {
                         scroll_to_top(container);
                     }
+                    *LOADED_SAVE_ID.write() = Some(save.id.clone());
                     *deck_success.write() = format!("Saved '{}'.", save.name);
+                    if let Some(warning) = warning {
+                        *deck_error.write() = warning;
+                    }
                     track_event(
                         EventType::SaveLoad,
                         SaveLoadEventData {
@@ -529,7 +1738,8 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
             for file in &files {
                 match file.read_bytes().await {
                     Ok(contents) => match serde_json::from_slice::<SaveDeckOrPile>(&contents) {
-                        Ok(save_deck_or_pile) => {
+                        Ok(mut save_deck_or_pile) => {
+                            save_deck_or_pile.backfill(&db.read());
                             let save = SaveData::new(save_deck_or_pile);
                             match save_deck(&save).await {
                                 Ok(_) => {
@@ -652,16 +1862,38 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
         }
 
         div { class: "field is-grouped is-grouped-multiline is-justify-content-center",
+            if let Some(save) = loaded_save.read().as_ref() {
+                div { class: "control",
+                    button {
+                        class: "button is-link",
+                        r#type: "button",
+                        title: "Save the current deck as a new revision of '{save.name}'",
+                        onclick: {
+                            let save = save.clone();
+                            move |_| save_revision(&save)
+                        },
+                        disabled: *is_loading.read() || common_deck.read().is_empty(),
+                        span { class: "icon",
+                            i { class: "fa-solid fa-clock-rotate-left" }
+                        }
+                        span { "Save new revision" }
+                    }
+                }
+            }
             div { class: "control",
                 button {
-                    class: "button is-link",
+                    class: if loaded_save.read().is_some() { "button" } else { "button is-link" },
                     r#type: "button",
                     onclick: save_to_browser,
                     disabled: *is_loading.read() || common_deck.read().is_empty(),
                     span { class: "icon",
                         i { class: "fa-solid fa-floppy-disk" }
                     }
-                    span { "Save current deck" }
+                    if loaded_save.read().is_some() {
+                        span { "Save as new deck" }
+                    } else {
+                        span { "Save current deck" }
+                    }
                 }
             }
             div { class: "control",
@@ -685,6 +1917,22 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
             }
         }
 
+        if let Some(save) = loaded_save.read().as_ref() {
+            div { class: "field",
+                div { class: "control",
+                    input {
+                        id: "saved_deck_change_note",
+                        class: "input",
+                        r#type: "text",
+                        maxlength: 200,
+                        placeholder: "Change note for the new revision of '{save.name}' (optional)",
+                        value: "{change_note}",
+                        oninput: move |ev| change_note.set(ev.value()),
+                    }
+                }
+            }
+        }
+
         // p { class: "help is-success content", "{deck_success}" }
         p { class: "help is-danger content", "{deck_error}" }
         if is_error_from_file.read().to_owned() {
@@ -812,51 +2060,347 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
             div {
                 p { class: "mt-3 mb-2",
                     "Saved decks"
-                    if !saved_decks.read().is_empty() {
+                    if filters.read().is_active() {
+                        " ({visible_saves.read().len()} of {saved_decks.read().len()})"
+                    } else {
                         " ({saved_decks.read().len()})"
                     }
                 }
             }
-            div {
-                class: "fixed-grid has-1-cols",
-                style: "max-height: 65vh; overflow: scroll;",
-                onmounted: move |elem| {
-                    *container_ref.write() = Some(elem.as_web_event());
-                },
+
+            // search and filters
+            div { class: "field",
+                div { class: "control has-icons-left",
+                    input {
+                        id: "saved_deck_search",
+                        class: "input",
+                        r#type: "search",
+                        maxlength: 100,
+                        placeholder: "Search by deck, folder, tag or card... (e.g. Tokino Sora, hSD01-001, etc.)",
+                        value: "{filters.read().search}",
+                        oninput: move |ev| {
+                            filters.write().search = ev.value();
+                            if let Some(container) = container_ref.write().as_mut() {
+                                scroll_to_top(container);
+                            }
+                        },
+                    }
+                    span { class: "icon is-left",
+                        i { class: "fa-solid fa-magnifying-glass" }
+                    }
+                }
+            }
+            div { class: "fixed-grid has-3-cols-tablet has-2-cols-mobile",
                 div { class: "grid",
-                    for save in saved_decks.read().iter() {
-                        div {
-                            class: "cell",
-                            style: "transition: background-color 0.2s;",
-                            if let SavedResult::Err { id, error } = &save {
-                                article { class: "message is-small is-danger",
-                                    div { class: "message-body",
-                                        div { class: "is-flex is-justify-content-end is-align-items-center is-flex-wrap-wrap is-gap-2",
-                                            div {
-                                                class: "is-flex is-align-items-center is-gap-2 is-flex-grow-1",
-                                                style: "flex: 1 1 14.75rem; min-width: 0;",
-                                                div {
-                                                    div { class: "is-flex is-align-items-center is-gap-2",
-                                                        span { class: "icon",
-                                                            i { class: "fa-solid fa-lg fa-triangle-exclamation" }
-                                                        }
-                                                        p { "Error loading this saved deck: " }
-                                                    }
-                                                    p { class: "is-size-7 has-text-grey",
-                                                        "{error}"
-                                                    }
-                                                }
+                    // Folder
+                    div { class: "cell",
+                        label { "for": "saved_deck_folder", class: "label", "Folder" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_folder",
+                                    oninput: move |ev| {
+                                        filters.write().folder = match ev.value().as_str() {
+                                            "all" => FolderFilter::All,
+                                            "unfiled" => FolderFilter::Unfiled,
+                                            folder => {
+                                                FolderFilter::Folder(
+                                                    folder.trim_start_matches("folder:").to_string(),
+                                                )
                                             }
-                                            div {
-                                                class: "buttons are-small is-flex-wrap-nowrap",
-                                                style: "flex: 0 0 auto; margin-bottom: 0; gap: 0.25rem;",
-                                                button {
-                                                    class: "button is-danger",
-                                                    r#type: "button",
-                                                    title: "Delete this entry",
-                                                    aria_label: "Delete this error entry",
-                                                    onclick: {
-                                                        let id = id.clone();
+                                        };
+                                    },
+                                    option {
+                                        value: "all",
+                                        selected: filters.read().folder == FolderFilter::All,
+                                        "All"
+                                    }
+                                    option {
+                                        value: "unfiled",
+                                        selected: filters.read().folder == FolderFilter::Unfiled,
+                                        "No folder"
+                                    }
+                                    for folder in folders.read().iter() {
+                                        option {
+                                            value: "folder:{folder}",
+                                            selected: filters.read().folder == FolderFilter::Folder(folder.clone()),
+                                            "{folder}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Tag
+                    div { class: "cell",
+                        label { "for": "saved_deck_tag", class: "label", "Tag" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_tag",
+                                    oninput: move |ev| {
+                                        filters.write().tag = ev
+                                            .value()
+                                            .strip_prefix("tag:")
+                                            .map(|tag| tag.to_string());
+                                    },
+                                    option {
+                                        value: "all",
+                                        selected: filters.read().tag.is_none(),
+                                        "All"
+                                    }
+                                    for tag in tags.read().iter() {
+                                        option {
+                                            value: "tag:{tag}",
+                                            selected: filters.read().tag.as_ref() == Some(tag),
+                                            "{tag}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Oshi
+                    div { class: "cell",
+                        label { "for": "saved_deck_oshi", class: "label", "Oshi" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_oshi",
+                                    oninput: move |ev| {
+                                        filters.write().oshi = ev
+                                            .value()
+                                            .strip_prefix("oshi:")
+                                            .map(|oshi| oshi.to_string());
+                                    },
+                                    option {
+                                        value: "all",
+                                        selected: filters.read().oshi.is_none(),
+                                        "All"
+                                    }
+                                    for (card_number , name) in oshis.read().iter() {
+                                        option {
+                                            value: "oshi:{card_number}",
+                                            selected: filters.read().oshi.as_ref() == Some(card_number),
+                                            "{card_number} - {name}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Color
+                    div { class: "cell",
+                        label { "for": "saved_deck_color", class: "label", "Color" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_color",
+                                    oninput: move |ev| {
+                                        filters.write().color = match ev.value().as_str() {
+                                            "white" => FilterColor::White,
+                                            "green" => FilterColor::Green,
+                                            "red" => FilterColor::Red,
+                                            "blue" => FilterColor::Blue,
+                                            "purple" => FilterColor::Purple,
+                                            "yellow" => FilterColor::Yellow,
+                                            "colorless" => FilterColor::Colorless,
+                                            _ => FilterColor::All,
+                                        };
+                                    },
+                                    option {
+                                        value: "all",
+                                        selected: filters.read().color == FilterColor::All,
+                                        "All"
+                                    }
+                                    option {
+                                        value: "white",
+                                        selected: filters.read().color == FilterColor::White,
+                                        "White"
+                                    }
+                                    option {
+                                        value: "green",
+                                        selected: filters.read().color == FilterColor::Green,
+                                        "Green"
+                                    }
+                                    option {
+                                        value: "red",
+                                        selected: filters.read().color == FilterColor::Red,
+                                        "Red"
+                                    }
+                                    option {
+                                        value: "blue",
+                                        selected: filters.read().color == FilterColor::Blue,
+                                        "Blue"
+                                    }
+                                    option {
+                                        value: "purple",
+                                        selected: filters.read().color == FilterColor::Purple,
+                                        "Purple"
+                                    }
+                                    option {
+                                        value: "yellow",
+                                        selected: filters.read().color == FilterColor::Yellow,
+                                        "Yellow"
+                                    }
+                                    option {
+                                        value: "colorless",
+                                        selected: filters.read().color == FilterColor::Colorless,
+                                        "Colorless"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Validity
+                    div { class: "cell",
+                        label { "for": "saved_deck_validity", class: "label", "Validity" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_validity",
+                                    oninput: move |ev| {
+                                        filters.write().validity = match ev.value().as_str() {
+                                            "valid" => Some(SaveValidity::Valid),
+                                            "invalid" => Some(SaveValidity::Invalid),
+                                            "pile" => Some(SaveValidity::Pile),
+                                            _ => None,
+                                        };
+                                    },
+                                    option {
+                                        value: "all",
+                                        selected: filters.read().validity.is_none(),
+                                        "All"
+                                    }
+                                    option {
+                                        value: "valid",
+                                        selected: filters.read().validity == Some(SaveValidity::Valid),
+                                        "Valid decks"
+                                    }
+                                    option {
+                                        value: "invalid",
+                                        selected: filters.read().validity == Some(SaveValidity::Invalid),
+                                        "Decks with warnings"
+                                    }
+                                    option {
+                                        value: "pile",
+                                        selected: filters.read().validity == Some(SaveValidity::Pile),
+                                        "Piles of cards"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Sort
+                    div { class: "cell",
+                        label { "for": "saved_deck_sort", class: "label", "Sort by" }
+                        div { class: "control",
+                            div { class: "select is-fullwidth",
+                                select {
+                                    id: "saved_deck_sort",
+                                    oninput: move |ev| {
+                                        filters.write().sort = match ev.value().as_str() {
+                                            "oldest" => LibrarySort::Oldest,
+                                            "last_saved" => LibrarySort::LastSaved,
+                                            "name" => LibrarySort::Name,
+                                            _ => LibrarySort::Newest,
+                                        };
+                                        if let Some(container) = container_ref.write().as_mut() {
+                                            scroll_to_top(container);
+                                        }
+                                    },
+                                    option {
+                                        value: "newest",
+                                        selected: filters.read().sort == LibrarySort::Newest,
+                                        "Newest first"
+                                    }
+                                    option {
+                                        value: "oldest",
+                                        selected: filters.read().sort == LibrarySort::Oldest,
+                                        "Oldest first"
+                                    }
+                                    option {
+                                        value: "last_saved",
+                                        selected: filters.read().sort == LibrarySort::LastSaved,
+                                        "Last saved"
+                                    }
+                                    option {
+                                        value: "name",
+                                        selected: filters.read().sort == LibrarySort::Name,
+                                        "Name"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if filters.read().is_active() {
+                div { class: "block",
+                    a {
+                        href: "#",
+                        role: "button",
+                        onclick: move |evt| {
+                            evt.prevent_default();
+                            let sort = filters.read().sort;
+                            *filters.write() = LibraryFilters {
+                                sort,
+                                ..Default::default()
+                            };
+                        },
+                        span { class: "icon",
+                            i { class: "fa-solid fa-xmark" }
+                        }
+                        "Clear filters"
+                    }
+                }
+            }
+
+            if visible_saves.read().is_empty() {
+                div { class: "notification",
+                    p { "No saved decks match the search." }
+                }
+            }
+            div {
+                class: "fixed-grid has-1-cols",
+                style: "max-height: 65vh; overflow: scroll;",
+                onmounted: move |elem| {
+                    *container_ref.write() = Some(elem.as_web_event());
+                },
+                div { class: "grid",
+                    for save in visible_saves.read().iter() {
+                        div {
+                            class: "cell",
+                            style: "transition: background-color 0.2s;",
+                            if let SavedResult::Err { id, error } = &save {
+                                article { class: "message is-small is-danger",
+                                    div { class: "message-body",
+                                        div { class: "is-flex is-justify-content-end is-align-items-center is-flex-wrap-wrap is-gap-2",
+                                            div {
+                                                class: "is-flex is-align-items-center is-gap-2 is-flex-grow-1",
+                                                style: "flex: 1 1 14.75rem; min-width: 0;",
+                                                div {
+                                                    div { class: "is-flex is-align-items-center is-gap-2",
+                                                        span { class: "icon",
+                                                            i { class: "fa-solid fa-lg fa-triangle-exclamation" }
+                                                        }
+                                                        p { "Error loading this saved deck: " }
+                                                    }
+                                                    p { class: "is-size-7 has-text-grey",
+                                                        "{error}"
+                                                    }
+                                                }
+                                            }
+                                            div {
+                                                class: "buttons are-small is-flex-wrap-nowrap",
+                                                style: "flex: 0 0 auto; margin-bottom: 0; gap: 0.25rem;",
+                                                button {
+                                                    class: "button is-danger",
+                                                    r#type: "button",
+                                                    title: "Delete this entry",
+                                                    aria_label: "Delete this error entry",
+                                                    onclick: {
+                                                        let id = id.clone();
                                                         let deck_error = deck_error;
                                                         let saved_decks = saved_decks;
                                                         move |_| {
@@ -928,9 +2472,57 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                     p { class: "has-text-weight-semibold",
                                                         "{save.name}"
                                                     }
+                                                    if save.folder.is_some() || !save.tags.is_empty() {
+                                                        div { class: "tags mb-0",
+                                                            if let Some(folder) = &save.folder {
+                                                                a {
+                                                                    class: "tag is-info is-light mb-0",
+                                                                    href: "#",
+                                                                    role: "button",
+                                                                    title: "Show this folder",
+                                                                    onclick: {
+                                                                        let folder = folder.clone();
+                                                                        move |evt: Event<MouseData>| {
+                                                                            evt.prevent_default();
+                                                                            filters.write().folder = FolderFilter::Folder(folder.clone());
+                                                                        }
+                                                                    },
+                                                                    span { class: "icon",
+                                                                        i { class: "fa-solid fa-folder" }
+                                                                    }
+                                                                    span { "{folder}" }
+                                                                }
+                                                            }
+                                                            for tag in save.tags.iter() {
+                                                                a {
+                                                                    class: "tag mb-0",
+                                                                    href: "#",
+                                                                    role: "button",
+                                                                    title: "Show this tag",
+                                                                    onclick: {
+                                                                        let tag = tag.clone();
+                                                                        move |evt: Event<MouseData>| {
+                                                                            evt.prevent_default();
+                                                                            filters.write().tag = Some(tag.clone());
+                                                                        }
+                                                                    },
+                                                                    "{tag}"
+                                                                }
+                                                            }
+                                                        }
+                                                    }
                                                     if pending_overwrite.read().as_ref() == Some(&save.id) {
                                                         p { class: "is-size-7 has-text-warning",
-                                                            "Click save again to overwrite."
+                                                            "Click save again to save a new revision."
+                                                        }
+                                                        input {
+                                                            class: "input is-small mt-1",
+                                                            r#type: "text",
+                                                            maxlength: 200,
+                                                            placeholder: "Change note (optional)",
+                                                            aria_label: "Change note",
+                                                            value: "{change_note}",
+                                                            oninput: move |ev| change_note.set(ev.value()),
                                                         }
                                                     } else if pending_delete.read().as_ref() == Some(&save.id) {
                                                         p { class: "is-size-7 has-text-danger",
@@ -939,6 +2531,48 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                     } else {
                                                         p { class: "is-size-7 has-text-grey",
                                                             "Saved at {format_datetime(&save.saved_at)}"
+                                                            if !save.revisions.is_empty() {
+                                                                " · {save.revisions.len() + 1} revisions"
+                                                            }
+                                                            if LOADED_SAVE_ID.read().as_ref() == Some(&save.id) {
+                                                                " · loaded"
+                                                            }
+                                                        }
+                                                        if let Some(note) = &save.note {
+                                                            p { class: "is-size-7 has-text-grey is-italic",
+                                                                "{note}"
+                                                            }
+                                                        }
+                                                    }
+                                                    if save.is_from_newer_version() {
+                                                        p { class: "is-size-7 has-text-warning-dark",
+                                                            "Saved with a newer version of the app ({save.app_version}), saving it again may lose some data."
+                                                        }
+                                                    }
+                                                    if let Some(card_repairs) = summaries
+                                                        .read()
+                                                        .get(&save.id)
+                                                        .map(|summary| summary.card_repairs)
+                                                        .filter(|count| *count > 0)
+                                                    {
+                                                        p { class: "is-size-7 has-text-warning-dark",
+                                                            "{card_repairs} cards have an outdated reference. "
+                                                            a {
+                                                                href: "#",
+                                                                role: "button",
+                                                                onclick: {
+                                                                    let id = save.id.clone();
+                                                                    move |evt: Event<MouseData>| {
+                                                                        evt.prevent_default();
+                                                                        if repair_open.read().as_ref() == Some(&id) {
+                                                                            repair_open.set(None);
+                                                                        } else {
+                                                                            repair_open.set(Some(id.clone()));
+                                                                        }
+                                                                    }
+                                                                },
+                                                                "Repair..."
+                                                            }
                                                         }
                                                     }
                                                 }
@@ -965,6 +2599,7 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                             pending_overwrite.set(None);
                                                             pending_delete.set(None);
                                                             *common_deck.write() = save.to_deck_or_pile(&db.read());
+                                                            *LOADED_SAVE_ID.write() = Some(save.id.clone());
                                                             *deck_success.write() = format!("Loaded '{}'.", save.name);
                                                             track_event(
                                                                 EventType::SaveLoad,
@@ -985,70 +2620,19 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                 button {
                                                     class: if pending_overwrite.read().as_ref() == Some(&save.id) { "button is-success" } else { "button" },
                                                     r#type: "button",
-                                                    title: if pending_overwrite.read().as_ref() == Some(&save.id) { "Click again to overwrite this deck" } else { "Overwrite this deck" },
-                                                    aria_label: format!("Overwrite saved deck '{}'", save.name),
+                                                    title: if pending_overwrite.read().as_ref() == Some(&save.id) { "Click again to save a new revision of this deck" } else { "Save the current deck as a new revision of this deck" },
+                                                    aria_label: format!("Save a new revision of saved deck '{}'", save.name),
                                                     onclick: {
-                                                        let id = save.id.clone();
-                                                        let name = save.name.clone();
-                                                        let item_kind = save.deck.kind();
+                                                        let save = save.clone();
                                                         let mut pending_overwrite = pending_overwrite;
                                                         let mut pending_delete = pending_delete;
-                                                        let deck_error = deck_error;
-                                                        let saved_decks = saved_decks;
                                                         move |_| {
-                                                            let id = id.clone();
-                                                            let name = name.clone();
-                                                            let item_kind = item_kind;
                                                             pending_delete.set(None);
-                                                            if pending_overwrite.read().as_ref() != Some(&id) {
-                                                                pending_overwrite.set(Some(id.clone()));
+                                                            if pending_overwrite.read().as_ref() != Some(&save.id) {
+                                                                pending_overwrite.set(Some(save.id.clone()));
                                                                 return;
                                                             }
-                                                            pending_overwrite.set(None);
-                                                            let mut deck_error = deck_error;
-                                                            let mut deck_success = deck_success;
-                                                            let mut saved_decks = saved_decks;
-                                                            spawn(async move {
-                                                                *deck_error.write() = String::new();
-                                                                is_error_from_file.set(false);
-                                                                *deck_success.write() = String::new();
-                                                                let mut new_save = SaveData::from_deck_or_pile(
-                                                                    common_deck.read().clone(),
-                                                                    &db.read(),
-                                                                );
-                                                                new_save.id = id.clone();
-                                                                match save_deck(&new_save).await {
-                                                                    Ok(_) => {
-                                                                        if let Some(save) = saved_decks
-                                                                            .write()
-                                                                            .iter_mut()
-                                                                            .find(|save| save.id() == id)
-                                                                        {
-                                                                            *save = SavedResult::Ok(new_save.clone());
-                                                                        }
-                                                                        *deck_success.write() = format!("Overwritten '{}'.", name);
-                                                                        track_event(
-                                                                            EventType::SaveLoad,
-                                                                            SaveLoadEventData {
-                                                                                action: "Overwrite deck",
-                                                                                item_kind,
-                                                                                error: None,
-                                                                            },
-                                                                        );
-                                                                    }
-                                                                    Err(err) => {
-                                                                        track_event(
-                                                                            EventType::SaveLoad,
-                                                                            SaveLoadEventData {
-                                                                                action: "Overwrite deck",
-                                                                                item_kind,
-                                                                                error: Some(err.clone()),
-                                                                            },
-                                                                        );
-                                                                        *deck_error.write() = err;
-                                                                    }
-                                                                }
-                                                            });
+                                                            save_revision(&save);
                                                         }
                                                     },
                                                     disabled: *is_loading.read() || common_deck.read().is_empty(),
@@ -1057,6 +2641,55 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                     }
                                                     span { "Save" }
                                                 }
+                                                button {
+                                                    class: if history_open.read().as_ref() == Some(&save.id) { "button is-info" } else { "button" },
+                                                    r#type: "button",
+                                                    title: "Show the history of this deck",
+                                                    aria_label: format!("Show the history of saved deck '{}'", save.name),
+                                                    onclick: {
+                                                        let id = save.id.clone();
+                                                        move |_| {
+                                                            history_diff.set(None);
+                                                            if history_open.read().as_ref() == Some(&id) {
+                                                                history_open.set(None);
+                                                            } else {
+                                                                history_open.set(Some(id.clone()));
+                                                            }
+                                                        }
+                                                    },
+                                                    disabled: *is_loading.read(),
+                                                    span { class: "icon",
+                                                        i { class: "fa-solid fa-clock-rotate-left" }
+                                                    }
+                                                }
+                                                button {
+                                                    class: if editing.read().as_ref() == Some(&save.id) { "button is-info" } else { "button" },
+                                                    r#type: "button",
+                                                    title: "Edit folder and tags",
+                                                    aria_label: format!("Edit folder and tags of saved deck '{}'", save.name),
+                                                    onclick: {
+                                                        let id = save.id.clone();
+                                                        let folder = save.folder.clone();
+                                                        let tags = save.tags.clone();
+                                                        let mut pending_overwrite = pending_overwrite;
+                                                        let mut pending_delete = pending_delete;
+                                                        move |_| {
+                                                            pending_overwrite.set(None);
+                                                            pending_delete.set(None);
+                                                            if editing.read().as_ref() == Some(&id) {
+                                                                editing.set(None);
+                                                                return;
+                                                            }
+                                                            edit_folder.set(folder.clone().unwrap_or_default());
+                                                            edit_tags.set(tags.join(", "));
+                                                            editing.set(Some(id.clone()));
+                                                        }
+                                                    },
+                                                    disabled: *is_loading.read(),
+                                                    span { class: "icon",
+                                                        i { class: "fa-solid fa-tags" }
+                                                    }
+                                                }
                                                 button {
                                                     class: "button",
                                                     r#type: "button",
@@ -1171,6 +2804,308 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                                                 }
                                             }
                                         }
+                                        if repair_open.read().as_ref() == Some(&save.id) {
+                                            div { class: "mt-3",
+                                                p { class: "has-text-weight-semibold", "Repair" }
+                                                p { class: "is-size-7 has-text-grey",
+                                                    "These cards are kept in the save, but their reference is outdated. "
+                                                    "They were searched again by card number and printing."
+                                                }
+                                                div { class: "content is-small mt-2",
+                                                    ul {
+                                                        for repair in save.deck.card_repairs(&db.read()) {
+                                                            li {
+                                                                "{repair.amount}x {repair.reference} → "
+                                                                match &repair.result {
+                                                                    Some((card, true)) => rsx! {
+                                                                        span { class: "has-text-success",
+                                                                            "found as {card.card_number}"
+                                                                        }
+                                                                    },
+                                                                    Some((card, false)) => rsx! {
+                                                                        span { class: "has-text-warning-dark",
+                                                                            "found as {card.card_number}, the printing may be different"
+                                                                        }
+                                                                    },
+                                                                    None => rsx! {
+                                                                        span { class: "has-text-danger", "not found" }
+                                                                    },
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                                div { class: "buttons are-small is-justify-content-end",
+                                                    button {
+                                                        class: "button",
+                                                        r#type: "button",
+                                                        onclick: move |_| repair_open.set(None),
+                                                        "Cancel"
+                                                    }
+                                                    button {
+                                                        class: "button is-link",
+                                                        r#type: "button",
+                                                        title: "Update the references of the cards that were found, as a new revision",
+                                                        onclick: {
+                                                            let save = save.clone();
+                                                            move |_| {
+                                                                let deck = save.deck.repaired(&db.read());
+                                                                let remaining = deck.card_repairs(&db.read()).len();
+                                                                let new_save = save.with_new_revision(
+                                                                    deck,
+                                                                    Some("Repaired card references".into()),
+                                                                );
+                                                                let success = format!("Repaired '{}'.", new_save.name);
+                                                                let warning = (remaining > 0)
+                                                                    .then(|| format!("{remaining} cards could not be repaired, they are kept in the save."));
+                                                                repair_open.set(None);
+                                                                update_saved_deck(new_save, "Repair deck", success, warning);
+                                                            }
+                                                        },
+                                                        disabled: *is_loading.read(),
+                                                        "Apply repair"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        if history_open.read().as_ref() == Some(&save.id) {
+                                            div { class: "mt-3",
+                                                p { class: "has-text-weight-semibold", "History" }
+                                                if save.revisions.is_empty() {
+                                                    p { class: "is-size-7 has-text-grey",
+                                                        "No previous revisions. Saving this deck again will keep the current one here."
+                                                    }
+                                                } else {
+                                                    p { class: "is-size-7 has-text-grey",
+                                                        "The last {MAX_REVISIONS} revisions are kept."
+                                                    }
+                                                }
+                                                for (idx , revision) in save.history() {
+                                                    div { class: "is-flex is-align-items-center is-flex-wrap-wrap is-gap-2 mt-2",
+                                                        div {
+                                                            class: "is-flex-grow-1",
+                                                            style: "min-width: 0;",
+                                                            p { class: "is-size-7",
+                                                                "Saved at {format_datetime(&revision.saved_at)}"
+                                                            }
+                                                            if let Some(note) = &revision.note {
+                                                                p { class: "is-size-7 has-text-grey is-italic",
+                                                                    "{note}"
+                                                                }
+                                                            }
+                                                        }
+                                                        div {
+                                                            class: "buttons are-small is-flex-wrap-nowrap",
+                                                            style: "margin-bottom: 0; gap: 0.25rem;",
+                                                            button {
+                                                                class: if history_diff.read().as_ref() == Some(&(save.id.clone(), idx)) { "button is-info" } else { "button" },
+                                                                r#type: "button",
+                                                                title: "Compare with the current revision",
+                                                                onclick: {
+                                                                    let id = save.id.clone();
+                                                                    move |_| {
+                                                                        let key = (id.clone(), idx);
+                                                                        if history_diff.read().as_ref() == Some(&key) {
+                                                                            history_diff.set(None);
+                                                                        } else {
+                                                                            history_diff.set(Some(key));
+                                                                        }
+                                                                    }
+                                                                },
+                                                                span { "Diff" }
+                                                            }
+                                                            button {
+                                                                class: "button",
+                                                                r#type: "button",
+                                                                title: "Load this revision",
+                                                                onclick: {
+                                                                    let id = save.id.clone();
+                                                                    let name = save.name.clone();
+                                                                    let revision = revision.clone();
+                                                                    let mut common_deck = common_deck;
+                                                                    let mut deck_error = deck_error;
+                                                                    let mut deck_success = deck_success;
+                                                                    move |_| {
+                                                                        *deck_error.write() = String::new();
+                                                                        is_error_from_file.set(false);
+                                                                        *common_deck.write() = revision.deck.to_deck_or_pile(&db.read());
+                                                                        *LOADED_SAVE_ID.write() = Some(id.clone());
+                                                                        *deck_success.write() = format!(
+                                                                            "Loaded '{}' from {}.",
+                                                                            name,
+                                                                            format_datetime(&revision.saved_at),
+                                                                        );
+                                                                        track_event(
+                                                                            EventType::SaveLoad,
+                                                                            SaveLoadEventData {
+                                                                                action: "Load revision",
+                                                                                item_kind: revision.deck.kind(),
+                                                                                error: None,
+                                                                            },
+                                                                        );
+                                                                    }
+                                                                },
+                                                                disabled: *is_loading.read(),
+                                                                span { "Load" }
+                                                            }
+                                                            button {
+                                                                class: "button",
+                                                                r#type: "button",
+                                                                title: "Make this revision the current one",
+                                                                onclick: {
+                                                                    let save = save.clone();
+                                                                    let revision = revision.clone();
+                                                                    move |_| {
+                                                                        let note = format!(
+                                                                            "Restored from {}",
+                                                                            format_datetime(&revision.saved_at),
+                                                                        );
+                                                                        let new_save = save.with_new_revision(revision.deck.clone(), Some(note));
+                                                                        let success = format!("Restored '{}'.", new_save.name);
+                                                                        update_saved_deck(new_save, "Restore revision", success, None);
+                                                                    }
+                                                                },
+                                                                disabled: *is_loading.read(),
+                                                                span { "Restore" }
+                                                            }
+                                                        }
+                                                    }
+                                                    if history_diff.read().as_ref() == Some(&(save.id.clone(), idx)) {
+                                                        div { class: "content is-small ml-3",
+                                                            {
+                                                                let diff = revision_diff(&revision.deck, &save.deck, &db.read());
+                                                                rsx! {
+                                                                    if diff.is_empty() {
+                                                                        p { class: "has-text-grey", "Same cards as the current revision." }
+                                                                    }
+                                                                    ul {
+                                                                        for line in diff {
+                                                                            li {
+                                                                                class: if line.change > 0 { "has-text-success" } else { "has-text-danger" },
+                                                                                if line.change > 0 {
+                                                                                    "+{line.change} {line.printing} {line.name}"
+                                                                                } else {
+                                                                                    "{line.change} {line.printing} {line.name}"
+                                                                                }
+                                                                            }
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        if editing.read().as_ref() == Some(&save.id) {
+                                            div { class: "mt-3",
+                                                div { class: "field is-grouped is-grouped-multiline",
+                                                    div { class: "control is-expanded",
+                                                        label {
+                                                            "for": "saved_deck_edit_folder",
+                                                            class: "label is-small",
+                                                            "Folder"
+                                                        }
+                                                        input {
+                                                            id: "saved_deck_edit_folder",
+                                                            class: "input is-small",
+                                                            r#type: "text",
+                                                            list: "saved_deck_folders",
+                                                            maxlength: 100,
+                                                            placeholder: "No folder",
+                                                            value: "{edit_folder}",
+                                                            oninput: move |ev| edit_folder.set(ev.value()),
+                                                        }
+                                                        datalist { id: "saved_deck_folders",
+                                                            for folder in folders.read().iter() {
+                                                                option { value: "{folder}" }
+                                                            }
+                                                        }
+                                                    }
+                                                    div { class: "control is-expanded",
+                                                        label {
+                                                            "for": "saved_deck_edit_tags",
+                                                            class: "label is-small",
+                                                            "Tags"
+                                                        }
+                                                        input {
+                                                            id: "saved_deck_edit_tags",
+                                                            class: "input is-small",
+                                                            r#type: "text",
+                                                            maxlength: 200,
+                                                            placeholder: "Comma separated, e.g. tournament, testing",
+                                                            value: "{edit_tags}",
+                                                            oninput: move |ev| edit_tags.set(ev.value()),
+                                                        }
+                                                    }
+                                                }
+                                                div { class: "buttons are-small is-justify-content-end",
+                                                    button {
+                                                        class: "button",
+                                                        r#type: "button",
+                                                        onclick: move |_| editing.set(None),
+                                                        "Cancel"
+                                                    }
+                                                    button {
+                                                        class: "button is-link",
+                                                        r#type: "button",
+                                                        onclick: {
+                                                            let save = save.clone();
+                                                            let deck_error = deck_error;
+                                                            let deck_success = deck_success;
+                                                            let saved_decks = saved_decks;
+                                                            move |_| {
+                                                                let folder = Some(edit_folder.read().trim().to_string())
+                                                                    .filter(|folder| !folder.is_empty());
+                                                                let save = save.with_folder_and_tags(folder, parse_tags(&edit_tags.read()));
+                                                                let mut deck_error = deck_error;
+                                                                let mut deck_success = deck_success;
+                                                                let mut saved_decks = saved_decks;
+                                                                spawn(async move {
+                                                                    *deck_error.write() = String::new();
+                                                                    is_error_from_file.set(false);
+                                                                    *deck_success.write() = String::new();
+                                                                    match save_deck(&save).await {
+                                                                        Ok(_) => {
+                                                                            if let Some(entry) = saved_decks
+                                                                                .write()
+                                                                                .iter_mut()
+                                                                                .find(|entry| entry.id() == save.id)
+                                                                            {
+                                                                                *entry = SavedResult::Ok(save.clone());
+                                                                            }
+                                                                            editing.set(None);
+                                                                            *deck_success.write() = format!("Updated '{}'.", save.name);
+                                                                            track_event(
+                                                                                EventType::SaveLoad,
+                                                                                SaveLoadEventData {
+                                                                                    action: "Edit folder and tags",
+                                                                                    item_kind: save.deck.kind(),
+                                                                                    error: None,
+                                                                                },
+                                                                            );
+                                                                        }
+                                                                        Err(err) => {
+                                                                            track_event(
+                                                                                EventType::SaveLoad,
+                                                                                SaveLoadEventData {
+                                                                                    action: "Edit folder and tags",
+                                                                                    item_kind: save.deck.kind(),
+                                                                                    error: Some(err.clone()),
+                                                                                },
+                                                                            );
+                                                                            *deck_error.write() = err;
+                                                                        }
+                                                                    }
+                                                                });
+                                                            }
+                                                        },
+                                                        disabled: *is_loading.read(),
+                                                        "Save"
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -1179,5 +3114,668 @@ pub fn SaveLoadPage(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabas
                 }
             }
         }
+
+        LibraryBackupPanel {
+            saved_decks,
+            auto_save,
+            db,
+            is_loading,
+        }
+        LibrarySyncPanel { saved_decks, is_loading }
+    }
+}
+
+#[component]
+fn LibraryBackupPanel(
+    mut saved_decks: Signal<Vec<SavedResult>>,
+    mut auto_save: Signal<Option<SaveData>>,
+    db: Signal<CardsDatabase>,
+    is_loading: Signal<bool>,
+) -> Element {
+    let mut backup_error = use_signal(String::new);
+    let mut backup_success = use_signal(String::new);
+    let mut pending_backup = use_signal(|| None::<LibraryBackup>);
+    let mut restore_mode = use_signal(|| RestoreMode::Merge);
+    let mut restore_conflict = use_signal(|| RestoreConflict::KeepBoth);
+    let mut confirm_replace = use_signal(|| false);
+
+    let download_backup = move |_| {
+        *backup_error.write() = String::new();
+        *backup_success.write() = String::new();
+
+        let saves = saved_decks
+            .read()
+            .iter()
+            .filter_map(|save| match save {
+                SavedResult::Ok(save) => Some(save.clone()),
+                SavedResult::Err { .. } => None,
+            })
+            .collect_vec();
+        let error_count = saved_decks.read().len() - saves.len();
+        let backup = LibraryBackup::new(saves, auto_save.read().clone());
+
+        match serde_json::to_vec_pretty(&backup) {
+            Ok(contents) => {
+                download_file(&LibraryBackup::file_name(), &contents[..]);
+                *backup_success.write() = if error_count > 0 {
+                    format!(
+                        "Backed up {} saved decks. {error_count} entries could not be read and are not included.",
+                        backup.saved_decks.len()
+                    )
+                } else {
+                    format!("Backed up {} saved decks.", backup.saved_decks.len())
+                };
+                track_event(
+                    EventType::SaveLoad,
+                    SaveLoadEventData {
+                        action: "Backup library",
+                        item_kind: "library",
+                        error: None,
+                    },
+                );
+            }
+            Err(err) => {
+                track_event(
+                    EventType::SaveLoad,
+                    SaveLoadEventData {
+                        action: "Backup library",
+                        item_kind: "library",
+                        error: Some(err.to_string()),
+                    },
+                );
+                *backup_error.write() = format!("Could not encode backup file: {err}");
+            }
+        }
+    };
+
+    let load_backup_file = move |event: Event<FormData>| async move {
+        *backup_error.write() = String::new();
+        *backup_success.write() = String::new();
+        confirm_replace.set(false);
+        pending_backup.set(None);
+
+        let Some(file) = event.files().into_iter().next() else {
+            return;
+        };
+        match file.read_bytes().await {
+            Ok(contents) => match LibraryBackup::from_file(&contents) {
+                Ok(backup) => pending_backup.set(Some(backup)),
+                Err(err) => *backup_error.write() = err,
+            },
+            Err(err) => *backup_error.write() = format!("Could not read file: {err}"),
+        }
+    };
+
+    let restore_backup = move |_| {
+        let Some(backup) = pending_backup.read().clone() else {
+            return;
+        };
+        let mode = *restore_mode.read();
+        // replacing the library cannot be undone
+        if mode == RestoreMode::Replace && !*confirm_replace.read() {
+            confirm_replace.set(true);
+            return;
+        }
+        confirm_replace.set(false);
+
+        spawn(async move {
+            *backup_error.write() = String::new();
+            *backup_success.write() = String::new();
+
+            // the unreadable entries are not in the backup, they are kept instead of being lost
+            let unreadable = saved_decks
+                .read()
+                .iter()
+                .filter(|save| matches!(save, SavedResult::Err { .. }))
+                .count();
+            let replaced = match mode {
+                RestoreMode::Merge => vec![],
+                RestoreMode::Replace => saved_decks
+                    .read()
+                    .iter()
+                    .filter_map(|save| match save {
+                        SavedResult::Ok(save) => Some(save.id.clone()),
+                        SavedResult::Err { .. } => None,
+                    })
+                    .collect_vec(),
+            };
+
+            let (saves, report) = match mode {
+                RestoreMode::Merge => {
+                    let library = saved_decks
+                        .read()
+                        .iter()
+                        .filter_map(|save| match save {
+                            SavedResult::Ok(save) => Some(save.clone()),
+                            SavedResult::Err { .. } => None,
+                        })
+                        .collect_vec();
+                    merge_library(&library, &backup.saved_decks, *restore_conflict.read())
+                }
+                RestoreMode::Replace => (
+                    backup.saved_decks.clone(),
+                    RestoreReport {
+                        added: backup.saved_decks.len(),
+                        ..Default::default()
+                    },
+                ),
+            };
+
+            match put_saved_decks(&saves, &replaced).await {
+                Ok(_) => {
+                    let restored = match (&backup.auto_save, mode) {
+                        (Some(save), RestoreMode::Replace) => Some(Some(save)),
+                        (Some(save), RestoreMode::Merge) if auto_save.read().is_none() => {
+                            Some(Some(save))
+                        }
+                        (None, RestoreMode::Replace) => Some(None),
+                        _ => None,
+                    };
+                    if let Some(save) = restored {
+                        match save {
+                            Some(save) => auto_save_deck(save),
+                            None => delete_auto_saved_deck(),
+                        };
+                        // the restore banner reads the auto-saved deck
+                        *AUTO_SAVE_DECK.write() = save.map(|save| save.to_deck_or_pile(&db.read()));
+                        auto_save.set(save.cloned());
+                    }
+                    write_settings(&backup.settings, mode == RestoreMode::Replace);
+
+                    match list_saved_decks().await {
+                        Ok(saves) => saved_decks.set(saves),
+                        Err(err) => *backup_error.write() = err,
+                    }
+                    pending_backup.set(None);
+                    *backup_success.write() = if mode == RestoreMode::Replace && unreadable > 0 {
+                        format!(
+                            "Restored the library: {report}. {unreadable} entries that could not be read were kept."
+                        )
+                    } else {
+                        format!("Restored the library: {report}.")
+                    };
+                    track_event(
+                        EventType::SaveLoad,
+                        SaveLoadEventData {
+                            action: match mode {
+                                RestoreMode::Merge => "Restore library (merge)",
+                                RestoreMode::Replace => "Restore library (replace)",
+                            },
+                            item_kind: "library",
+                            error: None,
+                        },
+                    );
+                }
+                Err(err) => {
+                    track_event(
+                        EventType::SaveLoad,
+                        SaveLoadEventData {
+                            action: match mode {
+                                RestoreMode::Merge => "Restore library (merge)",
+                                RestoreMode::Replace => "Restore library (replace)",
+                            },
+                            item_kind: "library",
+                            error: Some(err.clone()),
+                        },
+                    );
+                    *backup_error.write() = err;
+                }
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "mt-5",
+            p { class: "mb-2", "Library backup" }
+            p { class: "is-size-7 has-text-grey mb-2",
+                "Download all the saved decks, the auto-saved deck and the settings in a single file. Keep it somewhere safe, browser storage can be cleared."
+            }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    button {
+                        class: "button",
+                        r#type: "button",
+                        onclick: download_backup,
+                        disabled: *is_loading.read(),
+                        span { class: "icon",
+                            i { class: "fa-solid fa-box-archive" }
+                        }
+                        span { "Download backup" }
+                    }
+                }
+                div { class: "control",
+                    div { class: "file",
+                        label { class: "file-label",
+                            input {
+                                r#type: "file",
+                                class: "file-input",
+                                accept: ".json",
+                                onchange: load_backup_file,
+                                disabled: *is_loading.read(),
+                            }
+                            span { class: "file-cta",
+                                span { class: "file-icon",
+                                    i { class: "fa-solid fa-upload" }
+                                }
+                                span { class: "file-label", "Restore from backup..." }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(backup) = pending_backup.read().as_ref() {
+                div { class: "notification",
+                    p { class: "mb-3",
+                        "Backup from {format_datetime(&backup.exported_at)} with {backup.saved_decks.len()} saved decks"
+                        if backup.auto_save.is_some() {
+                            ", an auto-saved deck"
+                        }
+                        if !backup.settings.is_empty() {
+                            " and {backup.settings.len()} settings"
+                        }
+                        "."
+                    }
+                    div { class: "field is-grouped is-grouped-multiline",
+                        div { class: "control",
+                            label { "for": "restore_mode", class: "label is-small", "Restore" }
+                            div { class: "select is-small",
+                                select {
+                                    id: "restore_mode",
+                                    oninput: move |ev| {
+                                        confirm_replace.set(false);
+                                        *restore_mode.write() = match ev.value().as_str() {
+                                            "merge" => RestoreMode::Merge,
+                                            "replace" => RestoreMode::Replace,
+                                            _ => unreachable!(),
+                                        };
+                                    },
+                                    option {
+                                        value: "merge",
+                                        selected: *restore_mode.read() == RestoreMode::Merge,
+                                        "Merge with the library"
+                                    }
+                                    option {
+                                        value: "replace",
+                                        selected: *restore_mode.read() == RestoreMode::Replace,
+                                        "Replace the library"
+                                    }
+                                }
+                            }
+                        }
+                        if *restore_mode.read() == RestoreMode::Merge {
+                            div { class: "control",
+                                label { "for": "restore_conflict", class: "label is-small",
+                                    "When a deck already exists"
+                                }
+                                div { class: "select is-small",
+                                    select {
+                                        id: "restore_conflict",
+                                        oninput: move |ev| {
+                                            *restore_conflict.write() = match ev.value().as_str() {
+                                                "keep_both" => RestoreConflict::KeepBoth,
+                                                "keep_newest" => RestoreConflict::KeepNewest,
+                                                "use_backup" => RestoreConflict::UseBackup,
+                                                "use_library" => RestoreConflict::UseLibrary,
+                                                _ => unreachable!(),
+                                            };
+                                        },
+                                        option {
+                                            value: "keep_both",
+                                            selected: *restore_conflict.read() == RestoreConflict::KeepBoth,
+                                            "Keep both"
+                                        }
+                                        option {
+                                            value: "keep_newest",
+                                            selected: *restore_conflict.read() == RestoreConflict::KeepNewest,
+                                            "Keep the most recently saved"
+                                        }
+                                        option {
+                                            value: "use_backup",
+                                            selected: *restore_conflict.read() == RestoreConflict::UseBackup,
+                                            "Use the backup"
+                                        }
+                                        option {
+                                            value: "use_library",
+                                            selected: *restore_conflict.read() == RestoreConflict::UseLibrary,
+                                            "Keep the library"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if *confirm_replace.read() {
+                        p { class: "help is-danger mb-2",
+                            "Click restore again to replace all {saved_decks.read().len()} saved decks with the backup."
+                        }
+                        if saved_decks.read().iter().any(|save| matches!(save, SavedResult::Err { .. })) {
+                            p { class: "help mb-2",
+                                "The entries that could not be read are not in the backup, they will be kept."
+                            }
+                        }
+                    }
+                    div { class: "buttons",
+                        button {
+                            class: "button",
+                            r#type: "button",
+                            onclick: move |_| {
+                                confirm_replace.set(false);
+                                pending_backup.set(None);
+                            },
+                            "Cancel"
+                        }
+                        button {
+                            class: if *confirm_replace.read() { "button is-danger" } else { "button is-link" },
+                            r#type: "button",
+                            onclick: restore_backup,
+                            disabled: *is_loading.read(),
+                            span { class: "icon",
+                                i { class: "fa-solid fa-rotate-left" }
+                            }
+                            span { "Restore" }
+                        }
+                    }
+                }
+            }
+
+            p { class: "help is-success", "{backup_success}" }
+            p { class: "help is-danger", "{backup_error}" }
+        }
+    }
+}
+
+#[component]
+fn LibrarySyncPanel(
+    mut saved_decks: Signal<Vec<SavedResult>>,
+    is_loading: Signal<bool>,
+) -> Element {
+    let settings = use_hook(SyncSettings::load);
+    let mut url = use_signal(|| settings.url.clone());
+    let mut username = use_signal(|| settings.username.clone());
+    let mut password = SYNC_PASSWORD.signal();
+    let mut last_synced_at = use_signal(|| None::<String>);
+    let mut sync_error = use_signal(String::new);
+    let mut sync_success = use_signal(String::new);
+    let mut syncing = use_signal(|| false);
+
+    // the last sync of this location, another one was never synced
+    use_effect(move || {
+        last_synced_at.set(SyncState::load(url.read().trim()).last_synced_at);
+    });
+
+    let sync_now = move |_| async move {
+        *sync_error.write() = String::new();
+        *sync_success.write() = String::new();
+        syncing.set(true);
+
+        let settings = SyncSettings {
+            url: url.read().trim().to_string(),
+            username: username.read().clone(),
+            password: password.read().clone(),
+        };
+        settings.store();
+
+        match sync_library(&settings.backend()).await {
+            Ok(report) => {
+                *sync_success.write() = format!("Synced: {report}.");
+                last_synced_at.set(SyncState::load(&settings.url).last_synced_at);
+                track_event(
+                    EventType::SaveLoad,
+                    SaveLoadEventData {
+                        action: "Sync library",
+                        item_kind: "library",
+                        error: None,
+                    },
+                );
+            }
+            Err(err) => {
+                track_event(
+                    EventType::SaveLoad,
+                    SaveLoadEventData {
+                        action: "Sync library",
+                        item_kind: "library",
+                        error: Some(err.clone()),
+                    },
+                );
+                *sync_error.write() = err;
+            }
+        }
+        match list_saved_decks().await {
+            Ok(saves) => saved_decks.set(saves),
+            Err(err) => *sync_error.write() = err,
+        }
+
+        syncing.set(false);
+    };
+
+    rsx! {
+        div { class: "mt-5",
+            p { class: "mb-2", "Sync" }
+            p { class: "is-size-7 has-text-grey mb-2",
+                "Keep the saved decks in sync between devices, using your own WebDAV or HTTP server. "
+                "The URL must point to a file (e.g. https://dav.example.com/decks/library.json), "
+                "and the server must allow GET and PUT requests from this site (CORS)."
+            }
+            div { class: "field",
+                label { "for": "sync_url", class: "label is-small", "URL" }
+                div { class: "control",
+                    input {
+                        id: "sync_url",
+                        class: "input is-small",
+                        r#type: "url",
+                        placeholder: "https://dav.example.com/decks/library.json",
+                        value: "{url}",
+                        oninput: move |ev| url.set(ev.value()),
+                    }
+                }
+            }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control is-expanded",
+                    label { "for": "sync_username", class: "label is-small", "Username" }
+                    input {
+                        id: "sync_username",
+                        class: "input is-small",
+                        r#type: "text",
+                        autocomplete: "username",
+                        placeholder: "Optional",
+                        value: "{username}",
+                        oninput: move |ev| username.set(ev.value()),
+                    }
+                }
+                div { class: "control is-expanded",
+                    label { "for": "sync_password", class: "label is-small", "Password" }
+                    input {
+                        id: "sync_password",
+                        class: "input is-small",
+                        r#type: "password",
+                        autocomplete: "current-password",
+                        placeholder: "Optional",
+                        value: "{password}",
+                        oninput: move |ev| password.set(ev.value()),
+                    }
+                    p { class: "help", "Not saved, it's asked again after a reload." }
+                }
+            }
+            div { class: "field is-grouped is-align-items-center",
+                div { class: "control",
+                    button {
+                        class: "button",
+                        class: if *syncing.read() { "is-loading" },
+                        r#type: "button",
+                        onclick: sync_now,
+                        disabled: *is_loading.read() || *syncing.read() || url.read().trim().is_empty(),
+                        span { class: "icon",
+                            i { class: "fa-solid fa-rotate" }
+                        }
+                        span { "Sync now" }
+                    }
+                }
+                if let Some(last_synced_at) = last_synced_at.read().as_ref() {
+                    p { class: "is-size-7 has-text-grey",
+                        "Last synced at {format_datetime(last_synced_at)}"
+                    }
+                }
+            }
+            p { class: "help is-success", "{sync_success}" }
+            p { class: "help is-danger", "{sync_error}" }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = "2025-01-01T00:00:00Z";
+    const AFTER: &str = "2025-01-02T00:00:00Z";
+    const LATEST: &str = "2025-01-03T00:00:00Z";
+
+    fn save(id: &str, saved_at: &str) -> SaveData {
+        SaveData {
+            id: id.into(),
+            saved_at: saved_at.into(),
+            ..SaveData::new(SaveDeckOrPile::Pile(SavePileOfCards {
+                name: Some(id.into()),
+                cards: vec![],
+            }))
+        }
+    }
+
+    fn synced(saves: &[&SaveData]) -> SyncState {
+        SyncState {
+            saved_at: saves
+                .iter()
+                .map(|save| (save.id.clone(), save.saved_at.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(saves: &[SaveData]) -> Vec<&str> {
+        saves.iter().map(|save| save.id.as_str()).sorted().collect()
+    }
+
+    #[test]
+    fn unchanged_saves_are_kept() {
+        let a = save("a", BEFORE);
+        let plan = plan_sync(&[a.clone()], Some(&[a.clone()][..]), &synced(&[&a]));
+
+        assert!(plan.local_puts.is_empty());
+        assert!(plan.local_deletes.is_empty());
+        assert!(!plan.remote_changed);
+        assert_eq!(ids(&plan.remote), ["a"]);
+        assert_eq!(plan.report, SyncReport::default());
+    }
+
+    #[test]
+    fn remote_change_is_pulled() {
+        let a = save("a", BEFORE);
+        let edited = save("a", AFTER);
+        let plan = plan_sync(&[a.clone()], Some(&[edited.clone()][..]), &synced(&[&a]));
+
+        assert_eq!(plan.local_puts, [edited.clone()]);
+        assert!(!plan.remote_changed);
+        assert_eq!(plan.remote, [edited]);
+        assert_eq!(plan.report.pulled, 1);
+    }
+
+    #[test]
+    fn local_change_is_pushed() {
+        let a = save("a", BEFORE);
+        let edited = save("a", AFTER);
+        let plan = plan_sync(&[edited.clone()], Some(&[a.clone()][..]), &synced(&[&a]));
+
+        assert!(plan.local_puts.is_empty());
+        assert!(plan.remote_changed);
+        assert_eq!(plan.remote, [edited]);
+        assert_eq!(plan.report.pushed, 1);
+    }
+
+    #[test]
+    fn new_saves_are_copied_both_ways() {
+        let a = save("a", BEFORE);
+        let b = save("b", BEFORE);
+        let plan = plan_sync(&[a.clone()], Some(&[b.clone()][..]), &SyncState::default());
+
+        assert_eq!(plan.local_puts, [b]);
+        assert!(plan.local_deletes.is_empty());
+        assert!(plan.remote_changed);
+        assert_eq!(ids(&plan.remote), ["a", "b"]);
+        assert_eq!(plan.report.pulled, 1);
+        assert_eq!(plan.report.pushed, 1);
+    }
+
+    #[test]
+    fn remote_deletion_is_applied() {
+        let a = save("a", BEFORE);
+        let b = save("b", BEFORE);
+        let plan = plan_sync(
+            &[a.clone(), b.clone()],
+            Some(&[b.clone()][..]),
+            &synced(&[&a, &b]),
+        );
+
+        assert_eq!(plan.local_deletes, ["a"]);
+        assert_eq!(ids(&plan.remote), ["b"]);
+        assert_eq!(plan.report.deleted, 1);
+    }
+
+    #[test]
+    fn remote_deletion_keeps_a_local_change() {
+        let a = save("a", BEFORE);
+        let edited = save("a", AFTER);
+        let plan = plan_sync(&[edited.clone()], Some(&[][..]), &synced(&[&a]));
+
+        assert!(plan.local_deletes.is_empty());
+        assert!(plan.remote_changed);
+        assert_eq!(plan.remote, [edited]);
+    }
+
+    #[test]
+    fn local_deletion_is_pushed() {
+        let a = save("a", BEFORE);
+        let plan = plan_sync(&[], Some(&[a.clone()][..]), &synced(&[&a]));
+
+        assert!(plan.local_puts.is_empty());
+        assert!(plan.remote_changed);
+        assert!(plan.remote.is_empty());
+        assert_eq!(plan.report.deleted, 1);
+    }
+
+    #[test]
+    fn missing_remote_file_pushes_everything() {
+        let a = save("a", BEFORE);
+        let b = save("b", BEFORE);
+        let plan = plan_sync(&[a.clone(), b.clone()], None, &synced(&[&a, &b]));
+
+        assert!(plan.local_puts.is_empty());
+        assert!(plan.local_deletes.is_empty());
+        assert!(plan.remote_changed);
+        assert_eq!(ids(&plan.remote), ["a", "b"]);
+        assert_eq!(plan.report.pushed, 2);
+        assert_eq!(plan.report.deleted, 0);
+    }
+
+    #[test]
+    fn conflict_keeps_the_last_saved_and_a_copy() {
+        let a = save("a", BEFORE);
+        let local = save("a", AFTER);
+        let remote = save("a", LATEST);
+        let plan = plan_sync(
+            &[local.clone()],
+            Some(&[remote.clone()][..]),
+            &synced(&[&a]),
+        );
+
+        assert_eq!(plan.report.conflicts, 1);
+        assert!(plan.remote_changed);
+        assert_eq!(plan.local_puts.len(), 2);
+        assert_eq!(plan.local_puts[0], remote);
+        let copy = &plan.local_puts[1];
+        assert_ne!(copy.id, "a");
+        assert_eq!(copy.saved_at, local.saved_at);
+        assert_eq!(copy.name, format!("{} (conflict copy)", local.name));
+        assert_eq!(plan.remote, [remote, copy.clone()]);
     }
 }
@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+/// A rectangle in mm, from the top-left corner of the page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// Cut paths of a page, for cutting machines
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutSheet {
+    pub width: f32,
+    pub height: f32,
+    pub cuts: Vec<Rect>,
+    pub corner_radius: f32,
+    /// Printed with the page, used by the machine to align the cuts
+    pub registration_marks: Vec<Rect>,
+}
+
+impl CutSheet {
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let (w, h) = (num(self.width), num(self.height));
+        svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\">"
+        );
+
+        if !self.registration_marks.is_empty() {
+            svg.push_str("  <g id=\"registration\" fill=\"black\">\n");
+            for mark in &self.registration_marks {
+                let _ = writeln!(
+                    svg,
+                    "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                    num(mark.x),
+                    num(mark.y),
+                    num(mark.width),
+                    num(mark.height)
+                );
+            }
+            svg.push_str("  </g>\n");
+        }
+
+        svg.push_str("  <g id=\"cut\" fill=\"none\" stroke=\"red\" stroke-width=\"0.1\">\n");
+        let r = num(self.corner_radius);
+        for cut in &self.cuts {
+            let _ = writeln!(
+                svg,
+                "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{r}\" ry=\"{r}\"/>",
+                num(cut.x),
+                num(cut.y),
+                num(cut.width),
+                num(cut.height)
+            );
+        }
+        svg.push_str("  </g>\n");
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// ASCII DXF, with the cuts and the registration marks on separate layers
+    pub fn to_dxf(&self) -> String {
+        let mut dxf = String::new();
+
+        // units in mm
+        dxf.push_str("0\nSECTION\n2\nHEADER\n9\n$INSUNITS\n70\n4\n0\nENDSEC\n");
+
+        dxf.push_str("0\nSECTION\n2\nENTITIES\n");
+        for mark in &self.registration_marks {
+            self.dxf_rect(&mut dxf, "REGISTRATION", mark, 0.0);
+        }
+        for cut in &self.cuts {
+            self.dxf_rect(&mut dxf, "CUT", cut, self.corner_radius);
+        }
+        dxf.push_str("0\nENDSEC\n0\nEOF\n");
+
+        dxf
+    }
+
+    fn dxf_rect(&self, dxf: &mut String, layer: &str, rect: &Rect, radius: f32) {
+        // DXF origin is bottom-left
+        let (x0, y0) = (rect.x, self.height - rect.y - rect.height);
+        let (x1, y1) = (x0 + rect.width, y0 + rect.height);
+        let r = radius.min(rect.width / 2.0).min(rect.height / 2.0).max(0.0);
+
+        let mut line = |(ax, ay): (f32, f32), (bx, by): (f32, f32)| {
+            let _ = write!(
+                dxf,
+                "0\nLINE\n8\n{layer}\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+                num(ax),
+                num(ay),
+                num(bx),
+                num(by)
+            );
+        };
+        line((x0 + r, y0), (x1 - r, y0));
+        line((x1, y0 + r), (x1, y1 - r));
+        line((x1 - r, y1), (x0 + r, y1));
+        line((x0, y1 - r), (x0, y0 + r));
+
+        if r > 0.0 {
+            // counter-clockwise, in degrees
+            for (cx, cy, start) in [
+                (x0 + r, y0 + r, 180),
+                (x1 - r, y0 + r, 270),
+                (x1 - r, y1 - r, 0),
+                (x0 + r, y1 - r, 90),
+            ] {
+                let _ = write!(
+                    dxf,
+                    "0\nARC\n8\n{layer}\n10\n{}\n20\n{}\n40\n{}\n50\n{start}\n51\n{}\n",
+                    num(cx),
+                    num(cy),
+                    num(r),
+                    start + 90
+                );
+            }
+        }
+    }
+}
+
+fn num(value: f32) -> String {
+    let value = format!("{value:.3}");
+    value
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
@@ -0,0 +1,220 @@
+use js_sys::{Date, Uint8Array};
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::{JsCast, JsValue};
+
+const CACHE_DB_NAME: &str = "hocg-deck-convert.image_cache";
+const CACHE_DB_VERSION: u32 = 1;
+/// The image bytes, by key
+const IMAGES_STORE_NAME: &str = "images";
+/// The size and last use of the images, read for the eviction without the bytes
+const ENTRIES_STORE_NAME: &str = "entries";
+/// The least recently used images are removed past this size
+const MAX_CACHE_SIZE: u64 = 300_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    size: u64,
+    /// Milliseconds since the epoch
+    last_used: f64,
+}
+
+/// Size of the cache, in bytes and images
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheUsage {
+    pub size: u64,
+    pub images: usize,
+}
+
+/// The key of an image resized to `(width, height)` pixels, depends on the DPI and the card size
+pub fn cache_key(img_path: &str, (width, height): (u32, u32)) -> String {
+    format!("{img_path}#{width}x{height}")
+}
+
+/// Card images already resized for the proxies, kept between the exports and available offline.
+/// The images are encoded as lossless WebP by the proxy worker.
+pub struct ImageCache {
+    db: Rexie,
+}
+
+impl ImageCache {
+    pub async fn open() -> Result<Self, String> {
+        let db = Rexie::builder(CACHE_DB_NAME)
+            .version(CACHE_DB_VERSION)
+            .add_object_store(ObjectStore::new(IMAGES_STORE_NAME))
+            .add_object_store(ObjectStore::new(ENTRIES_STORE_NAME).key_path("key"))
+            .build()
+            .await
+            .map_err(|err| format!("Could not open image cache: {err}"))?;
+        Ok(Self { db })
+    }
+
+    /// Returns the image bytes, and marks them as recently used
+    pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let transaction = self
+            .db
+            .transaction(
+                &[IMAGES_STORE_NAME, ENTRIES_STORE_NAME],
+                TransactionMode::ReadWrite,
+            )
+            .ok()?;
+        let images = transaction.store(IMAGES_STORE_NAME).ok()?;
+        let entries = transaction.store(ENTRIES_STORE_NAME).ok()?;
+
+        let bytes = images
+            .get(key.into())
+            .await
+            .ok()??
+            .dyn_into::<Uint8Array>()
+            .ok()?
+            .to_vec();
+        let entry = CacheEntry {
+            key: key.into(),
+            size: bytes.len() as u64,
+            last_used: Date::now(),
+        };
+        entries.put(&to_value(&entry).ok()?, None).await.ok()?;
+        transaction.done().await.ok()?;
+
+        Some(bytes)
+    }
+
+    pub async fn put(&self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        let entry = CacheEntry {
+            key: key.into(),
+            size: bytes.len() as u64,
+            last_used: Date::now(),
+        };
+
+        let transaction = self
+            .db
+            .transaction(
+                &[IMAGES_STORE_NAME, ENTRIES_STORE_NAME],
+                TransactionMode::ReadWrite,
+            )
+            .map_err(|err| format!("Could not open image cache transaction: {err}"))?;
+        let images = transaction
+            .store(IMAGES_STORE_NAME)
+            .map_err(|err| format!("Could not open image store: {err}"))?;
+        let entries = transaction
+            .store(ENTRIES_STORE_NAME)
+            .map_err(|err| format!("Could not open image entries store: {err}"))?;
+        images
+            .put(&Uint8Array::from(bytes).into(), Some(&JsValue::from(key)))
+            .await
+            .map_err(|err| format!("Could not cache image: {err}"))?;
+        let entry = to_value(&entry).map_err(|err| format!("Could not encode entry: {err}"))?;
+        entries
+            .put(&entry, None)
+            .await
+            .map_err(|err| format!("Could not cache image: {err}"))?;
+        transaction
+            .done()
+            .await
+            .map_err(|err| format!("Image cache write transaction failed: {err}"))
+    }
+
+    async fn entries(&self) -> Result<Vec<CacheEntry>, String> {
+        let transaction = self
+            .db
+            .transaction(&[ENTRIES_STORE_NAME], TransactionMode::ReadOnly)
+            .map_err(|err| format!("Could not open image cache transaction: {err}"))?;
+        let entries = transaction
+            .store(ENTRIES_STORE_NAME)
+            .map_err(|err| format!("Could not open image entries store: {err}"))?;
+        let values = entries
+            .scan(None, None, None, None)
+            .await
+            .map_err(|err| format!("Could not read image cache: {err}"))?;
+        transaction
+            .done()
+            .await
+            .map_err(|err| format!("Image cache read transaction failed: {err}"))?;
+        Ok(values
+            .into_iter()
+            .filter_map(|(_, value)| from_value(value).ok())
+            .collect())
+    }
+
+    async fn delete(&self, keys: &[String]) -> Result<(), String> {
+        let transaction = self
+            .db
+            .transaction(
+                &[IMAGES_STORE_NAME, ENTRIES_STORE_NAME],
+                TransactionMode::ReadWrite,
+            )
+            .map_err(|err| format!("Could not open image cache transaction: {err}"))?;
+        let images = transaction
+            .store(IMAGES_STORE_NAME)
+            .map_err(|err| format!("Could not open image store: {err}"))?;
+        let entries = transaction
+            .store(ENTRIES_STORE_NAME)
+            .map_err(|err| format!("Could not open image entries store: {err}"))?;
+        for key in keys {
+            images
+                .delete(key.into())
+                .await
+                .map_err(|err| format!("Could not delete cached image: {err}"))?;
+            entries
+                .delete(key.into())
+                .await
+                .map_err(|err| format!("Could not delete cached image: {err}"))?;
+        }
+        transaction
+            .done()
+            .await
+            .map_err(|err| format!("Image cache delete transaction failed: {err}"))
+    }
+
+    /// Removes the least recently used images, until the cache fits in its size
+    pub async fn evict(&self) -> Result<(), String> {
+        let mut entries = self.entries().await?;
+        let mut size: u64 = entries.iter().map(|e| e.size).sum();
+        if size <= MAX_CACHE_SIZE {
+            return Ok(());
+        }
+
+        entries.sort_by(|a, b| a.last_used.total_cmp(&b.last_used));
+        let mut evicted = vec![];
+        for entry in entries {
+            if size <= MAX_CACHE_SIZE {
+                break;
+            }
+            size -= entry.size;
+            evicted.push(entry.key);
+        }
+        self.delete(&evicted).await
+    }
+
+    pub async fn usage(&self) -> Result<CacheUsage, String> {
+        let entries = self.entries().await?;
+        Ok(CacheUsage {
+            size: entries.iter().map(|e| e.size).sum(),
+            images: entries.len(),
+        })
+    }
+
+    pub async fn clear(&self) -> Result<(), String> {
+        let transaction = self
+            .db
+            .transaction(
+                &[IMAGES_STORE_NAME, ENTRIES_STORE_NAME],
+                TransactionMode::ReadWrite,
+            )
+            .map_err(|err| format!("Could not open image cache transaction: {err}"))?;
+        for store in [IMAGES_STORE_NAME, ENTRIES_STORE_NAME] {
+            transaction
+                .store(store)
+                .map_err(|err| format!("Could not open image cache store: {err}"))?
+                .clear()
+                .await
+                .map_err(|err| format!("Could not clear image cache: {err}"))?;
+        }
+        transaction
+            .done()
+            .await
+            .map_err(|err| format!("Image cache clear transaction failed: {err}"))
+    }
+}
@@ -1,7 +1,13 @@
 #![allow(non_snake_case)]
 
+mod archive;
 mod components;
+mod cut_file;
+mod image_cache;
+mod price_cache;
+mod price_history;
 mod sources;
+mod sync;
 mod tracker;
 
 use std::collections::BTreeMap;
@@ -76,6 +82,10 @@ pub enum Page {
 }
 
 fn main() {
+    // the proxy image worker loads the app module too, without a page
+    if web_sys::window().is_none() {
+        return;
+    }
     launch(App);
 }
 
@@ -119,6 +129,20 @@ fn App() -> Element {
         *CARDS_DB.write() = card_db;
     });
 
+    let _cards_prices: Coroutine<()> = use_coroutine(|_rx| async move {
+        match price_cache::load().await {
+            Ok(saved) => {
+                // written after the load, a price check could have been faster. It doesn't
+                // hold the prices during its fetch, so nothing else is borrowing them here.
+                let mut prices = CARDS_PRICES.write();
+                for (key, price) in saved {
+                    prices.entry(key).or_insert(price);
+                }
+            }
+            Err(err) => error!("{err}"),
+        }
+    });
+
     rsx! {
         section { class: "section",
             div { class: "container",
@@ -439,6 +463,8 @@ fn Form() -> Element {
                                 Some(DeckType::TabletopSim) => ImageOptions::deck_log(),
                                 Some(DeckType::ProxySheets) => ImageOptions::proxy_print(),
                                 Some(DeckType::PriceCheck) => ImageOptions::price_check(),
+                                Some(DeckType::Decklist) => ImageOptions::card_details(),
+                                Some(DeckType::Bundle) => ImageOptions::card_details(),
                                 _ => ImageOptions::holodelta(),
                             };
                         },
@@ -489,6 +515,7 @@ pub fn ImportPage() -> Element {
                                 "holo_duel" => Some(DeckType::HoloDuel),
                                 "hocg_tts" => Some(DeckType::TabletopSim),
                                 "unknown" => Some(DeckType::Unknown),
+                                "batch" => Some(DeckType::Batch),
                                 _ => None,
                             };
                         },
@@ -522,6 +549,11 @@ pub fn ImportPage() -> Element {
                             selected: *import_format.read() == Some(DeckType::Unknown),
                             "I don't know..."
                         }
+                        option {
+                            value: "batch",
+                            selected: *import_format.read() == Some(DeckType::Batch),
+                            "Batch conversion (many files)"
+                        }
                     }
                 }
             }
@@ -546,6 +578,9 @@ pub fn ImportPage() -> Element {
             if *import_format.read() == Some(DeckType::Unknown) {
                 UnknownImport { common_deck: COMMON_DECK.signal(), db: CARDS_DB.signal() }
             }
+            if *import_format.read() == Some(DeckType::Batch) {
+                batch::Import { db: CARDS_DB.signal() }
+            }
         }
     }
 }
@@ -585,6 +620,8 @@ pub fn ExportPage() -> Element {
                                 "hocg_tts" => ImageOptions::deck_log(),
                                 "proxy_sheets" => ImageOptions::proxy_print(),
                                 "price_check" => ImageOptions::price_check(),
+                                "decklist" => ImageOptions::card_details(),
+                                "bundle" => ImageOptions::card_details(),
                                 _ => ImageOptions::holodelta(),
                             };
                             *export_format.write() = match ev.value().as_str() {
@@ -596,6 +633,8 @@ pub fn ExportPage() -> Element {
                                 "hocg_tts" => Some(DeckType::TabletopSim),
                                 "proxy_sheets" => Some(DeckType::ProxySheets),
                                 "price_check" => Some(DeckType::PriceCheck),
+                                "decklist" => Some(DeckType::Decklist),
+                                "bundle" => Some(DeckType::Bundle),
                                 _ => None,
                             };
                             *SHOW_PRICE.write() = *export_format.read() == Some(DeckType::PriceCheck);
@@ -635,6 +674,16 @@ pub fn ExportPage() -> Element {
                             selected: *export_format.read() == Some(DeckType::PriceCheck),
                             "Price check"
                         }
+                        option {
+                            value: "decklist",
+                            selected: *export_format.read() == Some(DeckType::Decklist),
+                            "Decklist sheet (PDF)"
+                        }
+                        option {
+                            value: "bundle",
+                            selected: *export_format.read() == Some(DeckType::Bundle),
+                            "All formats (ZIP)"
+                        }
                     }
                 }
             }
@@ -665,8 +714,89 @@ pub fn ExportPage() -> Element {
                     show_price: SHOW_PRICE.signal(),
                 }
             }
+            if *export_format.read() == Some(DeckType::Decklist) {
+                decklist::Export { common_deck: COMMON_DECK.signal(), db: CARDS_DB.signal() }
+            }
+            if *export_format.read() == Some(DeckType::Bundle) {
+                bundle::Export { common_deck: COMMON_DECK.signal(), db: CARDS_DB.signal() }
+            }
+        }
+    }
+}
+
+/// Deck file formats that can be recognized from the file content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckFileFormat {
+    HoloDelta,
+    HoloDuel,
+    TabletopSim,
+    SavedDeck,
+}
+
+impl DeckFileFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeckFileFormat::HoloDelta => "holoDelta",
+            DeckFileFormat::HoloDuel => "HoloDuel",
+            DeckFileFormat::TabletopSim => "Tabletop Simulator (by Noodlebrain)",
+            DeckFileFormat::SavedDeck => "hololive OCG Deck Converter Saved deck",
+        }
+    }
+
+    pub fn track_name(&self) -> &'static str {
+        match self {
+            DeckFileFormat::HoloDelta => "holoDelta",
+            DeckFileFormat::HoloDuel => "HoloDuel",
+            DeckFileFormat::TabletopSim => "Tabletop Sim",
+            DeckFileFormat::SavedDeck => "Saved deck",
         }
     }
+
+    pub fn image_options(&self) -> ImageOptions {
+        match self {
+            DeckFileFormat::HoloDelta => ImageOptions::holodelta(),
+            DeckFileFormat::HoloDuel => ImageOptions::holodelta(),
+            DeckFileFormat::TabletopSim => ImageOptions::deck_log(),
+            DeckFileFormat::SavedDeck => ImageOptions::card_details(),
+        }
+    }
+}
+
+/// Try every known deck file format, in order
+pub fn detect_deck_file(
+    contents: &[u8],
+    db: &CardsDatabase,
+) -> Option<(DeckOrPile, DeckFileFormat)> {
+    // holoDelta
+    if let Ok(deck) = holodelta::Deck::from_file(contents) {
+        return Some((
+            DeckOrPile::Deck(holodelta::Deck::to_common_deck(deck, db)),
+            DeckFileFormat::HoloDelta,
+        ));
+    }
+
+    // HoloDuel
+    if let Ok(deck) = holoduel::Deck::from_file(contents) {
+        return Some((
+            DeckOrPile::Deck(holoduel::Deck::to_common_deck(deck, db)),
+            DeckFileFormat::HoloDuel,
+        ));
+    }
+
+    // Tabletop Sim
+    if let Ok(deck) = tabletop_sim::Deck::from_file(contents) {
+        return Some((
+            DeckOrPile::Deck(tabletop_sim::Deck::to_common_deck(deck, db)),
+            DeckFileFormat::TabletopSim,
+        ));
+    }
+
+    // Saved deck file
+    if let Ok(deck) = serde_json::from_slice::<SaveDeckOrPile>(contents) {
+        return Some((deck.to_deck_or_pile(db), DeckFileFormat::SavedDeck));
+    }
+
+    None
 }
 
 #[component]
@@ -694,82 +824,17 @@ pub fn UnknownImport(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDataba
             *file_name.write() = file.name();
 
             if let Ok(contents) = file.read_bytes().await {
-                let contents = contents.to_vec();
-
-                // holoDelta
-                let deck = holodelta::Deck::from_file(&contents);
-                debug!("{:?}", deck);
-                if let Ok(deck) = deck {
-                    *common_deck.write() =
-                        DeckOrPile::Deck(holodelta::Deck::to_common_deck(deck, &db.read()));
-                    *PREVIEW_IMAGE_OPTIONS.write() = ImageOptions::holodelta();
-                    *deck_success.write() = "Deck file format: holoDelta".into();
-                    track_event(
-                        EventType::Import("Unknown".into()),
-                        EventData {
-                            format: "Unknown",
-                            file_format: Some("holoDelta"),
-                            error: None,
-                        },
-                    );
-                    AUTO_SAVE_DECK.write().replace(common_deck.read().clone());
-                    return;
-                }
-
-                // HoloDuel
-                let deck = holoduel::Deck::from_file(&contents);
-                debug!("{:?}", deck);
-                if let Ok(deck) = deck {
-                    *common_deck.write() =
-                        DeckOrPile::Deck(holoduel::Deck::to_common_deck(deck, &db.read()));
-                    *PREVIEW_IMAGE_OPTIONS.write() = ImageOptions::holodelta();
-                    *deck_success.write() = "Deck file format: HoloDuel".into();
-                    track_event(
-                        EventType::Import("Unknown".into()),
-                        EventData {
-                            format: "Unknown",
-                            file_format: Some("HoloDuel"),
-                            error: None,
-                        },
-                    );
-                    AUTO_SAVE_DECK.write().replace(common_deck.read().clone());
-                    return;
-                }
-
-                // Tabletop Sim
-                let deck = tabletop_sim::Deck::from_file(&contents);
-                debug!("{:?}", deck);
-                if let Ok(deck) = deck {
-                    *common_deck.write() =
-                        DeckOrPile::Deck(tabletop_sim::Deck::to_common_deck(deck, &db.read()));
-                    *PREVIEW_IMAGE_OPTIONS.write() = ImageOptions::deck_log();
-                    *deck_success.write() =
-                        "Deck file format: Tabletop Simulator (by Noodlebrain)".into();
-                    track_event(
-                        EventType::Import("Unknown".into()),
-                        EventData {
-                            format: "Unknown",
-                            file_format: Some("Tabletop Sim"),
-                            error: None,
-                        },
-                    );
-                    AUTO_SAVE_DECK.write().replace(common_deck.read().clone());
-                    return;
-                }
-
-                // Saved deck file
-                let deck = serde_json::from_slice::<SaveDeckOrPile>(&contents);
+                let deck = detect_deck_file(&contents, &db.read());
                 debug!("{:?}", deck);
-                if let Ok(deck) = deck {
-                    *common_deck.write() = deck.to_deck_or_pile(&db.read());
-                    *PREVIEW_IMAGE_OPTIONS.write() = ImageOptions::card_details();
-                    *deck_success.write() =
-                        "Deck file format: hololive OCG Deck Converter Saved deck".into();
+                if let Some((deck, file_format)) = deck {
+                    *common_deck.write() = deck;
+                    *PREVIEW_IMAGE_OPTIONS.write() = file_format.image_options();
+                    *deck_success.write() = format!("Deck file format: {}", file_format.name());
                     track_event(
                         EventType::Import("Unknown".into()),
                         EventData {
                             format: "Unknown",
-                            file_format: Some("Saved deck"),
+                            file_format: Some(file_format.track_name()),
                             error: None,
                         },
                     );
@@ -828,7 +893,7 @@ pub enum CardType {
     Main,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, PartialOrd, Ord)]
 pub enum CardLanguage {
     Japanese,
     English,
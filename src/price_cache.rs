@@ -0,0 +1,93 @@
+use jiff::Timestamp;
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+
+use crate::sources::{
+    price_check::{PriceCache, PriceCacheKey},
+    price_provider::CardPrice,
+};
+
+const PRICE_CACHE_DB_NAME: &str = "hocg-deck-convert.price_cache";
+const PRICE_CACHE_DB_VERSION: u32 = 1;
+const PRICES_STORE_NAME: &str = "prices";
+/// All the prices are saved together, they are fetched together
+const SNAPSHOT_KEY: &str = "shared";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PriceSnapshot {
+    key: String,
+    prices: Vec<(PriceCacheKey, Timestamp, CardPrice)>,
+}
+
+async fn open_price_cache_db() -> Result<Rexie, String> {
+    Rexie::builder(PRICE_CACHE_DB_NAME)
+        .version(PRICE_CACHE_DB_VERSION)
+        .add_object_store(ObjectStore::new(PRICES_STORE_NAME).key_path("key"))
+        .build()
+        .await
+        .map_err(|err| format!("Could not open price cache: {err}"))
+}
+
+/// The prices from the last session, empty when there is none
+pub async fn load() -> Result<PriceCache, String> {
+    let db = open_price_cache_db().await?;
+    let transaction = db
+        .transaction(&[PRICES_STORE_NAME], TransactionMode::ReadOnly)
+        .map_err(|err| format!("Could not open price cache transaction: {err}"))?;
+    let store = transaction
+        .store(PRICES_STORE_NAME)
+        .map_err(|err| format!("Could not open price cache store: {err}"))?;
+
+    let value = store
+        .get(SNAPSHOT_KEY.into())
+        .await
+        .map_err(|err| format!("Could not read price cache: {err}"))?;
+    transaction
+        .done()
+        .await
+        .map_err(|err| format!("Price cache read transaction failed: {err}"))?;
+
+    let Some(value) = value else {
+        return Ok(PriceCache::new());
+    };
+    // saved before the prices had their currency, they are fetched again
+    let Ok(snapshot) = from_value::<PriceSnapshot>(value) else {
+        return Ok(PriceCache::new());
+    };
+    Ok(snapshot
+        .prices
+        .into_iter()
+        .map(|(key, time, price)| (key, (time, price)))
+        .collect())
+}
+
+/// Replaces the saved prices. The price list is left out, it's already saved with its file.
+pub async fn store(prices: &PriceCache) -> Result<(), String> {
+    let snapshot = PriceSnapshot {
+        key: SNAPSHOT_KEY.into(),
+        prices: prices
+            .iter()
+            .filter(|(key, _)| !matches!(key, PriceCacheKey::PriceList(_)))
+            .map(|(key, (time, price))| (key.clone(), *time, *price))
+            .collect(),
+    };
+    let value =
+        to_value(&snapshot).map_err(|err| format!("Could not encode price cache: {err}"))?;
+
+    let db = open_price_cache_db().await?;
+    let transaction = db
+        .transaction(&[PRICES_STORE_NAME], TransactionMode::ReadWrite)
+        .map_err(|err| format!("Could not open price cache transaction: {err}"))?;
+    let store = transaction
+        .store(PRICES_STORE_NAME)
+        .map_err(|err| format!("Could not open price cache store: {err}"))?;
+    store
+        .put(&value, None)
+        .await
+        .map_err(|err| format!("Could not save price cache: {err}"))?;
+    transaction
+        .done()
+        .await
+        .map_err(|err| format!("Price cache write transaction failed: {err}"))
+}
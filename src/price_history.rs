@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+
+const HISTORY_DB_NAME: &str = "hocg-deck-convert.price_history";
+const HISTORY_DB_VERSION: u32 = 1;
+const SERIES_STORE_NAME: &str = "series";
+
+/// A price from this time, in seconds since the epoch
+pub type PricePoint = (i64, f64);
+
+/// The prices of a card, only the changes are kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PriceSeries {
+    key: String,
+    points: Vec<PricePoint>,
+}
+
+async fn open_history_db() -> Result<Rexie, String> {
+    Rexie::builder(HISTORY_DB_NAME)
+        .version(HISTORY_DB_VERSION)
+        .add_object_store(ObjectStore::new(SERIES_STORE_NAME).key_path("key"))
+        .build()
+        .await
+        .map_err(|err| format!("Could not open price history: {err}"))
+}
+
+/// Adds the prices of a snapshot to their series, when they changed
+pub async fn record(snapshot: &[(String, f64)], time: i64) -> Result<(), String> {
+    let db = open_history_db().await?;
+    let transaction = db
+        .transaction(&[SERIES_STORE_NAME], TransactionMode::ReadWrite)
+        .map_err(|err| format!("Could not open price history transaction: {err}"))?;
+    let store = transaction
+        .store(SERIES_STORE_NAME)
+        .map_err(|err| format!("Could not open price history store: {err}"))?;
+
+    for (key, price) in snapshot {
+        let value = store
+            .get(key.into())
+            .await
+            .map_err(|err| format!("Could not read price history: {err}"))?;
+        let mut series = value
+            .and_then(|value| from_value::<PriceSeries>(value).ok())
+            .unwrap_or_else(|| PriceSeries {
+                key: key.clone(),
+                points: vec![],
+            });
+        if series.points.last().is_some_and(|(_, last)| last == price) {
+            continue;
+        }
+        series.points.push((time, *price));
+        let value =
+            to_value(&series).map_err(|err| format!("Could not encode price history: {err}"))?;
+        store
+            .put(&value, None)
+            .await
+            .map_err(|err| format!("Could not save price history: {err}"))?;
+    }
+
+    transaction
+        .done()
+        .await
+        .map_err(|err| format!("Price history write transaction failed: {err}"))
+}
+
+/// The price changes of each key, oldest first. Keys without history are left out.
+pub async fn load(keys: &[String]) -> Result<HashMap<String, Vec<PricePoint>>, String> {
+    let db = open_history_db().await?;
+    let transaction = db
+        .transaction(&[SERIES_STORE_NAME], TransactionMode::ReadOnly)
+        .map_err(|err| format!("Could not open price history transaction: {err}"))?;
+    let store = transaction
+        .store(SERIES_STORE_NAME)
+        .map_err(|err| format!("Could not open price history store: {err}"))?;
+
+    let mut history = HashMap::with_capacity(keys.len());
+    for key in keys {
+        let value = store
+            .get(key.into())
+            .await
+            .map_err(|err| format!("Could not read price history: {err}"))?;
+        if let Some(series) = value.and_then(|value| from_value::<PriceSeries>(value).ok()) {
+            history.insert(series.key, series.points);
+        }
+    }
+
+    transaction
+        .done()
+        .await
+        .map_err(|err| format!("Price history read transaction failed: {err}"))?;
+    Ok(history)
+}
+
+/// The price at `time`, from the last change before it
+pub fn value_at(points: &[PricePoint], time: i64) -> Option<f64> {
+    points
+        .iter()
+        .take_while(|(t, _)| *t <= time)
+        .last()
+        .map(|(_, value)| *value)
+}
+
+/// The value of several cards over time, at each change of their prices.
+/// A card counts from its first known price.
+pub fn total_series(cards: &[(u32, &[PricePoint])]) -> Vec<PricePoint> {
+    let mut times: Vec<i64> = cards
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|(t, _)| *t))
+        .collect();
+    times.sort_unstable();
+    times.dedup();
+
+    times
+        .into_iter()
+        .map(|time| {
+            let total = cards
+                .iter()
+                .filter_map(|(amount, points)| Some(*amount as f64 * value_at(points, time)?))
+                .sum();
+            (time, total)
+        })
+        .collect()
+}
@@ -0,0 +1,369 @@
+use dioxus::{logger::tracing::debug, prelude::*};
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::{CardsDatabase, DeckLike, DeckOrPile, DeckType, bundle, json};
+use crate::{
+    CardLanguage, DeckFileFormat, EventType, PREVIEW_CARD_LANG, archive::ZipArchive,
+    components::save_load::SaveDeckOrPile, detect_deck_file, download_file, track_event,
+    tracker::TrackEvent,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+enum BatchTarget {
+    HoloDelta,
+    HoloDuel,
+    TabletopSim,
+    SavedDeck,
+}
+
+impl BatchTarget {
+    fn deck_type(&self) -> Option<DeckType> {
+        match self {
+            BatchTarget::HoloDelta => Some(DeckType::HoloDelta),
+            BatchTarget::HoloDuel => Some(DeckType::HoloDuel),
+            BatchTarget::TabletopSim => Some(DeckType::TabletopSim),
+            BatchTarget::SavedDeck => None,
+        }
+    }
+
+    fn export_id(&self) -> &'static str {
+        match self {
+            BatchTarget::HoloDelta => "holodelta",
+            BatchTarget::HoloDuel => "holoduel",
+            BatchTarget::TabletopSim => "hocg_tts",
+            BatchTarget::SavedDeck => "saved_deck",
+        }
+    }
+
+    /// The same as the bundle, a saved deck keeps any card
+    fn allow_unreleased(&self) -> bool {
+        self.deck_type().is_none_or(bundle::allow_unreleased)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BatchFileReport {
+    file_name: String,
+    result: Result<BatchFileOutput, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BatchFileOutput {
+    source_format: DeckFileFormat,
+    output_name: String,
+    warnings: Vec<String>,
+    unknown_cards: Vec<String>,
+}
+
+/// Convert a single deck into the target format
+fn convert_deck(
+    deck: &DeckOrPile,
+    target: BatchTarget,
+    db: &CardsDatabase,
+) -> Result<(String, Vec<u8>), String> {
+    let file_name = deck.file_name(db);
+    if let Some(deck_type) = target.deck_type() {
+        let deck = json::Deck::from_common_deck(deck_type, deck.clone().into_deck(db), db)
+            .ok_or("Missing an Oshi card.")?;
+        let file = deck.to_file().map_err(|e| e.to_string())?;
+        Ok((format!("{file_name}.{}.json", target.export_id()), file))
+    } else {
+        let save = SaveDeckOrPile::from_deck_or_pile(deck, db);
+        let file = serde_json::to_vec_pretty(&save)
+            .map_err(|e| format!("Could not encode save file: {e}"))?;
+        let ext = match deck {
+            DeckOrPile::Deck(_) => "saved_deck",
+            DeckOrPile::Pile(_) => "saved_pile",
+        };
+        Ok((format!("{file_name}.{ext}.json"), file))
+    }
+}
+
+fn batch_convert(
+    files: &[(String, Vec<u8>)],
+    target: BatchTarget,
+    db: &CardsDatabase,
+    card_lang: CardLanguage,
+) -> (Vec<BatchFileReport>, ZipArchive) {
+    let mut archive = ZipArchive::new();
+    let reports = files
+        .iter()
+        .map(|(file_name, contents)| {
+            let result = detect_deck_file(contents, db)
+                .ok_or_else(|| "Cannot parse deck file".to_string())
+                .and_then(|(deck, source_format)| {
+                    let (output_name, file) = convert_deck(&deck, target, db)?;
+                    let output_name = archive.add_file(&output_name, &file);
+                    Ok(BatchFileOutput {
+                        source_format,
+                        output_name,
+                        warnings: deck.validate(db, target.allow_unreleased(), card_lang),
+                        unknown_cards: deck
+                            .all_cards()
+                            .filter(|c| c.is_unknown(db))
+                            .map(|c| c.card_number.clone())
+                            .unique()
+                            .collect(),
+                    })
+                });
+            BatchFileReport {
+                file_name: file_name.clone(),
+                result,
+            }
+        })
+        .collect_vec();
+
+    archive.add_file("report.txt", batch_report_text(&reports).as_bytes());
+
+    (reports, archive)
+}
+
+fn batch_report_text(reports: &[BatchFileReport]) -> String {
+    let mut text = String::new();
+    for report in reports {
+        match &report.result {
+            Ok(output) => {
+                text.push_str(&format!(
+                    "[OK] {} ({}) -> {}\n",
+                    report.file_name,
+                    output.source_format.name(),
+                    output.output_name
+                ));
+                for warn in &output.warnings {
+                    text.push_str(&format!("    warning: {warn}\n"));
+                }
+                if !output.unknown_cards.is_empty() {
+                    text.push_str(&format!(
+                        "    unknown cards: {}\n",
+                        output.unknown_cards.join(", ")
+                    ));
+                }
+            }
+            Err(error) => {
+                text.push_str(&format!("[ERROR] {}: {error}\n", report.file_name));
+            }
+        }
+    }
+    text
+}
+
+#[component]
+pub fn Import(db: Signal<CardsDatabase>) -> Element {
+    #[derive(Serialize)]
+    struct EventData {
+        format: &'static str,
+        target: BatchTarget,
+        language: CardLanguage,
+        file_count: usize,
+        error_count: usize,
+    }
+    impl TrackEvent for EventData {}
+
+    let mut target = use_signal(|| BatchTarget::HoloDelta);
+    let mut files = use_signal(Vec::<(String, Vec<u8>)>::new);
+    let mut reports = use_signal(Vec::<BatchFileReport>::new);
+    let mut deck_error = use_signal(String::new);
+    let mut loading = use_signal(|| false);
+
+    let from_files = move |event: Event<FormData>| async move {
+        *deck_error.write() = String::new();
+        reports.write().clear();
+        files.write().clear();
+        for file in &event.files() {
+            match file.read_bytes().await {
+                Ok(contents) => files.write().push((file.name(), contents.to_vec())),
+                Err(e) => *deck_error.write() = format!("Could not read file: {e}"),
+            }
+        }
+    };
+
+    let convert = move |_| {
+        *loading.write() = true;
+        *deck_error.write() = String::new();
+
+        let file_name = format!("batch.{}.zip", target.read().export_id());
+        let (new_reports, archive) = batch_convert(
+            &files.read(),
+            *target.read(),
+            &db.read(),
+            *PREVIEW_CARD_LANG.read(),
+        );
+        debug!("{:?}", new_reports);
+
+        let error_count = new_reports.iter().filter(|r| r.result.is_err()).count();
+        if error_count < new_reports.len() {
+            download_file(&file_name, &archive.finish()[..]);
+        } else {
+            *deck_error.write() = "No deck file could be converted.".into();
+        }
+        track_event(
+            EventType::Export("Batch conversion".into()),
+            EventData {
+                format: "Batch conversion",
+                target: *target.read(),
+                language: *PREVIEW_CARD_LANG.read(),
+                file_count: new_reports.len(),
+                error_count,
+            },
+        );
+        *reports.write() = new_reports;
+
+        *loading.write() = false;
+    };
+
+    rsx! {
+        div { class: "field",
+            label { "for": "batch_target", class: "label", "Convert to" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "batch_target",
+                        oninput: move |ev| {
+                            reports.write().clear();
+                            *target.write() = match ev.value().as_str() {
+                                "holo_delta" => BatchTarget::HoloDelta,
+                                "holo_duel" => BatchTarget::HoloDuel,
+                                "hocg_tts" => BatchTarget::TabletopSim,
+                                "saved_deck" => BatchTarget::SavedDeck,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            value: "holo_delta",
+                            selected: *target.read() == BatchTarget::HoloDelta,
+                            "holoDelta"
+                        }
+                        option {
+                            value: "hocg_tts",
+                            selected: *target.read() == BatchTarget::TabletopSim,
+                            "Tabletop Simulator (by Noodlebrain)"
+                        }
+                        option {
+                            value: "holo_duel",
+                            selected: *target.read() == BatchTarget::HoloDuel,
+                            "HoloDuel (unmaintained)"
+                        }
+                        option {
+                            value: "saved_deck",
+                            selected: *target.read() == BatchTarget::SavedDeck,
+                            "Saved deck"
+                        }
+                    }
+                }
+            }
+        }
+
+        div { class: "field",
+            label { "for": "batch_card_language", class: "label", "Card language" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "batch_card_language",
+                        oninput: move |ev| {
+                            reports.write().clear();
+                            *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
+                                "jp" => CardLanguage::Japanese,
+                                "en" => CardLanguage::English,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: *PREVIEW_CARD_LANG.read() == CardLanguage::Japanese,
+                            value: "jp",
+                            "Japanese"
+                        }
+                        option {
+                            selected: *PREVIEW_CARD_LANG.read() == CardLanguage::English,
+                            value: "en",
+                            "English"
+                        }
+                    }
+                }
+            }
+            p { class: "help", "The cards are validated for this language." }
+        }
+
+        div { class: "field",
+            div { class: "control",
+                div {
+                    class: "file",
+                    class: if !files.read().is_empty() { "has-name" },
+                    label { "for": "batch_import_files", class: "file-label",
+                        input {
+                            id: "batch_import_files",
+                            r#type: "file",
+                            class: "file-input",
+                            multiple: true,
+                            onchange: from_files,
+                        }
+                        span { class: "file-cta",
+                            span { class: "file-icon",
+                                i { class: "fa-solid fa-upload" }
+                            }
+                            span { class: "file-label", " Load files… " }
+                        }
+                        if !files.read().is_empty() {
+                            span { class: "file-name", "{files.read().len()} files" }
+                        }
+                    }
+                }
+            }
+        }
+
+        div { class: "field",
+            div { class: "control",
+                button {
+                    r#type: "button",
+                    class: "button",
+                    class: if *loading.read() { "is-loading" },
+                    disabled: files.read().is_empty() || *loading.read(),
+                    onclick: convert,
+                    span { class: "icon",
+                        i { class: "fa-solid fa-file-zipper" }
+                    }
+                    span { "Convert and download ZIP" }
+                }
+            }
+            p { class: "help is-danger", "{deck_error}" }
+        }
+
+        if !reports.read().is_empty() {
+            div { class: "content",
+                ul {
+                    for report in reports.read().iter() {
+                        li {
+                            match &report.result {
+                                Ok(output) => rsx! {
+                                    span { class: "icon has-text-success",
+                                        i { class: "fa-solid fa-check" }
+                                    }
+                                    "{report.file_name} ({output.source_format.name()}) → {output.output_name}"
+                                    if !output.warnings.is_empty() || !output.unknown_cards.is_empty() {
+                                        ul {
+                                            for warn in &output.warnings {
+                                                li { class: "has-text-warning-dark", "{warn}" }
+                                            }
+                                            if !output.unknown_cards.is_empty() {
+                                                li { class: "has-text-warning-dark",
+                                                    "Unknown cards: "
+                                                    {output.unknown_cards.join(", ")}
+                                                }
+                                            }
+                                        }
+                                    }
+                                },
+                                Err(error) => rsx! {
+                                    span { class: "icon has-text-danger",
+                                        i { class: "fa-solid fa-xmark" }
+                                    }
+                                    "{report.file_name}: {error}"
+                                },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use dioxus::prelude::*;
+use serde::Serialize;
+
+use super::{CardsDatabase, CommonCard, DeckLike, DeckOrPile, DeckType, json, proxy_sheets};
+use crate::{
+    CardLanguage, EventType, PREVIEW_CARD_LANG,
+    archive::ZipArchive,
+    components::{
+        deck_validation::{DeckValidation, has_missing_proxies},
+        save_load::SaveDeckOrPile,
+    },
+    download_file, track_event,
+    tracker::TrackEvent,
+};
+
+/// (deck type, format name, export id, allow unreleased)
+const JSON_FORMATS: [(DeckType, &str, &str, bool); 3] = [
+    (DeckType::HoloDelta, "holoDelta", "holodelta", true),
+    (DeckType::TabletopSim, "Tabletop Sim", "hocg_tts", false),
+    (DeckType::HoloDuel, "HoloDuel", "holoduel", false),
+];
+
+/// The export of this format accepts unreleased cards
+pub(super) fn allow_unreleased(deck_type: DeckType) -> bool {
+    JSON_FORMATS
+        .iter()
+        .find(|(format, ..)| *format == deck_type)
+        .is_none_or(|(.., allow_unreleased)| *allow_unreleased)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BundleEntry {
+    format: &'static str,
+    file_name: Option<String>,
+    warnings: Vec<String>,
+}
+
+pub(super) fn card_name(card: &CommonCard, db: &CardsDatabase, card_lang: CardLanguage) -> String {
+    card.card_info(db)
+        .and_then(|c| match card_lang {
+            CardLanguage::Japanese => c.name.japanese.as_deref().or(c.name.english.as_deref()),
+            CardLanguage::English => c.name.english.as_deref().or(c.name.japanese.as_deref()),
+        })
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+/// Plain text list of the cards, one line per card
+fn text_list(deck: &DeckOrPile, db: &CardsDatabase, card_lang: CardLanguage) -> String {
+    let mut text = format!("{}\n", deck.required_deck_name(db));
+
+    let mut section = |title: &str, cards: &[CommonCard]| {
+        let total: u32 = cards.iter().map(|c| c.amount).sum();
+        text.push_str(&format!("\n{title} ({total})\n"));
+        for card in cards {
+            text.push_str(&format!(
+                "{} {} {}\n",
+                card.amount,
+                card.card_number,
+                card_name(card, db, card_lang)
+            ));
+        }
+    };
+
+    match deck {
+        DeckOrPile::Deck(deck) => {
+            section("Oshi", deck.oshi.as_slice());
+            section("Main deck", &deck.main_deck);
+            section("Cheer deck", &deck.cheer_deck);
+        }
+        DeckOrPile::Pile(pile) => section("Cards", &pile.cards),
+    }
+
+    text
+}
+
+fn manifest_text(entries: &[BundleEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        match &entry.file_name {
+            Some(file_name) => text.push_str(&format!("{}: {file_name}\n", entry.format)),
+            None => text.push_str(&format!("{}: not exported\n", entry.format)),
+        }
+        for warn in &entry.warnings {
+            text.push_str(&format!("    - {warn}\n"));
+        }
+    }
+    text
+}
+
+/// Runs every exporter on the deck, and packages the files into one archive
+async fn export_bundle(
+    deck: &DeckOrPile,
+    db: &CardsDatabase,
+    card_lang: CardLanguage,
+) -> (ZipArchive, Vec<BundleEntry>) {
+    let mut archive = ZipArchive::new();
+    let mut entries = vec![];
+    let file_name = deck.file_name(db);
+
+    // json decks
+    for (deck_type, format, export_id, allow_unreleased) in JSON_FORMATS {
+        let mut warnings = deck.validate(db, allow_unreleased, card_lang);
+        let file = json::Deck::from_common_deck(deck_type, deck.clone().into_deck(db), db)
+            .ok_or_else(|| "Missing an Oshi card.".to_string())
+            .and_then(|d| d.to_file().map_err(|e| e.to_string()));
+        let file_name = match file {
+            Ok(file) => Some(archive.add_file(&format!("{file_name}.{export_id}.json"), &file)),
+            Err(e) => {
+                warnings.push(e);
+                None
+            }
+        };
+        entries.push(BundleEntry {
+            format,
+            file_name,
+            warnings,
+        });
+    }
+
+    // saved deck
+    let save = SaveDeckOrPile::from_deck_or_pile(deck, db);
+    let mut warnings = vec![];
+    let unknown_cards = deck.all_cards().filter(|c| c.is_unknown(db)).count();
+    if unknown_cards > 0 {
+        warnings.push(format!("{unknown_cards} unknown cards are not saved."));
+    }
+    let ext = match deck {
+        DeckOrPile::Deck(_) => "saved_deck",
+        DeckOrPile::Pile(_) => "saved_pile",
+    };
+    let save_file_name = match serde_json::to_vec_pretty(&save) {
+        Ok(file) => Some(archive.add_file(&format!("{file_name}.{ext}.json"), &file)),
+        Err(e) => {
+            warnings.push(format!("Could not encode save file: {e}"));
+            None
+        }
+    };
+    entries.push(BundleEntry {
+        format: "Saved deck",
+        file_name: save_file_name,
+        warnings,
+    });
+
+    // text list
+    let text = text_list(deck, db, card_lang);
+    entries.push(BundleEntry {
+        format: "Text list",
+        file_name: Some(archive.add_file(&format!("{file_name}.txt"), text.as_bytes())),
+        warnings: vec![],
+    });
+
+    // proxy sheets
+    let settings = proxy_sheets::ProxySheetSettings::with_defaults(card_lang);
+    let mut warnings = vec![];
+    if has_missing_proxies(deck, db, card_lang) {
+        warnings.push(match card_lang {
+            CardLanguage::Japanese => "Missing Japanese proxies.".into(),
+            CardLanguage::English => "Missing English proxies.".into(),
+        });
+    }
+    let pdf_file_name = match proxy_sheets::generate_pdf(deck, db, settings, |_| {}).await {
+        Ok(output) => {
+            for skipped in output.skipped {
+                warnings.push(format!("Skipped {}: {}", skipped.name, skipped.error));
+            }
+            Some(archive.add_file(&settings.file_name(deck, db), &output.file))
+        }
+        Err(e) => {
+            warnings.push(e.to_string());
+            None
+        }
+    };
+    entries.push(BundleEntry {
+        format: "Proxy sheets",
+        file_name: pdf_file_name,
+        warnings,
+    });
+
+    archive.add_file("manifest.txt", manifest_text(&entries).as_bytes());
+
+    (archive, entries)
+}
+
+#[component]
+pub fn Export(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
+    #[derive(Serialize)]
+    struct EventData {
+        format: &'static str,
+        language: CardLanguage,
+        file_count: usize,
+        warning_count: usize,
+    }
+    impl TrackEvent for EventData {}
+
+    let card_lang = PREVIEW_CARD_LANG.signal();
+    let mut entries = use_signal(Vec::<BundleEntry>::new);
+    let mut loading = use_signal(|| false);
+
+    let download_bundle = move |_| async move {
+        *loading.write() = true;
+        entries.write().clear();
+
+        let deck = common_deck.read().clone();
+        let file_name = format!("{}.all_formats.zip", deck.file_name(&db.read()));
+        let (archive, new_entries) = export_bundle(&deck, &db.read(), *card_lang.read()).await;
+        download_file(&file_name, &archive.finish()[..]);
+
+        track_event(
+            EventType::Export("All formats".into()),
+            EventData {
+                format: "All formats",
+                language: *card_lang.read(),
+                file_count: new_entries.iter().filter(|e| e.file_name.is_some()).count(),
+                warning_count: new_entries.iter().map(|e| e.warnings.len()).sum(),
+            },
+        );
+        *entries.write() = new_entries;
+
+        *loading.write() = false;
+    };
+
+    rsx! {
+        DeckValidation {
+            deck_check: true,
+            proxy_check: true,
+            allow_unreleased: true,
+            allow_pile: true,
+            card_lang,
+            db,
+            common_deck,
+        }
+
+        div { class: "field",
+            label { "for": "bundle_card_language", class: "label", "Card language" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "bundle_card_language",
+                        oninput: move |ev| {
+                            *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
+                                "jp" => CardLanguage::Japanese,
+                                "en" => CardLanguage::English,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: *PREVIEW_CARD_LANG.read() == CardLanguage::Japanese,
+                            value: "jp",
+                            "Japanese"
+                        }
+                        option {
+                            selected: *PREVIEW_CARD_LANG.read() == CardLanguage::English,
+                            value: "en",
+                            "English"
+                        }
+                    }
+                }
+            }
+            p { class: "help", "Used for the text list and the proxy sheets." }
+        }
+
+        div { class: "field",
+            div { class: "control",
+                button {
+                    r#type: "button",
+                    class: "button",
+                    class: if *loading.read() { "is-loading" },
+                    disabled: common_deck.read().is_empty() || *loading.read(),
+                    onclick: download_bundle,
+                    span { class: "icon",
+                        i { class: "fa-solid fa-file-zipper" }
+                    }
+                    span { "Download all formats" }
+                }
+            }
+        }
+
+        if !entries.read().is_empty() {
+            div { class: "content",
+                ul {
+                    for entry in entries.read().iter() {
+                        li {
+                            if let Some(file_name) = &entry.file_name {
+                                span { class: "icon has-text-success",
+                                    i { class: "fa-solid fa-check" }
+                                }
+                                "{entry.format}: {file_name}"
+                            } else {
+                                span { class: "icon has-text-danger",
+                                    i { class: "fa-solid fa-xmark" }
+                                }
+                                "{entry.format}: not exported"
+                            }
+                            if !entry.warnings.is_empty() {
+                                ul {
+                                    for warn in &entry.warnings {
+                                        li { class: "has-text-warning-dark", "{warn}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
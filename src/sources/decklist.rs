@@ -0,0 +1,663 @@
+use std::error::Error;
+
+use ::image::{DynamicImage, RgbaImage};
+use dioxus::prelude::*;
+use gloo::utils::{document, window};
+use indexmap::IndexMap;
+use printpdf::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use super::{CardsDatabase, CommonCard, DeckLike, DeckOrPile, bundle, proxy_sheets};
+use crate::{
+    CardLanguage, EventType, PREVIEW_CARD_LANG, components::deck_validation::DeckValidation,
+    download_file, track_event, tracker::TrackEvent,
+};
+
+pub(super) const DPI: f32 = 200.0;
+const MARGIN: f64 = 12.0;
+const ROW_HEIGHT: f64 = 6.5;
+const FONT: &str = "sans-serif";
+const PLAYER_INFO_KEY: &str = "hocg-deck-convert.decklist_player";
+
+/// Saved for the next event
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct PlayerInfo {
+    name: String,
+    player_id: String,
+}
+
+impl PlayerInfo {
+    fn load() -> Self {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|ls| ls.get_item(PLAYER_INFO_KEY).ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self) -> Option<()> {
+        let ls = window().local_storage().ok()??;
+        let json = serde_json::to_string(self).ok()?;
+        ls.set_item(PLAYER_INFO_KEY, &json).ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DecklistSettings {
+    card_lang: CardLanguage,
+    paper_size: proxy_sheets::PaperSize,
+    player: PlayerInfo,
+    event: String,
+    date: String,
+}
+
+/// (amount, card number, name), one row per card number
+fn section_rows(
+    cards: &[CommonCard],
+    db: &CardsDatabase,
+    card_lang: CardLanguage,
+) -> Vec<(u32, String, String)> {
+    let mut rows: IndexMap<&String, (u32, String)> = IndexMap::new();
+    for card in cards {
+        rows.entry(&card.card_number)
+            .or_insert_with(|| (0, bundle::card_name(card, db, card_lang)))
+            .0 += card.amount;
+    }
+    rows.into_iter()
+        .map(|(card_number, (amount, name))| (amount, card_number.clone(), name))
+        .collect()
+}
+
+/// A page drawn with the browser canvas, for the Japanese names. Units are in mm.
+pub(super) struct Page {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+    pub(super) width: f64,
+    pub(super) height: f64,
+}
+
+impl Page {
+    pub(super) fn new(width: Mm, height: Mm) -> Result<Self, Box<dyn Error>> {
+        Self::with_dpi(width, height, DPI, Some("white"))
+    }
+
+    /// A page at another resolution, transparent without a background
+    pub(super) fn with_dpi(
+        width: Mm,
+        height: Mm,
+        dpi: f32,
+        background: Option<&str>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let px_per_mm = (dpi / 25.4) as f64;
+        let canvas = document()
+            .create_element("canvas")
+            .map_err(|e| format!("{e:?}"))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|e| format!("{e:?}"))?;
+        canvas.set_width((width.0 as f64 * px_per_mm).round() as u32);
+        canvas.set_height((height.0 as f64 * px_per_mm).round() as u32);
+        let ctx = canvas
+            .get_context("2d")
+            .map_err(|e| format!("{e:?}"))?
+            .ok_or("Canvas is not supported")?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(|e| format!("{e:?}"))?;
+
+        if let Some(background) = background {
+            ctx.set_fill_style_str(background);
+            ctx.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+        }
+        ctx.scale(px_per_mm, px_per_mm)
+            .map_err(|e| format!("{e:?}"))?;
+        ctx.set_text_baseline("middle");
+
+        Ok(Self {
+            canvas,
+            ctx,
+            width: width.0 as f64,
+            height: height.0 as f64,
+        })
+    }
+
+    pub(super) fn text(&self, text: &str, (x, y): (f64, f64), size: f64, bold: bool, color: &str) {
+        let weight = if bold { "bold " } else { "" };
+        self.ctx.set_font(&format!("{weight}{size}px {FONT}"));
+        self.ctx.set_fill_style_str(color);
+        let _ = self.ctx.fill_text(text, x, y);
+    }
+
+    pub(super) fn text_right(
+        &self,
+        text: &str,
+        (x, y): (f64, f64),
+        size: f64,
+        bold: bool,
+        color: &str,
+    ) {
+        self.ctx.set_text_align("right");
+        self.text(text, (x, y), size, bold, color);
+        self.ctx.set_text_align("left");
+    }
+
+    /// Shrinks the text to fit in `max_width`
+    pub(super) fn text_centered(
+        &self,
+        text: &str,
+        (x, y): (f64, f64),
+        size: f64,
+        bold: bool,
+        color: &str,
+        max_width: f64,
+    ) {
+        let weight = if bold { "bold " } else { "" };
+        self.ctx.set_font(&format!("{weight}{size}px {FONT}"));
+        self.ctx.set_fill_style_str(color);
+        self.ctx.set_text_align("center");
+        let _ = self.ctx.fill_text_with_max_width(text, x, y, max_width);
+        self.ctx.set_text_align("left");
+    }
+
+    pub(super) fn text_width(&self, text: &str, size: f64, bold: bool) -> f64 {
+        let weight = if bold { "bold " } else { "" };
+        self.ctx.set_font(&format!("{weight}{size}px {FONT}"));
+        self.ctx
+            .measure_text(text)
+            .map(|metrics| metrics.width())
+            .unwrap_or_default()
+    }
+
+    pub(super) fn rect(
+        &self,
+        (x, y, w, h): (f64, f64, f64, f64),
+        fill: Option<&str>,
+        stroke: Option<&str>,
+    ) {
+        if let Some(fill) = fill {
+            self.ctx.set_fill_style_str(fill);
+            self.ctx.fill_rect(x, y, w, h);
+        }
+        if let Some(stroke) = stroke {
+            self.ctx.set_stroke_style_str(stroke);
+            self.ctx.set_line_width(0.3);
+            self.ctx.stroke_rect(x, y, w, h);
+        }
+    }
+
+    fn line(&self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), color: &str) {
+        self.ctx.set_stroke_style_str(color);
+        self.ctx.set_line_width(0.2);
+        self.ctx.begin_path();
+        self.ctx.move_to(x1, y1);
+        self.ctx.line_to(x2, y2);
+        self.ctx.stroke();
+    }
+
+    pub(super) fn to_image(&self) -> Result<DynamicImage, Box<dyn Error>> {
+        let (w, h) = (self.canvas.width(), self.canvas.height());
+        let data = self
+            .ctx
+            .get_image_data(0.0, 0.0, w as f64, h as f64)
+            .map_err(|e| format!("{e:?}"))?
+            .data();
+        let image = RgbaImage::from_raw(w, h, data.0).ok_or("Invalid canvas size")?;
+        Ok(DynamicImage::ImageRgba8(image))
+    }
+}
+
+/// Lays out the sections over as many pages as needed
+struct Sheet<'a> {
+    settings: &'a DecklistSettings,
+    deck_name: String,
+    pages: Vec<Page>,
+    y: f64,
+    page_width: Mm,
+    page_height: Mm,
+}
+
+impl Sheet<'_> {
+    fn page(&self) -> &Page {
+        self.pages
+            .last()
+            .expect("the first page is added on creation")
+    }
+
+    fn new_page(&mut self) -> Result<(), Box<dyn Error>> {
+        let page = Page::new(self.page_width, self.page_height)?;
+        let page_no = self.pages.len() + 1;
+        page.text(
+            &format!(
+                "{} - {} - page {page_no}",
+                self.deck_name, self.settings.player.name
+            ),
+            (MARGIN, page.height - MARGIN / 2.0),
+            2.8,
+            false,
+            "#777",
+        );
+        self.pages.push(page);
+        self.y = MARGIN;
+        Ok(())
+    }
+
+    fn ensure_room(&mut self, height: f64) -> Result<bool, Box<dyn Error>> {
+        if self.y + height > self.page().height - MARGIN {
+            self.new_page()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn header(&mut self, errors: &[String]) {
+        let page = self.page();
+        let right = page.width - MARGIN;
+        let mut y = self.y;
+        page.text("Decklist", (MARGIN, y + 4.0), 8.0, true, "black");
+        page.text_right(
+            "hololive OFFICIAL CARD GAME",
+            (right, y + 4.0),
+            3.5,
+            false,
+            "#555",
+        );
+        y += 11.0;
+
+        // player info
+        let width = (right - MARGIN) / 2.0;
+        let fields = [
+            ("Player name", &self.settings.player.name),
+            ("Player ID", &self.settings.player.player_id),
+            ("Event", &self.settings.event),
+            ("Date", &self.settings.date),
+        ];
+        for (idx, (label, value)) in fields.into_iter().enumerate() {
+            let x = MARGIN + width * (idx % 2) as f64;
+            let field_y = y + 11.0 * (idx / 2) as f64;
+            page.rect((x, field_y, width, 11.0), None, Some("black"));
+            page.text(label, (x + 1.5, field_y + 2.2), 2.6, false, "#555");
+            page.text(value, (x + 1.5, field_y + 7.0), 4.2, false, "black");
+        }
+        y += 22.0 + 3.0;
+
+        page.text(
+            &format!("Deck: {}", self.deck_name),
+            (MARGIN, y + 2.5),
+            4.2,
+            true,
+            "black",
+        );
+        y += 7.0;
+
+        // deck check warnings
+        if !errors.is_empty() {
+            let height = 4.0 + 5.0 * errors.len() as f64;
+            page.rect(
+                (MARGIN, y, right - MARGIN, height),
+                Some("#fff3cd"),
+                Some("#e0a800"),
+            );
+            for (idx, error) in errors.iter().enumerate() {
+                page.text(
+                    &format!("Warning: {error}"),
+                    (MARGIN + 2.0, y + 4.5 + 5.0 * idx as f64),
+                    3.5,
+                    false,
+                    "#7a5a00",
+                );
+            }
+            y += height + 4.0;
+        }
+
+        self.y = y;
+    }
+
+    fn section_title(&mut self, title: &str, total: u32) {
+        let page = self.page();
+        let right = page.width - MARGIN;
+        page.rect(
+            (MARGIN, self.y, right - MARGIN, ROW_HEIGHT),
+            Some("#e8e8e8"),
+            Some("black"),
+        );
+        let y = self.y + ROW_HEIGHT / 2.0;
+        page.text(title, (MARGIN + 2.0, y), 4.0, true, "black");
+        page.text_right(
+            &format!("{total} cards"),
+            (right - 2.0, y),
+            4.0,
+            true,
+            "black",
+        );
+        self.y += ROW_HEIGHT;
+    }
+
+    fn section(
+        &mut self,
+        title: &str,
+        rows: &[(u32, String, String)],
+    ) -> Result<(), Box<dyn Error>> {
+        let total = rows.iter().map(|r| r.0).sum();
+        self.ensure_room(ROW_HEIGHT * 2.0)?;
+        self.section_title(title, total);
+
+        for (amount, card_number, name) in rows {
+            if self.ensure_room(ROW_HEIGHT)? {
+                self.section_title(&format!("{title} (continued)"), total);
+            }
+            let page = self.page();
+            let right = page.width - MARGIN;
+            let y = self.y + ROW_HEIGHT / 2.0;
+            page.text_right(&amount.to_string(), (MARGIN + 10.0, y), 3.8, true, "black");
+            page.text(card_number, (MARGIN + 15.0, y), 3.8, false, "black");
+            page.text(name, (MARGIN + 50.0, y), 3.8, false, "black");
+            page.line(
+                (MARGIN, self.y + ROW_HEIGHT),
+                (right, self.y + ROW_HEIGHT),
+                "#bbb",
+            );
+            self.y += ROW_HEIGHT;
+        }
+
+        self.y += 4.0;
+        Ok(())
+    }
+}
+
+async fn generate_pdf(
+    deck: &DeckOrPile,
+    db: &CardsDatabase,
+    settings: &DecklistSettings,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (page_width, page_height) = settings
+        .paper_size
+        .dimensions()
+        .ok_or("Unsupported paper size")?;
+    let errors = deck.validate(db, false, settings.card_lang);
+
+    let mut sheet = Sheet {
+        settings,
+        deck_name: deck.required_deck_name(db),
+        pages: vec![],
+        y: 0.0,
+        page_width,
+        page_height,
+    };
+    sheet.new_page()?;
+    sheet.header(&errors);
+
+    let card_lang = settings.card_lang;
+    match deck {
+        DeckOrPile::Deck(deck) => {
+            sheet.section("Oshi", &section_rows(deck.oshi.as_slice(), db, card_lang))?;
+            sheet.section("Main deck", &section_rows(&deck.main_deck, db, card_lang))?;
+            sheet.section("Cheer deck", &section_rows(&deck.cheer_deck, db, card_lang))?;
+        }
+        DeckOrPile::Pile(pile) => {
+            sheet.section("Cards", &section_rows(&pile.cards, db, card_lang))?;
+        }
+    }
+
+    // signature, with the grand total
+    sheet.ensure_room(15.0)?;
+    let total: u32 = deck.all_cards().map(|c| c.amount).sum();
+    let page = sheet.page();
+    let right = page.width - MARGIN;
+    let y = sheet.y + 8.0;
+    page.text(
+        &format!("Total: {total} cards"),
+        (MARGIN, y),
+        4.0,
+        true,
+        "black",
+    );
+    page.text("Signature", (right - 70.0, y), 3.5, false, "#555");
+    page.line((right - 52.0, y + 2.0), (right, y + 2.0), "black");
+
+    // Create PDF document
+    let title = format!("Decklist for {}", sheet.deck_name);
+    let mut doc = PdfDocument::new(&title);
+    doc.metadata.info.producer = "hololive OCG Deck Converter".to_string();
+
+    let mut pages = Vec::with_capacity(sheet.pages.len());
+    for page in &sheet.pages {
+        let image_id = doc.add_image(&proxy_sheets::to_raw_image(&page.to_image()?)?);
+        pages.push(PdfPage::new(
+            page_width,
+            page_height,
+            vec![Op::UseXobject {
+                id: image_id,
+                transform: XObjectTransform {
+                    dpi: Some(DPI),
+                    ..Default::default()
+                },
+            }],
+        ));
+    }
+
+    Ok(doc.with_pages(pages).save(
+        &PdfSaveOptions {
+            image_optimization: Some(ImageOptimizationOptions {
+                // keep the text sharp
+                max_image_size: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        &mut Vec::new(),
+    ))
+}
+
+#[component]
+pub fn Export(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
+    #[derive(Serialize)]
+    struct EventData {
+        format: &'static str,
+        language: CardLanguage,
+        paper_size: proxy_sheets::PaperSize,
+        warning_count: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+    impl TrackEvent for EventData {}
+
+    let card_lang = PREVIEW_CARD_LANG.signal();
+    let mut paper_size = use_signal(proxy_sheets::PaperSize::local_default);
+    let mut player = use_signal(PlayerInfo::load);
+    let mut event = use_signal(String::new);
+    let mut date = use_signal(String::new);
+    let mut error = use_signal(String::new);
+    let mut loading = use_signal(|| false);
+
+    // keep the player info for the next event
+    use_effect(move || {
+        player.read().store();
+    });
+
+    let download_decklist = move |_| async move {
+        *loading.write() = true;
+        *error.write() = String::new();
+
+        let deck = common_deck.read().clone();
+        let settings = DecklistSettings {
+            card_lang: *card_lang.read(),
+            paper_size: *paper_size.read(),
+            player: player.read().clone(),
+            event: event.read().clone(),
+            date: date.read().clone(),
+        };
+        let file_name = format!("{}.decklist.pdf", deck.file_name(&db.read()));
+
+        let result = generate_pdf(&deck, &db.read(), &settings).await;
+        if let Ok(file) = &result {
+            download_file(&file_name, &file[..]);
+        }
+        if let Err(e) = &result {
+            *error.write() = e.to_string();
+        }
+
+        track_event(
+            EventType::Export("Decklist".into()),
+            EventData {
+                format: "Decklist",
+                language: settings.card_lang,
+                paper_size: settings.paper_size,
+                warning_count: deck.validate(&db.read(), false, settings.card_lang).len(),
+                error: result.err().map(|e| e.to_string()),
+            },
+        );
+
+        *loading.write() = false;
+    };
+
+    rsx! {
+        DeckValidation {
+            deck_check: true,
+            proxy_check: false,
+            allow_unreleased: false,
+            allow_pile: false,
+            card_lang,
+            db,
+            common_deck,
+        }
+
+        div { class: "block",
+            div { class: "grid is-col-min-8",
+                // Card language
+                div { class: "cell",
+                    label { "for": "decklist_card_language", class: "label", "Card names" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "decklist_card_language",
+                                oninput: move |ev| {
+                                    *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
+                                        "jp" => CardLanguage::Japanese,
+                                        "en" => CardLanguage::English,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::Japanese,
+                                    value: "jp",
+                                    "Japanese"
+                                }
+                                option {
+                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::English,
+                                    value: "en",
+                                    "English"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Paper size
+                div { class: "cell",
+                    label { "for": "decklist_paper_size", class: "label", "Paper size" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "decklist_paper_size",
+                                oninput: move |ev| {
+                                    *paper_size.write() = match ev.value().as_str() {
+                                        "a4" => proxy_sheets::PaperSize::A4,
+                                        "letter" => proxy_sheets::PaperSize::Letter,
+                                        "legal" => proxy_sheets::PaperSize::Legal,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: *paper_size.read() == proxy_sheets::PaperSize::A4,
+                                    value: "a4",
+                                    "A4 (210x297 mm)"
+                                }
+                                option {
+                                    selected: *paper_size.read() == proxy_sheets::PaperSize::Letter,
+                                    value: "letter",
+                                    "Letter (8.5x11 in)"
+                                }
+                                option {
+                                    selected: *paper_size.read() == proxy_sheets::PaperSize::Legal,
+                                    value: "legal",
+                                    "Legal (8.5x14 in)"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Player info
+                div { class: "cell",
+                    label { "for": "decklist_player_name", class: "label", "Player name" }
+                    div { class: "control",
+                        input {
+                            id: "decklist_player_name",
+                            class: "input",
+                            r#type: "text",
+                            value: "{player.read().name}",
+                            oninput: move |ev| player.write().name = ev.value(),
+                        }
+                    }
+                }
+                div { class: "cell",
+                    label { "for": "decklist_player_id", class: "label", "Player ID" }
+                    div { class: "control",
+                        input {
+                            id: "decklist_player_id",
+                            class: "input",
+                            r#type: "text",
+                            value: "{player.read().player_id}",
+                            oninput: move |ev| player.write().player_id = ev.value(),
+                        }
+                    }
+                }
+                div { class: "cell",
+                    label { "for": "decklist_event", class: "label", "Event" }
+                    div { class: "control",
+                        input {
+                            id: "decklist_event",
+                            class: "input",
+                            r#type: "text",
+                            value: "{event}",
+                            oninput: move |ev| *event.write() = ev.value(),
+                        }
+                    }
+                }
+                div { class: "cell",
+                    label { "for": "decklist_date", class: "label", "Date" }
+                    div { class: "control",
+                        input {
+                            id: "decklist_date",
+                            class: "input",
+                            r#type: "date",
+                            value: "{date}",
+                            oninput: move |ev| *date.write() = ev.value(),
+                        }
+                    }
+                }
+            }
+            p { class: "help", "Leave the fields empty to fill them by hand." }
+        }
+
+        div { class: "field",
+            div { class: "control",
+                button {
+                    r#type: "button",
+                    class: "button",
+                    class: if *loading.read() { "is-loading" },
+                    disabled: common_deck.read().is_empty() || *loading.read(),
+                    onclick: download_decklist,
+                    span { class: "icon",
+                        i { class: "fa-solid fa-clipboard-list" }
+                    }
+                    span { "Download decklist (PDF)" }
+                }
+            }
+            p { class: "help is-danger", "{error}" }
+        }
+    }
+}
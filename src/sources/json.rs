@@ -20,7 +20,7 @@ use crate::{
 use super::{CardsDatabase, CommonDeck, DeckType, holodelta, holoduel, tabletop_sim};
 
 #[derive(Debug, Clone)]
-enum Deck {
+pub enum Deck {
     HoloDelta(holodelta::Deck),
     HoloDuel(holoduel::Deck),
     TabletopSim(tabletop_sim::Deck),
@@ -61,7 +61,11 @@ impl Deck {
         })
     }
 
-    fn from_common_deck(deck_type: DeckType, deck: CommonDeck, db: &CardsDatabase) -> Option<Self> {
+    pub fn from_common_deck(
+        deck_type: DeckType,
+        deck: CommonDeck,
+        db: &CardsDatabase,
+    ) -> Option<Self> {
         Some(match deck_type {
             DeckType::HoloDelta => Deck::HoloDelta(holodelta::Deck::from_common_deck(deck, db)?),
             DeckType::HoloDuel => Deck::HoloDuel(holoduel::Deck::from_common_deck(deck, db)?),
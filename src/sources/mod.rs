@@ -6,27 +6,29 @@ use std::{
 use hocg_fan_sim_assets_model::{
     self as hocg, CardIllustration, CardOrderingOptions, CardsDatabase,
 };
-use hocg_fan_sim_prices_model::Price;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use jiff::Timestamp;
 use price_check::PriceCache;
+use price_provider::CardPrice;
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    CardLanguage, CardType,
-    sources::price_check::{PriceCacheKey, PriceCheckService},
-};
+use crate::{CardLanguage, CardType, sources::price_check::PriceCheckService};
 
+pub mod batch;
+pub mod bundle;
 pub mod deck_log;
+pub mod decklist;
 pub mod edit_deck;
 pub mod holodelta;
 pub mod holoduel;
 pub mod json;
 pub mod price_check;
+pub mod price_provider;
 pub mod proxy_sheets;
 pub mod starter_decks;
 pub mod tabletop_sim;
+mod text_proxy;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DeckType {
@@ -37,6 +39,9 @@ pub enum DeckType {
     TabletopSim,
     ProxySheets,
     PriceCheck,
+    Decklist,
+    Bundle,
+    Batch,
     Unknown,
 }
 
@@ -45,6 +50,10 @@ pub struct CommonCard {
     pub card_number: String,
     pub illustration_idx: Option<usize>,
     pub amount: u32,
+    /// The saved card it was loaded from, when it's not in the cards database.
+    /// It's saved back unchanged, so the card is not lost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_card: Option<String>,
 }
 
 impl CommonCard {
@@ -57,6 +66,7 @@ impl CommonCard {
             illustration_idx: Some(illustration_idx),
             card_number,
             amount,
+            saved_card: None,
         }
     }
 
@@ -76,6 +86,7 @@ impl CommonCard {
                 card_number: card.card_number.clone(),
                 illustration_idx: None,
                 amount,
+                saved_card: None,
             }
         }
     }
@@ -98,6 +109,7 @@ impl CommonCard {
                 card_number: "UNKNOWN".into(),
                 illustration_idx: None,
                 amount,
+                saved_card: None,
             }
         }
     }
@@ -116,6 +128,7 @@ impl CommonCard {
                 card_number,
                 illustration_idx: None,
                 amount,
+                saved_card: None,
             }
         }
     }
@@ -269,12 +282,9 @@ impl CommonCard {
         prices: &PriceCache,
         service: PriceCheckService,
         free_basic_cheers: bool,
-    ) -> Option<Price> {
+    ) -> Option<CardPrice> {
         if free_basic_cheers && self.is_basic_cheer() {
-            Some(match service {
-                PriceCheckService::Yuyutei => Price::from_yen(0),
-                PriceCheckService::TcgPlayer => Price::from_dollar(0.0),
-            })
+            Some(service.provider().currency().price(0.0))
         } else {
             self.price_cache(db, prices, service).map(|p| p.1)
         }
@@ -290,25 +300,17 @@ impl CommonCard {
             .map(|p| p.to_string())
     }
     pub fn price_url(&self, db: &CardsDatabase, service: PriceCheckService) -> Option<String> {
-        self.card_illustration(db).and_then(|c| match service {
-            PriceCheckService::Yuyutei => c.yuyutei_sell_url.clone(),
-            PriceCheckService::TcgPlayer => c.tcgplayer_url(),
-        })
+        self.card_illustration(db)
+            .and_then(|c| service.provider().card_url(c))
     }
     pub fn price_cache<'a>(
         &self,
         db: &CardsDatabase,
         prices: &'a PriceCache,
         service: PriceCheckService,
-    ) -> Option<&'a (Timestamp, Price)> {
-        self.card_illustration(db).and_then(|c| {
-            prices.get(&match service {
-                PriceCheckService::Yuyutei => {
-                    PriceCacheKey::Yuyutei(c.yuyutei_sell_url.as_ref()?.to_string())
-                }
-                PriceCheckService::TcgPlayer => PriceCacheKey::TcgPlayer(c.tcgplayer_product_id?),
-            })
-        })
+    ) -> Option<&'a (Timestamp, CardPrice)> {
+        self.card_illustration(db)
+            .and_then(|c| prices.get(&service.provider().cache_key(c)?))
     }
 
     pub fn alt_cards(&self, db: &CardsDatabase) -> Vec<Self> {
@@ -780,14 +782,15 @@ pub trait DeckLike: Clone + Hash {
         prices: &PriceCache,
         service: PriceCheckService,
         free_basic_cheers: bool,
-    ) -> Price {
-        self.all_cards()
+    ) -> CardPrice {
+        let value = self
+            .all_cards()
             .filter_map(|c| {
                 c.price(db, prices, service, free_basic_cheers)
-                    .map(|p| (c, p))
+                    .map(|p| p.value * c.amount as f64)
             })
-            .map(|(c, p)| p * c.amount)
-            .sum()
+            .sum();
+        service.provider().currency().price(value)
     }
     fn is_price_approximate(
         &self,
@@ -812,10 +815,6 @@ pub trait DeckLike: Clone + Hash {
             ""
         };
         let price = self.price(db, prices, service, free_basic_cheers);
-        let price = match service {
-            PriceCheckService::Yuyutei => price.to_string(),
-            PriceCheckService::TcgPlayer => format!("{} USD", price),
-        };
         format!("{approx_price}{price}")
     }
 }
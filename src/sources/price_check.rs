@@ -4,18 +4,24 @@ use dioxus::{
     logger::tracing::{debug, error},
     prelude::*,
 };
-use hocg_fan_sim_prices_model::{Price, PricesDatabase, ServiceId};
+use gloo::utils::window;
+use hocg_fan_sim_prices_model::PricesDatabase;
 use itertools::Itertools;
 use jiff::{SignedDuration, Timestamp};
 use reqwest::{Client, ClientBuilder};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::CardsDatabase;
+use super::{CardsDatabase, CommonCard, PileOfCards, bundle};
 use crate::{
-    CardLanguage, EventType, FREE_BASIC_CHEERS, PREVIEW_CARD_LANG,
+    EventType, FREE_BASIC_CHEERS, PREVIEW_CARD_LANG, price_cache,
+    price_history::{self, PricePoint},
     sources::{
         DeckLike, DeckOrPile,
         price_check::PriceCheckService::{TcgPlayer, Yuyutei},
+        price_provider::{
+            CardPrice, Currency, PRICE_LIST, PriceList, PriceListProvider, PriceProvider,
+            shared_prices, store_price_list,
+        },
     },
     track_event,
     tracker::{TrackEvent, track_external_url},
@@ -23,64 +29,181 @@ use crate::{
 
 const HOCG_FAN_SIM_PRICES_URL: &str =
     "https://qrimpuff.github.io/hocg-fan-sim-prices/hocg_prices.json";
+/// The prices are fetched again past this age
+const PRICES_MAX_AGE: SignedDuration = SignedDuration::from_hours(1);
+const PRICE_LIST_EXAMPLE: &str = r#"{"name": "My store", "currency": "EUR", "card_url": "https://.../{card_number}", "prices": {"hSD01-001": 1.5}}"#;
+const EXCHANGE_RATE_KEY: &str = "hocg-deck-convert.exchange_rate";
+const DEFAULT_JPY_PER_USD: f64 = 150.0;
 
-pub type PriceCache = HashMap<PriceCacheKey, (Timestamp, Price)>;
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub type PriceCache = HashMap<PriceCacheKey, (Timestamp, CardPrice)>;
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum PriceCacheKey {
     Yuyutei(String),
     TcgPlayer(u32),
+    /// By card number, Suruga-ya has no prices
+    Surugaya(String),
+    /// By card number, from the imported price list
+    PriceList(String),
 }
 
 #[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
 pub enum PriceCheckService {
     Yuyutei,
     TcgPlayer,
+    Surugaya,
+    PriceList,
 }
 
-fn price_lookup_key(key: &PriceCacheKey) -> ServiceId {
+/// Key of the card series in the price history
+fn history_key(key: &PriceCacheKey) -> String {
     match key {
-        PriceCacheKey::Yuyutei(url) => ServiceId::from_yuyutei(url.clone()),
-        PriceCacheKey::TcgPlayer(product_id) => ServiceId::from_tcgplayer(*product_id),
+        PriceCacheKey::Yuyutei(url) => format!("yuyutei:{url}"),
+        PriceCacheKey::TcgPlayer(product_id) => format!("tcgplayer:{product_id}"),
+        PriceCacheKey::Surugaya(card_number) => format!("surugaya:{card_number}"),
+        PriceCacheKey::PriceList(card_number) => format!("price_list:{card_number}"),
     }
 }
 
+fn format_value(value: f64, service: PriceCheckService) -> String {
+    service.provider().currency().format(value)
+}
+
+/// Saved by the user, or the bundled rate
+fn load_exchange_rate() -> f64 {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|ls| ls.get_item(EXCHANGE_RATE_KEY).ok().flatten())
+        .and_then(|rate| rate.parse().ok())
+        .filter(|rate: &f64| *rate > 0.0)
+        .unwrap_or(DEFAULT_JPY_PER_USD)
+}
+
+fn store_exchange_rate(rate: f64) -> Option<()> {
+    let ls = window().local_storage().ok()??;
+    if rate == DEFAULT_JPY_PER_USD {
+        ls.remove_item(EXCHANGE_RATE_KEY).ok()
+    } else {
+        ls.set_item(EXCHANGE_RATE_KEY, &rate.to_string()).ok()
+    }
+}
+
+/// One deck entry, with the price at each vendor in the comparison currency
+#[derive(Debug, Clone, PartialEq)]
+struct ComparedCard {
+    card: CommonCard,
+    yuyutei: Option<f64>,
+    tcgplayer: Option<f64>,
+}
+
+impl ComparedCard {
+    /// The vendor for the split cart, the only one that has it or the cheaper one
+    fn cheaper(&self) -> Option<PriceCheckService> {
+        match (self.yuyutei, self.tcgplayer) {
+            (Some(y), Some(t)) if t < y => Some(TcgPlayer),
+            (Some(_), _) => Some(Yuyutei),
+            (None, Some(_)) => Some(TcgPlayer),
+            (None, None) => None,
+        }
+    }
+
+    fn price(&self, service: PriceCheckService) -> Option<f64> {
+        match service {
+            Yuyutei => self.yuyutei,
+            TcgPlayer => self.tcgplayer,
+            PriceCheckService::Surugaya | PriceCheckService::PriceList => None,
+        }
+    }
+}
+
+fn vendor_name(service: Option<PriceCheckService>) -> String {
+    service
+        .map(|service| service.provider().name())
+        .unwrap_or("Unavailable".into())
+}
+
+fn compare_prices(
+    deck: &DeckOrPile,
+    db: &CardsDatabase,
+    prices: &PriceCache,
+    free_basic_cheers: bool,
+    currency: Currency,
+    jpy_per_usd: f64,
+) -> Vec<ComparedCard> {
+    let price = |card: &CommonCard, service| {
+        let price = card.price(db, prices, service, free_basic_cheers)?;
+        // not comparable without a rate, e.g. in euro
+        Some(currency.convert(price.value, price.currency, jpy_per_usd)? * card.amount as f64)
+    };
+    deck.all_cards()
+        .map(|card| ComparedCard {
+            card: card.clone(),
+            yuyutei: price(card, Yuyutei),
+            tcgplayer: price(card, TcgPlayer),
+        })
+        .collect()
+}
+
+fn is_stale(cache_time: Timestamp) -> bool {
+    Timestamp::now().duration_since(cache_time) > PRICES_MAX_AGE
+}
+
+/// e.g. "3 hours ago"
+fn format_age(cache_time: Timestamp) -> String {
+    let age = Timestamp::now().duration_since(cache_time);
+    let (amount, unit) = if age < SignedDuration::from_mins(1) {
+        return "just now".into();
+    } else if age < SignedDuration::from_hours(1) {
+        (age.as_mins(), "minute")
+    } else if age < SignedDuration::from_hours(24) {
+        (age.as_hours(), "hour")
+    } else {
+        (age.as_hours() / 24, "day")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural} ago")
+}
+
 fn http_client() -> &'static Client {
     static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
     HTTP_CLIENT.get_or_init(|| ClientBuilder::new().build().unwrap())
 }
 
+/// Nothing is borrowed during the fetch, the database is only read before and after it
 async fn price_check(
-    deck: &DeckOrPile,
-    db: &CardsDatabase,
-    prices: &PriceCache,
+    deck: DeckOrPile,
+    db: Signal<CardsDatabase>,
+    prices: PriceCache,
     service: PriceCheckService,
+    refresh: bool,
 ) -> Result<PriceCache, Box<dyn Error>> {
     debug!("price check");
+    let provider = service.provider();
+
+    // the price list doesn't change over time, it has no history
+    let local_prices = provider.local_prices(&db.read());
+    if let Some(prices) = local_prices {
+        return Ok(prices);
+    }
 
     // read price from cache
-    let need_prices = deck
-        .all_cards()
-        // check price for all versions
-        .flat_map(|c| c.alt_cards(db).into_iter())
-        .filter(|c| {
-            if let Some(i) = c.card_illustration(db) {
-                match service {
-                    Yuyutei => i.yuyutei_sell_url.is_some(),
-                    TcgPlayer => i.tcgplayer_product_id.is_some(),
-                }
-            } else {
-                false
-            }
-        })
-        .any(|c| {
-            c.price_cache(db, prices, service)
-                .map(|(cache_time, _)| {
-                    // more than an hour
-                    Timestamp::now().duration_since(*cache_time) > SignedDuration::from_hours(1)
-                })
-                .unwrap_or(true)
-        });
-    if !need_prices {
+    let need_prices = {
+        let db = db.read();
+        deck.all_cards()
+            // check price for all versions
+            .flat_map(|c| c.alt_cards(&db).into_iter())
+            .filter(|c| {
+                c.card_illustration(&db)
+                    .is_some_and(|i| provider.cache_key(i).is_some())
+            })
+            .any(|c| {
+                c.price_cache(&db, &prices, service)
+                    .map(|(cache_time, _)| is_stale(*cache_time))
+                    .unwrap_or(true)
+            })
+    };
+    if !refresh && !need_prices {
         return Ok(PriceCache::new());
     }
 
@@ -97,50 +220,26 @@ async fn price_check(
     let content = resp.text().await.unwrap();
     debug!("loaded shared prices db ({} bytes)", content.len());
 
-    let shared_prices: PricesDatabase = serde_json::from_str(&content).map_err(|_| content)?;
+    let shared: PricesDatabase = serde_json::from_str(&content).map_err(|_| content)?;
 
     // it contains all the prices
-    let prices: PriceCache = db
-        .values()
-        .flat_map(|c| &c.illustrations)
-        .cartesian_product([Yuyutei, TcgPlayer])
-        .filter_map(|(c, service)| {
-            Some(match service {
-                Yuyutei => PriceCacheKey::Yuyutei(c.yuyutei_sell_url.as_ref()?.to_string()),
-                TcgPlayer => PriceCacheKey::TcgPlayer(c.tcgplayer_product_id?),
-            })
-        })
-        .filter_map(|key| {
-            let (_timestamp, price) = shared_prices.get(&price_lookup_key(&key))?;
-            Some((key, *price))
-        })
-        .map(|(key, price)| (key, (Timestamp::now(), price)))
-        .collect();
+    let prices = shared_prices(&shared, &db.read());
     debug!("{:?}", prices);
 
-    Ok(prices)
-}
+    // a snapshot of the deck cards for the price history, the prices are still good without it
+    let snapshot = {
+        let db = db.read();
+        deck.all_cards()
+            .filter_map(|c| provider.cache_key(c.card_illustration(&db)?))
+            .unique()
+            .filter_map(|key| Some((history_key(&key), prices.get(&key)?.1.value)))
+            .collect_vec()
+    };
+    if let Err(err) = price_history::record(&snapshot, Timestamp::now().as_second()).await {
+        error!("{err}");
+    }
 
-fn tcgplayer_mass_entry_url(
-    deck: &DeckOrPile,
-    free_basic_cheers: bool,
-    db: &CardsDatabase,
-) -> String {
-    let product_ids = deck
-        .all_cards()
-        .filter(|c| !c.is_basic_cheer() || !free_basic_cheers)
-        .filter_map(|c| {
-            Some(format!(
-                "{}-{}",
-                c.amount,
-                c.card_illustration(db)?.tcgplayer_product_id?
-            ))
-        })
-        .collect::<Vec<_>>()
-        .join("||");
-    format!(
-        "https://www.tcgplayer.com/massentry?c={product_ids}&productline=hololive%20OFFICIAL%20CARD%20GAME"
-    )
+    Ok(prices)
 }
 
 #[component]
@@ -166,38 +265,65 @@ pub fn Export(
     let mut deck_error = use_signal(String::new);
     let mut loading = use_signal(|| false);
 
+    let mut price_list_error = use_signal(String::new);
+
     let has_prices = use_memo(move || {
-        prices.read().keys().any(|key| match *price_service.read() {
-            Yuyutei => matches!(key, PriceCacheKey::Yuyutei(_)),
-            TcgPlayer => matches!(key, PriceCacheKey::TcgPlayer(_)),
-        })
+        let service = *price_service.read();
+        common_deck
+            .read()
+            .all_cards()
+            .flat_map(|c| c.alt_cards(&db.read()))
+            .any(|c| c.price_cache(&db.read(), &prices.read(), service).is_some())
     });
 
-    let has_missing_tcgplayer_ids = use_memo(move || {
+    let has_missing_cards = use_memo(move || {
+        let provider = price_service.read().provider();
         common_deck.read().all_cards().any(|c| {
             c.card_illustration(&db.read())
-                .and_then(|c| c.tcgplayer_product_id)
+                .and_then(|i| provider.cache_key(i))
                 .is_none()
         })
     });
 
-    let price_check = move |_| async move {
-        let common_deck = common_deck.read();
+    let cart = use_memo(move || {
+        price_service.read().provider().cart_url(
+            &common_deck.read(),
+            *FREE_BASIC_CHEERS.read(),
+            &db.read(),
+        )
+    });
+
+    // the oldest price of the deck
+    let prices_time = use_memo(move || {
+        let service = *price_service.read();
+        common_deck
+            .read()
+            .all_cards()
+            .filter_map(|c| c.price_cache(&db.read(), &prices.read(), service))
+            .map(|(cache_time, _)| *cache_time)
+            .min()
+    });
 
+    let price_check = move |refresh: bool| async move {
         *loading.write() = true;
         *deck_error.write() = String::new();
 
-        let price_check = price_check(
-            &common_deck,
-            &db.read(),
-            &prices.read(),
-            *price_service.read(),
-        )
-        .await;
+        // copies, the deck and the prices can change during the fetch
+        let deck = common_deck.read().clone();
+        let cached = prices.read().clone();
+        let service = *price_service.read();
+        let price_check = price_check(deck, db, cached, service, refresh).await;
         match price_check {
             Ok(price_check) => {
+                let fetched = !price_check.is_empty();
                 prices.write().extend(price_check);
                 *show_price.write() = true;
+                if fetched {
+                    let saved = prices.read().clone();
+                    if let Err(err) = price_cache::store(&saved).await {
+                        error!("{err}");
+                    }
+                }
                 track_event(
                     EventType::Export("Price check".into()),
                     EventData {
@@ -209,7 +335,14 @@ pub fn Export(
                 );
             }
             Err(e) => {
-                *deck_error.write() = e.to_string();
+                // offline, the saved prices are still shown
+                if prices_time.read().is_some() {
+                    *show_price.write() = true;
+                    *deck_error.write() =
+                        format!("Could not refresh the prices ({e}), the saved prices are shown.");
+                } else {
+                    *deck_error.write() = e.to_string();
+                }
                 track_event(
                     EventType::Export("Price check".into()),
                     EventData {
@@ -329,14 +462,51 @@ pub fn Export(
         *loading.write() = false;
     };
 
-    let tcgplayer_mass_entry = move |_| {
-        let url =
-            tcgplayer_mass_entry_url(&common_deck.read(), *FREE_BASIC_CHEERS.read(), &db.read());
+    let open_cart = move |_| {
+        let Some((url, cart_name)) = cart() else {
+            return;
+        };
         web_sys::window().unwrap().open_with_url(&url).unwrap();
 
-        track_external_url("TCGplayer - Mass Entry");
+        track_external_url(&format!(
+            "{} - {cart_name}",
+            price_service.read().provider().name()
+        ));
     };
 
+    let import_price_list = move |event: Event<FormData>| async move {
+        *price_list_error.write() = String::new();
+        for file in &event.files() {
+            let Ok(contents) = file.read_bytes().await else {
+                continue;
+            };
+            match PriceList::from_file(&contents) {
+                Ok(list) => {
+                    store_price_list(Some(&list));
+                    *PREVIEW_CARD_LANG.write() = PriceListProvider.card_lang();
+                    *PRICE_LIST.write() = Some(list);
+                    // the old prices of the list are replaced on the next check
+                    prices
+                        .write()
+                        .retain(|key, _| !matches!(key, PriceCacheKey::PriceList(_)));
+                }
+                Err(err) => *price_list_error.write() = err,
+            }
+        }
+    };
+
+    let remove_price_list = move |_| {
+        store_price_list(None);
+        *PRICE_LIST.write() = None;
+        prices
+            .write()
+            .retain(|key, _| !matches!(key, PriceCacheKey::PriceList(_)));
+    };
+
+    // the price list is as recent as its file
+    let prices_age =
+        prices_time().filter(|_| *price_service.read() != PriceCheckService::PriceList);
+
     rsx! {
 
         div { class: "field",
@@ -347,19 +517,76 @@ pub fn Export(
                         id: "service",
                         oninput: move |ev| {
                             *show_price.write() = true;
-                            *price_service.write() = match ev.value().as_str() {
+                            let service = match ev.value().as_str() {
                                 "yuyutei" => Yuyutei,
                                 "tcgplayer" => TcgPlayer,
+                                "surugaya" => PriceCheckService::Surugaya,
+                                "price_list" => PriceCheckService::PriceList,
                                 _ => unreachable!(),
                             };
-                            *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
-                                "yuyutei" => CardLanguage::Japanese,
-                                "tcgplayer" => CardLanguage::English,
-                                _ => unreachable!(),
-                            };
+                            *price_service.write() = service;
+                            *PREVIEW_CARD_LANG.write() = service.provider().card_lang();
                         },
                         option { value: "yuyutei", "Yuyutei (JPY)" }
                         option { value: "tcgplayer", "TCGplayer (USD)" }
+                        option { value: "surugaya", "Suruga-ya (JPY)" }
+                        option { value: "price_list",
+                            if let Some(list) = &*PRICE_LIST.read() {
+                                "{list.name} ({list.currency.code()})"
+                            } else {
+                                "Price list file"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if *price_service.read() == PriceCheckService::PriceList {
+            div { class: "field",
+                label { "for": "price_list_file", class: "label", "Price list" }
+                div { class: "control",
+                    div { class: "file has-name",
+                        label { class: "file-label",
+                            input {
+                                id: "price_list_file",
+                                r#type: "file",
+                                class: "file-input",
+                                accept: ".json",
+                                onchange: import_price_list,
+                            }
+                            span { class: "file-cta",
+                                span { class: "file-icon",
+                                    i { class: "fa-solid fa-upload" }
+                                }
+                                span { class: "file-label", " Load a price list… " }
+                            }
+                            span { class: "file-name",
+                                if let Some(list) = &*PRICE_LIST.read() {
+                                    "{list.name}, {list.prices.len()} cards"
+                                } else {
+                                    "No price list"
+                                }
+                            }
+                        }
+                    }
+                }
+                p { class: "help",
+                    "A JSON file from a store, with the price of each card number: "
+                    code { "{PRICE_LIST_EXAMPLE}" }
+                }
+                p { class: "help is-danger", "{price_list_error}" }
+                if PRICE_LIST.read().is_some() {
+                    div { class: "control mt-2",
+                        button {
+                            r#type: "button",
+                            class: "button is-small",
+                            onclick: remove_price_list,
+                            span { class: "icon",
+                                i { class: "fa-solid fa-trash" }
+                            }
+                            span { "Remove price list" }
+                        }
                     }
                 }
             }
@@ -380,23 +607,69 @@ pub fn Export(
             }
         }
 
+        if price_service.read().provider().has_prices() {
+            div { class: "field is-grouped",
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        class: if *loading.read() { "is-loading" },
+                        disabled: common_deck.read().is_empty() || *loading.read(),
+                        onclick: move |_| price_check(false),
+                        span { class: "icon",
+                            i { class: "fa-solid fa-tag" }
+                        }
+                        span { "Check price" }
+                    }
+                }
+                if *price_service.read() != PriceCheckService::PriceList {
+                    div { class: "control",
+                        button {
+                            r#type: "button",
+                            class: "button",
+                            title: "Fetch the prices again, even if they are recent",
+                            disabled: common_deck.read().is_empty() || *loading.read(),
+                            onclick: move |_| price_check(true),
+                            span { class: "icon",
+                                i { class: "fa-solid fa-rotate" }
+                            }
+                            span { "Refresh prices" }
+                        }
+                    }
+                }
+            }
+        } else {
+            div { class: "field",
+                p { class: "help",
+                    "{price_service.read().provider().name()} has no price feed, each card links to its search."
+                }
+            }
+        }
         div { class: "field",
-            div { class: "control",
-                button {
-                    r#type: "button",
-                    class: "button",
-                    class: if *loading.read() { "is-loading" },
-                    disabled: common_deck.read().is_empty() || *loading.read(),
-                    onclick: price_check,
-                    span { class: "icon",
-                        i { class: "fa-solid fa-tag" }
+            if let Some(prices_time) = prices_age {
+                p { class: "help",
+                    "Prices from {format_age(prices_time)}"
+                    if is_stale(prices_time) {
+                        span {
+                            class: "tag is-warning ml-2",
+                            title: "These prices are saved from an earlier check, they may have changed",
+                            "Stale"
+                        }
                     }
-                    span { "Check price" }
                 }
             }
             p { class: "help is-danger", "{deck_error}" }
         }
 
+        PriceHistory {
+            common_deck,
+            db,
+            prices,
+            price_service,
+        }
+
+        PriceComparison { common_deck, db, prices }
+
         br {}
 
         div { class: "field",
@@ -429,13 +702,13 @@ pub fn Export(
             }
         }
 
-        if *price_service.read() == TcgPlayer {
+        if let Some((_, cart_name)) = cart() {
             br {}
 
-            if *has_missing_tcgplayer_ids.read() {
+            if *has_missing_cards.read() {
                 div { class: "field",
                     p { class: "notification is-warning",
-                        "Some cards are missing from TCGplayer, so they will not be included in the mass entry."
+                        "Some cards are missing from {price_service.read().provider().name()}, so they will not be included in the {cart_name}."
                     }
                 }
             }
@@ -445,11 +718,489 @@ pub fn Export(
                     button {
                         class: "button",
                         disabled: common_deck.read().is_empty() || *loading.read(),
-                        onclick: tcgplayer_mass_entry,
+                        onclick: open_cart,
                         span { class: "icon",
                             i { class: "fa-solid fa-external-link" }
                         }
-                        span { "TCGplayer Mass Entry" }
+                        span { "{price_service.read().provider().name()} {cart_name}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Key of the card printing in the price history
+fn card_history_key(
+    card: &CommonCard,
+    db: &CardsDatabase,
+    service: PriceCheckService,
+) -> Option<String> {
+    let key = service.provider().cache_key(card.card_illustration(db)?)?;
+    Some(history_key(&key))
+}
+
+/// The deck value and the card prices since the first price check on this device
+#[component]
+fn PriceHistory(
+    common_deck: Signal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    prices: Signal<PriceCache>,
+    price_service: Signal<PriceCheckService>,
+) -> Element {
+    let mut history = use_signal(HashMap::<String, Vec<PricePoint>>::new);
+    let mut show_cards = use_signal(|| false);
+
+    // the keys of the deck cards, with their amount
+    let deck_keys = use_memo(move || {
+        let free_basic_cheers = *FREE_BASIC_CHEERS.read();
+        common_deck
+            .read()
+            .all_cards()
+            .filter(|c| !free_basic_cheers || !c.is_basic_cheer())
+            .filter_map(|c| {
+                Some((
+                    c.clone(),
+                    card_history_key(c, &db.read(), *price_service.read())?,
+                ))
+            })
+            .collect_vec()
+    });
+
+    // reloaded after each price check
+    use_effect(move || {
+        let _ = prices.read();
+        let keys = deck_keys
+            .read()
+            .iter()
+            .map(|(_, key)| key.clone())
+            .collect_vec();
+        spawn(async move {
+            match price_history::load(&keys).await {
+                Ok(loaded) => history.set(loaded),
+                Err(err) => error!("{err}"),
+            }
+        });
+    });
+
+    let deck_series = use_memo(move || {
+        let history = history.read();
+        let deck_keys = deck_keys.read();
+        let cards = deck_keys
+            .iter()
+            .filter_map(|(card, key)| Some((card.amount, history.get(key)?.as_slice())))
+            .collect_vec();
+        price_history::total_series(&cards)
+    });
+
+    let card_rows = use_memo(move || {
+        let history = history.read();
+        deck_keys
+            .read()
+            .iter()
+            .unique_by(|(_, key)| key)
+            .filter_map(|(card, key)| Some((card.clone(), history.get(key)?.clone())))
+            .collect_vec()
+    });
+
+    let service = *price_service.read();
+    let deck_series = deck_series.read();
+    let Some(&(_, current)) = deck_series.last() else {
+        return rsx! {};
+    };
+    let lowest = deck_series
+        .iter()
+        .map(|(_, v)| *v)
+        .fold(f64::INFINITY, f64::min);
+    let highest = deck_series.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+
+    rsx! {
+        div { class: "field",
+            label { class: "label", "Price history" }
+            PriceChart { points: deck_series.to_vec(), height: 80 }
+            p { class: "help",
+                "Deck value: {format_value(current, service)} now, "
+                "lowest {format_value(lowest, service)}, highest {format_value(highest, service)}. "
+                "The prices are kept on this device at each price check."
+            }
+            p { class: "help",
+                a {
+                    href: "#",
+                    role: "button",
+                    onclick: move |evt| {
+                        evt.prevent_default();
+                        *show_cards.write() ^= true;
+                    },
+                    if *show_cards.read() {
+                        "Hide the card prices"
+                    } else {
+                        "Show the card prices"
+                    }
+                }
+            }
+            if *show_cards.read() {
+                table { class: "table is-narrow is-fullwidth mt-2",
+                    tbody {
+                        for (card, points) in card_rows.read().iter().cloned() {
+                            tr {
+                                td { style: "width: 40%;",
+                                    "{card.card_number} "
+                                    {bundle::card_name(&card, &db.read(), *PREVIEW_CARD_LANG.read())}
+                                }
+                                td {
+                                    PriceChart { points: points.clone(), height: 24 }
+                                }
+                                td { class: "has-text-right",
+                                    {points.last().map(|(_, v)| format_value(*v, service))}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A step line of the prices until now, scaled to its lowest and highest prices
+#[component]
+fn PriceChart(points: Vec<PricePoint>, height: u32) -> Element {
+    const WIDTH: f64 = 300.0;
+    let height = height as f64;
+
+    let now = Timestamp::now().as_second();
+    let start = points.first().map(|(t, _)| *t).unwrap_or(now);
+    let duration = (now - start).max(1) as f64;
+    let lowest = points.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    let highest = points.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+    let range = (highest - lowest).max(f64::EPSILON);
+
+    let x = |time: i64| (time - start) as f64 / duration * WIDTH;
+    // flat prices are drawn in the middle
+    let y = |value: f64| {
+        if highest > lowest {
+            2.0 + (highest - value) / range * (height - 4.0)
+        } else {
+            height / 2.0
+        }
+    };
+
+    let mut line = vec![];
+    for (idx, (time, value)) in points.iter().enumerate() {
+        if idx > 0 {
+            line.push((x(*time), y(points[idx - 1].1)));
+        }
+        line.push((x(*time), y(*value)));
+    }
+    if let Some((_, value)) = points.last() {
+        line.push((WIDTH, y(*value)));
+    }
+    let line = line
+        .into_iter()
+        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+        .join(" ");
+
+    rsx! {
+        svg {
+            view_box: "0 0 {WIDTH} {height}",
+            preserve_aspect_ratio: "none",
+            style: "width: 100%; height: {height}px; display: block;",
+            polyline {
+                points: "{line}",
+                fill: "none",
+                stroke: "currentColor",
+                stroke_width: "1.5",
+                vector_effect: "non-scaling-stroke",
+            }
+        }
+    }
+}
+
+/// Both vendors side by side in one currency, with the cheapest split between them
+#[component]
+fn PriceComparison(
+    common_deck: Signal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    prices: Signal<PriceCache>,
+) -> Element {
+    #[derive(Serialize)]
+    struct EventData {
+        format: &'static str,
+        price_check_convert: &'static str,
+        currency: Currency,
+        custom_exchange_rate: bool,
+    }
+    impl TrackEvent for EventData {}
+
+    let mut show_comparison = use_signal(|| false);
+    let mut currency = use_signal(|| Currency::Jpy);
+    let mut jpy_per_usd = use_signal(load_exchange_rate);
+
+    use_effect(move || {
+        store_exchange_rate(*jpy_per_usd.read());
+    });
+
+    // the shared prices have both vendors
+    let has_both_vendors = use_memo(move || {
+        let prices = prices.read();
+        prices
+            .keys()
+            .any(|key| matches!(key, PriceCacheKey::Yuyutei(_)))
+            && prices
+                .keys()
+                .any(|key| matches!(key, PriceCacheKey::TcgPlayer(_)))
+    });
+
+    let compared = use_memo(move || {
+        compare_prices(
+            &common_deck.read(),
+            &db.read(),
+            &prices.read(),
+            *FREE_BASIC_CHEERS.read(),
+            *currency.read(),
+            *jpy_per_usd.read(),
+        )
+    });
+
+    let split_cart_mass_entry = move |_| {
+        let cards = compared
+            .read()
+            .iter()
+            .filter(|c| c.cheaper() == Some(TcgPlayer))
+            .map(|c| c.card.clone())
+            .collect_vec();
+        let pile = DeckOrPile::Pile(PileOfCards { name: None, cards });
+        let Some((url, cart_name)) =
+            TcgPlayer
+                .provider()
+                .cart_url(&pile, *FREE_BASIC_CHEERS.read(), &db.read())
+        else {
+            return;
+        };
+        web_sys::window().unwrap().open_with_url(&url).unwrap();
+
+        track_external_url(&format!("TCGplayer - {cart_name}"));
+        track_event(
+            EventType::Export("Price check".into()),
+            EventData {
+                format: "Price check",
+                price_check_convert: "split cart",
+                currency: *currency.read(),
+                custom_exchange_rate: *jpy_per_usd.read() != DEFAULT_JPY_PER_USD,
+            },
+        );
+    };
+
+    if !*has_both_vendors.read() || common_deck.read().is_empty() {
+        return rsx! {};
+    }
+
+    let currency_value = *currency.read();
+    let compared = compared.read();
+    let total = |service| {
+        let missing = compared
+            .iter()
+            .filter(|c| c.price(service).is_none())
+            .count();
+        let total: f64 = compared.iter().filter_map(|c| c.price(service)).sum();
+        (total, missing)
+    };
+    let (yuyutei_total, yuyutei_missing) = total(Yuyutei);
+    let (tcgplayer_total, tcgplayer_missing) = total(TcgPlayer);
+    let split_total = |service| {
+        compared
+            .iter()
+            .filter(|c| c.cheaper() == Some(service))
+            .filter_map(|c| c.price(service))
+            .sum::<f64>()
+    };
+    let split_count = |service| {
+        compared
+            .iter()
+            .filter(|c| c.cheaper() == Some(service))
+            .map(|c| c.card.amount)
+            .sum::<u32>()
+    };
+    let (split_yuyutei, split_tcgplayer) = (split_total(Yuyutei), split_total(TcgPlayer));
+    let unavailable = compared
+        .iter()
+        .filter(|c| c.cheaper().is_none())
+        .map(|c| c.card.amount)
+        .sum::<u32>();
+    // only a vendor with every card can be a single cart
+    let best_single = [
+        (yuyutei_missing == 0).then_some(yuyutei_total),
+        (tcgplayer_missing == 0).then_some(tcgplayer_total),
+    ]
+    .into_iter()
+    .flatten()
+    .fold(None, |best: Option<f64>, total| {
+        Some(best.map_or(total, |best| best.min(total)))
+    });
+    let savings = best_single.map(|best| best - (split_yuyutei + split_tcgplayer));
+
+    let vendor_total = |total: f64, missing: usize| {
+        if missing > 0 {
+            format!(">{} ({missing} missing)", currency_value.format(total))
+        } else {
+            currency_value.format(total)
+        }
+    };
+
+    rsx! {
+        div { class: "field",
+            a {
+                href: "#",
+                role: "button",
+                onclick: move |evt| {
+                    evt.prevent_default();
+                    *show_comparison.write() ^= true;
+                },
+                span { class: "icon",
+                    i { class: "fa-solid fa-scale-balanced" }
+                }
+                if *show_comparison.read() {
+                    "Hide the vendor comparison"
+                } else {
+                    "Compare Yuyutei and TCGplayer"
+                }
+            }
+        }
+
+        if *show_comparison.read() {
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "comparison_currency",
+                            oninput: move |ev| {
+                                *currency.write() = match ev.value().as_str() {
+                                    "jpy" => Currency::Jpy,
+                                    "usd" => Currency::Usd,
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: currency_value == Currency::Jpy,
+                                value: "jpy",
+                                "In JPY"
+                            }
+                            option {
+                                selected: currency_value == Currency::Usd,
+                                value: "usd",
+                                "In USD"
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    div { class: "field has-addons",
+                        p { class: "control",
+                            a { class: "button is-static", "1 USD =" }
+                        }
+                        p { class: "control",
+                            input {
+                                id: "exchange_rate",
+                                r#type: "number",
+                                class: "input",
+                                style: "width: 7em;",
+                                min: "1",
+                                step: "0.01",
+                                value: "{jpy_per_usd}",
+                                oninput: move |ev| {
+                                    if let Ok(rate) = ev.value().parse::<f64>()
+                                        && rate > 0.0
+                                    {
+                                        *jpy_per_usd.write() = rate;
+                                    }
+                                },
+                            }
+                        }
+                        p { class: "control",
+                            a { class: "button is-static", "JPY" }
+                        }
+                    }
+                }
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        disabled: *jpy_per_usd.read() == DEFAULT_JPY_PER_USD,
+                        onclick: move |_| jpy_per_usd.set(DEFAULT_JPY_PER_USD),
+                        "Default rate"
+                    }
+                }
+            }
+
+            div { class: "table-container",
+                table { class: "table is-narrow is-striped",
+                    thead {
+                        tr {
+                            th { "Card" }
+                            th { class: "has-text-right", "Yuyutei" }
+                            th { class: "has-text-right", "TCGplayer" }
+                            th { "Cheaper" }
+                        }
+                    }
+                    tbody {
+                        for compared_card in compared.iter() {
+                            tr {
+                                td {
+                                    "{compared_card.card.amount}x {compared_card.card.card_number} "
+                                    {bundle::card_name(&compared_card.card, &db.read(), *PREVIEW_CARD_LANG.read())}
+                                }
+                                td { class: "has-text-right",
+                                    {compared_card.yuyutei.map(|v| currency_value.format(v)).unwrap_or("-".into())}
+                                }
+                                td { class: "has-text-right",
+                                    {compared_card.tcgplayer.map(|v| currency_value.format(v)).unwrap_or("-".into())}
+                                }
+                                td { {vendor_name(compared_card.cheaper())} }
+                            }
+                        }
+                    }
+                    tfoot {
+                        tr {
+                            th { "Whole deck" }
+                            th { class: "has-text-right", {vendor_total(yuyutei_total, yuyutei_missing)} }
+                            th { class: "has-text-right",
+                                {vendor_total(tcgplayer_total, tcgplayer_missing)}
+                            }
+                            th {}
+                        }
+                    }
+                }
+            }
+
+            div { class: "content help",
+                p {
+                    "Split cart: {split_count(Yuyutei)} cards at Yuyutei ({currency_value.format(split_yuyutei)}) "
+                    "and {split_count(TcgPlayer)} cards at TCGplayer ({currency_value.format(split_tcgplayer)}), "
+                    "for {currency_value.format(split_yuyutei + split_tcgplayer)}."
+                    if let Some(savings) = savings {
+                        " It saves {currency_value.format(savings.max(0.0))} over the cheapest single vendor."
+                    }
+                    if unavailable > 0 {
+                        " {unavailable} cards are not sold by either vendor."
+                    }
+                }
+                p {
+                    "Yuyutei sells the Japanese cards and TCGplayer the English cards. "
+                    "Shipping and fees are not included, and the exchange rate is set by you."
+                }
+            }
+
+            if split_count(TcgPlayer) > 0 {
+                div { class: "field",
+                    div { class: "control",
+                        button {
+                            class: "button",
+                            onclick: split_cart_mass_entry,
+                            span { class: "icon",
+                                i { class: "fa-solid fa-external-link" }
+                            }
+                            span { "TCGplayer Mass Entry (split cart)" }
+                        }
                     }
                 }
             }
@@ -0,0 +1,419 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt};
+
+use dioxus::prelude::*;
+use gloo::utils::window;
+use hocg_fan_sim_assets_model::{CardIllustration, CardsDatabase};
+use hocg_fan_sim_prices_model::{Price, PricesDatabase, ServiceId};
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DeckLike, DeckOrPile,
+    price_check::{PriceCache, PriceCacheKey, PriceCheckService},
+};
+use crate::CardLanguage;
+
+const PRICE_LIST_KEY: &str = "hocg-deck-convert.price_list";
+
+/// The price list imported by the user, e.g. from a local store
+pub static PRICE_LIST: GlobalSignal<Option<PriceList>> = Signal::global(load_price_list);
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Jpy,
+    Usd,
+    Eur,
+}
+
+impl Currency {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Jpy => "JPY",
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Currency::Jpy => format!("¥{value:.0}"),
+            Currency::Usd | Currency::Eur => format!("{value:.2} {}", self.code()),
+        }
+    }
+
+    /// Converts a price from the other currency, with the yen for one dollar.
+    /// `None` for the euro, there is no rate for it.
+    pub fn convert(&self, value: f64, from: Currency, jpy_per_usd: f64) -> Option<f64> {
+        match (from, self) {
+            (from, to) if from == *to => Some(value),
+            (Currency::Jpy, Currency::Usd) => Some(value / jpy_per_usd),
+            (Currency::Usd, Currency::Jpy) => Some(value * jpy_per_usd),
+            _ => None,
+        }
+    }
+
+    pub fn price(&self, value: f64) -> CardPrice {
+        CardPrice {
+            value,
+            currency: *self,
+        }
+    }
+
+    /// The amount of a shared price in this currency. The price has no getter, so its
+    /// serialized number is compared to the one of a single yen or dollar. `None` for
+    /// another currency, the shared prices have no euro.
+    pub fn amount(&self, price: Price) -> Option<f64> {
+        let unit = match self {
+            Currency::Jpy => Price::from_yen(1),
+            Currency::Usd => Price::from_dollar(1.0),
+            Currency::Eur => return None,
+        };
+        let (variant, units) = serialized_price(price)?;
+        let (unit_variant, unit) = serialized_price(unit)?;
+        (variant == unit_variant && unit > 0.0).then(|| units / unit)
+    }
+}
+
+/// The number of a serialized price, with its variant when it's an enum, e.g. `{"Yen": 120}`
+fn serialized_price(price: Price) -> Option<(Option<String>, f64)> {
+    match serde_json::to_value(price).ok()? {
+        serde_json::Value::Number(number) => Some((None, number.as_f64()?)),
+        serde_json::Value::Object(object) if object.len() == 1 => {
+            let (variant, number) = object.into_iter().next()?;
+            Some((Some(variant), number.as_f64()?))
+        }
+        _ => None,
+    }
+}
+
+/// A card price in the currency of its service
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct CardPrice {
+    pub value: f64,
+    pub currency: Currency,
+}
+
+impl fmt::Display for CardPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.currency.format(self.value))
+    }
+}
+
+// the prices of a service are compared, they have the same currency
+impl PartialEq for CardPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CardPrice {}
+
+impl PartialOrd for CardPrice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CardPrice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.total_cmp(&other.value)
+    }
+}
+
+/// Where the prices of the cards come from, and how to buy them
+pub trait PriceProvider {
+    fn name(&self) -> String;
+
+    fn currency(&self) -> Currency;
+
+    /// The language of the cards that are sold, for the preview
+    fn card_lang(&self) -> CardLanguage;
+
+    /// The price of this printing, `None` when it's not sold there
+    fn cache_key(&self, illustration: &CardIllustration) -> Option<PriceCacheKey>;
+
+    /// The page of this printing
+    fn card_url(&self, illustration: &CardIllustration) -> Option<String>;
+
+    /// The id in hocg-fan-sim-prices, for the shared prices
+    fn shared_id(&self, _key: &PriceCacheKey) -> Option<ServiceId> {
+        None
+    }
+
+    /// The prices can be checked, otherwise the cards only link to their page
+    fn has_prices(&self) -> bool {
+        true
+    }
+
+    /// Prices that are already available, without hocg-fan-sim-prices
+    fn local_prices(&self, _db: &CardsDatabase) -> Option<PriceCache> {
+        None
+    }
+
+    /// A cart filled with the deck, and the name of the link
+    fn cart_url(
+        &self,
+        _deck: &DeckOrPile,
+        _free_basic_cheers: bool,
+        _db: &CardsDatabase,
+    ) -> Option<(String, &'static str)> {
+        None
+    }
+}
+
+impl PriceCheckService {
+    pub fn provider(self) -> &'static dyn PriceProvider {
+        match self {
+            PriceCheckService::Yuyutei => &Yuyutei,
+            PriceCheckService::TcgPlayer => &TcgPlayer,
+            PriceCheckService::Surugaya => &Surugaya,
+            PriceCheckService::PriceList => &PriceListProvider,
+        }
+    }
+}
+
+pub struct Yuyutei;
+
+impl PriceProvider for Yuyutei {
+    fn name(&self) -> String {
+        "Yuyutei".into()
+    }
+
+    fn currency(&self) -> Currency {
+        Currency::Jpy
+    }
+
+    fn card_lang(&self) -> CardLanguage {
+        CardLanguage::Japanese
+    }
+
+    fn cache_key(&self, illustration: &CardIllustration) -> Option<PriceCacheKey> {
+        Some(PriceCacheKey::Yuyutei(
+            illustration.yuyutei_sell_url.as_ref()?.to_string(),
+        ))
+    }
+
+    fn card_url(&self, illustration: &CardIllustration) -> Option<String> {
+        illustration.yuyutei_sell_url.clone()
+    }
+
+    fn shared_id(&self, key: &PriceCacheKey) -> Option<ServiceId> {
+        match key {
+            PriceCacheKey::Yuyutei(url) => Some(ServiceId::from_yuyutei(url.clone())),
+            _ => None,
+        }
+    }
+}
+
+pub struct TcgPlayer;
+
+impl PriceProvider for TcgPlayer {
+    fn name(&self) -> String {
+        "TCGplayer".into()
+    }
+
+    fn currency(&self) -> Currency {
+        Currency::Usd
+    }
+
+    fn card_lang(&self) -> CardLanguage {
+        CardLanguage::English
+    }
+
+    fn cache_key(&self, illustration: &CardIllustration) -> Option<PriceCacheKey> {
+        Some(PriceCacheKey::TcgPlayer(illustration.tcgplayer_product_id?))
+    }
+
+    fn card_url(&self, illustration: &CardIllustration) -> Option<String> {
+        illustration.tcgplayer_url()
+    }
+
+    fn shared_id(&self, key: &PriceCacheKey) -> Option<ServiceId> {
+        match key {
+            PriceCacheKey::TcgPlayer(product_id) => Some(ServiceId::from_tcgplayer(*product_id)),
+            _ => None,
+        }
+    }
+
+    fn cart_url(
+        &self,
+        deck: &DeckOrPile,
+        free_basic_cheers: bool,
+        db: &CardsDatabase,
+    ) -> Option<(String, &'static str)> {
+        let product_ids = deck
+            .all_cards()
+            .filter(|c| !c.is_basic_cheer() || !free_basic_cheers)
+            .filter_map(|c| {
+                Some(format!(
+                    "{}-{}",
+                    c.amount,
+                    c.card_illustration(db)?.tcgplayer_product_id?
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join("||");
+        let url = format!(
+            "https://www.tcgplayer.com/massentry?c={product_ids}&productline=hololive%20OFFICIAL%20CARD%20GAME"
+        );
+        Some((url, "Mass Entry"))
+    }
+}
+
+/// Suruga-ya has no price feed, the cards link to their search
+pub struct Surugaya;
+
+impl PriceProvider for Surugaya {
+    fn name(&self) -> String {
+        "Suruga-ya".into()
+    }
+
+    fn currency(&self) -> Currency {
+        Currency::Jpy
+    }
+
+    fn card_lang(&self) -> CardLanguage {
+        CardLanguage::Japanese
+    }
+
+    fn cache_key(&self, illustration: &CardIllustration) -> Option<PriceCacheKey> {
+        Some(PriceCacheKey::Surugaya(illustration.card_number.clone()))
+    }
+
+    fn card_url(&self, illustration: &CardIllustration) -> Option<String> {
+        Some(format!(
+            "https://www.suruga-ya.jp/search?category=&search_word={}",
+            illustration.card_number
+        ))
+    }
+
+    fn has_prices(&self) -> bool {
+        false
+    }
+}
+
+/// A price list file, e.g. from a local store in Europe or Japan:
+/// `{"name": "...", "currency": "EUR", "card_url": "https://.../{card_number}", "prices": {"hSD01-001": 1.5}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceList {
+    pub name: String,
+    pub currency: Currency,
+    /// The page of a card, `{card_number}` is replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_url: Option<String>,
+    /// By card number, the same for every printing
+    pub prices: BTreeMap<String, f64>,
+}
+
+impl PriceList {
+    pub fn from_file(bytes: &[u8]) -> Result<Self, String> {
+        let list: PriceList =
+            serde_json::from_slice(bytes).map_err(|err| format!("Invalid price list: {err}"))?;
+        if list
+            .prices
+            .values()
+            .any(|value| !value.is_finite() || *value < 0.0)
+        {
+            return Err("Invalid price list: prices must be positive numbers".into());
+        }
+        Ok(list)
+    }
+}
+
+fn load_price_list() -> Option<PriceList> {
+    let ls = window().local_storage().ok()??;
+    let list = ls.get_item(PRICE_LIST_KEY).ok()??;
+    serde_json::from_str(&list).ok()
+}
+
+pub fn store_price_list(list: Option<&PriceList>) -> Option<()> {
+    let ls = window().local_storage().ok()??;
+    match list {
+        Some(list) => ls
+            .set_item(PRICE_LIST_KEY, &serde_json::to_string(list).ok()?)
+            .ok(),
+        None => ls.remove_item(PRICE_LIST_KEY).ok(),
+    }
+}
+
+pub struct PriceListProvider;
+
+impl PriceProvider for PriceListProvider {
+    fn name(&self) -> String {
+        PRICE_LIST
+            .read()
+            .as_ref()
+            .map(|list| list.name.clone())
+            .unwrap_or("Price list".into())
+    }
+
+    fn currency(&self) -> Currency {
+        PRICE_LIST
+            .read()
+            .as_ref()
+            .map(|list| list.currency)
+            .unwrap_or(Currency::Jpy)
+    }
+
+    fn card_lang(&self) -> CardLanguage {
+        match self.currency() {
+            Currency::Jpy => CardLanguage::Japanese,
+            Currency::Usd | Currency::Eur => CardLanguage::English,
+        }
+    }
+
+    fn cache_key(&self, illustration: &CardIllustration) -> Option<PriceCacheKey> {
+        PRICE_LIST
+            .read()
+            .as_ref()?
+            .prices
+            .contains_key(&illustration.card_number)
+            .then(|| PriceCacheKey::PriceList(illustration.card_number.clone()))
+    }
+
+    fn card_url(&self, illustration: &CardIllustration) -> Option<String> {
+        let list = PRICE_LIST.read();
+        let list = list.as_ref()?;
+        list.prices
+            .contains_key(&illustration.card_number)
+            .then(|| {
+                list.card_url
+                    .as_ref()
+                    .map(|url| url.replace("{card_number}", &illustration.card_number))
+            })?
+    }
+
+    fn local_prices(&self, _db: &CardsDatabase) -> Option<PriceCache> {
+        let list = PRICE_LIST.read();
+        let list = list.as_ref()?;
+        let now = Timestamp::now();
+        Some(
+            list.prices
+                .iter()
+                .map(|(card_number, value)| {
+                    (
+                        PriceCacheKey::PriceList(card_number.clone()),
+                        (now, list.currency.price(*value)),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The prices of every printing from hocg-fan-sim-prices, for all the services in it
+pub fn shared_prices(shared: &PricesDatabase, db: &CardsDatabase) -> PriceCache {
+    let now = Timestamp::now();
+    let providers: [&dyn PriceProvider; 2] = [&Yuyutei, &TcgPlayer];
+    db.values()
+        .flat_map(|c| &c.illustrations)
+        .flat_map(|i| providers.map(|p| (p, p.cache_key(i))))
+        .filter_map(|(p, key)| {
+            let key = key?;
+            let (_timestamp, price) = shared.get(&p.shared_id(&key)?)?;
+            let currency = p.currency();
+            Some((key, (now, currency.price(currency.amount(*price)?))))
+        })
+        .collect()
+}
@@ -1,54 +1,179 @@
+mod worker;
+
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::io::Cursor;
-use std::{collections::HashMap, sync::Arc};
+use std::ops::Range;
+use std::time::Duration;
 
-use ::image::ImageFormat;
+use ::image::codecs::jpeg::JpegEncoder;
 use ::image::imageops::FilterType;
+use ::image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
 use bitflags::bitflags;
 use dioxus::prelude::*;
-use futures::future::try_join_all;
-use futures::lock::Mutex;
-use imageproc::drawing::draw_line_segment_mut;
+use dioxus_sdk_time::sleep;
+use futures::{StreamExt, stream};
+use gloo::utils::window;
+use hocg_fan_sim_assets_model::CardOrderingOptions;
+use imageproc::drawing::{draw_filled_rect_mut, draw_line_segment_mut};
+use imageproc::edges::canny;
+use imageproc::geometric_transformations::{Interpolation, rotate_about_center};
+use itertools::Itertools;
 use printpdf::*;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 
-use super::{CardsDatabase, ImageOptions};
+use self::worker::{ImageId, ImageWorker, PagePlan, PlacedImage, ZipImage};
+use super::{CardsDatabase, CommonCard, ImageOptions, PileOfCards, bundle, decklist, text_proxy};
 use crate::components::deck_validation::{DeckValidation, has_missing_proxies};
+use crate::components::save_load::{LibraryDeck, list_library_decks};
+use crate::cut_file::{self, CutSheet};
+use crate::image_cache::{self, CacheUsage, ImageCache};
 use crate::sources::{DeckLike, DeckOrPile};
 use crate::tracker::TrackEvent;
 use crate::{
-    CardLanguage, EventType, PREVIEW_CARD_LANG, download_file, get_local_country, track_event,
+    CardLanguage, CardType, EventType, PREVIEW_CARD_LANG, download_file, get_local_country,
+    track_event,
 };
 
 const DPI: f32 = 300.0;
+const MAX_CONCURRENT_DOWNLOADS: usize = 6;
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const JPEG_QUALITY: u8 = 90;
 const INCH_PER_MM: f32 = 0.0393701;
 const DEFAULT_MARGIN: Mm = Mm(4.5);
 const DEFAULT_CROP_MARK_THICKNESS: Mm = Mm(0.25);
+const CARD_CORNER_RADIUS: Mm = Mm(3.0);
+const REGISTRATION_MARK_INSET: Mm = DEFAULT_MARGIN;
+const REGISTRATION_MARK_SIZE: Mm = Mm(5.0);
+const REGISTRATION_MARK_LENGTH: Mm = Mm(10.0);
+const REGISTRATION_MARK_THICKNESS: Mm = Mm(0.5);
 
 const DEFAULT_INCLUDE_CHEERS: bool = false;
 const DEFAULT_CROP_MARK_SIZE: CropMarksSize = CropMarksSize::Mm(3.0);
 const DEFAULT_CROP_MARK_POSITION: CropMarksPosition = CropMarksPosition::Centered;
 const DEFAULT_CARD_SIZE: CardSize = CardSize::Metric;
 const DEFAULT_GAP: Mm = Mm(0.5);
+const DEFAULT_BLEED: Mm = Mm(0.0);
+const DEFAULT_BLEED_MODE: BleedMode = BleedMode::Mirror;
+const DEFAULT_PAGE_MODE: PageMode = PageMode::Sheets;
+const DEFAULT_DUPLEX: bool = false;
+const DEFAULT_BACK_OFFSET: (Mm, Mm) = (Mm(0.0), Mm(0.0));
+const DEFAULT_REGISTRATION_MARKS: bool = false;
+const DEFAULT_INK_SAVER: InkSaver = InkSaver::None;
+const DEFAULT_WATERMARK: Watermark = Watermark::None;
+const DEFAULT_QUALITY: QualityPreset = QualityPreset::Lossless;
+const DEFAULT_TEXT_PROXIES: bool = true;
+const DEFAULT_TRANSLATION_OVERLAY: bool = false;
+const WATERMARK_TEXT: &str = "PROXY";
+const PAGE_SETUP_KEY: &str = "hocg-deck-convert.proxy_page_setup";
+const CALIBRATION_RULER: Mm = Mm(100.0);
+const CALIBRATION_SQUARE: Mm = Mm(10.0);
+const CALIBRATION_TEST_SQUARE: Mm = Mm(50.0);
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
-enum PaperSize {
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PaperSize {
     A4,
     Letter,
     Legal,
+    /// See [`PageSetup::custom_paper`]
+    Custom,
 }
 
 impl PaperSize {
-    fn dimensions(&self) -> (Mm, Mm) {
+    pub(super) fn dimensions(&self) -> Option<(Mm, Mm)> {
         match self {
-            PaperSize::A4 => (Mm(210.0), Mm(297.0)),
-            PaperSize::Letter => (Mm(215.9), Mm(279.4)),
-            PaperSize::Legal => (Mm(215.9), Mm(355.6)),
+            PaperSize::A4 => Some((Mm(210.0), Mm(297.0))),
+            PaperSize::Letter => Some((Mm(215.9), Mm(279.4))),
+            PaperSize::Legal => Some((Mm(215.9), Mm(355.6))),
+            PaperSize::Custom => None,
+        }
+    }
+
+    /// Letter in the Americas, A4 everywhere else
+    pub(super) fn local_default() -> Self {
+        match get_local_country().as_deref() {
+            Some("US" | "CA" | "MX" | "CR" | "PA" | "DO" | "GT" | "CL" | "CO" | "VE" | "PE") => {
+                PaperSize::Letter
+            }
+            _ => PaperSize::A4,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+/// Page margins in mm, the cards are centered inside
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+struct Margins {
+    top: f32,
+    right: f32,
+    bottom: f32,
+    left: f32,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Margins {
+            top: DEFAULT_MARGIN.0,
+            right: DEFAULT_MARGIN.0,
+            bottom: DEFAULT_MARGIN.0,
+            left: DEFAULT_MARGIN.0,
+        }
+    }
+}
+
+/// Printer specific settings, saved for the next time
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+struct PageSetup {
+    paper_size: PaperSize,
+    /// Width and height in mm, for [`PaperSize::Custom`]
+    custom_paper: (f32, f32),
+    margins: Margins,
+    /// Corrects the card size for printers that shrink the output, 1.0 is the actual size
+    scale: f32,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup {
+            paper_size: PaperSize::local_default(),
+            custom_paper: (210.0, 297.0),
+            margins: Margins::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl PageSetup {
+    fn load() -> Self {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|ls| ls.get_item(PAGE_SETUP_KEY).ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self) -> Option<()> {
+        let ls = window().local_storage().ok()??;
+        let json = serde_json::to_string(self).ok()?;
+        ls.set_item(PAGE_SETUP_KEY, &json).ok()
+    }
+
+    fn paper_dimensions(&self) -> (Mm, Mm) {
+        self.paper_size
+            .dimensions()
+            .unwrap_or((Mm(self.custom_paper.0), Mm(self.custom_paper.1)))
+    }
+
+    fn card_dimensions(&self, card_size: CardSize) -> (Mm, Mm) {
+        let (card_w, card_h) = card_size.dimensions();
+        (card_w * self.scale, card_h * self.scale)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 enum CardSize {
     Metric,
     Imperial,
@@ -83,24 +208,620 @@ impl Serialize for CropMarksSize {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+impl<'de> Deserialize<'de> for CropMarksSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "None" => Ok(CropMarksSize::None),
+            "Full length" => Ok(CropMarksSize::FullLength),
+            size => size
+                .strip_suffix("mm")
+                .and_then(|size_mm| size_mm.parse().ok())
+                .map(CropMarksSize::Mm)
+                .ok_or_else(|| de::Error::custom(format!("invalid crop marks size: {value}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 enum CropMarksPosition {
     Centered,
     CardCorners,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
-struct ProxySheetSettings {
+/// How the card image is extended into the bleed area
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum BleedMode {
+    /// Repeat the edge pixels
+    Replicate,
+    /// Mirror the image at its edges
+    Mirror,
+}
+
+impl BleedMode {
+    /// Maps a coordinate outside of `0..len` back into the image
+    fn source_coord(&self, coord: i64, len: u32) -> u32 {
+        let len = len as i64;
+        let coord = match self {
+            BleedMode::Replicate => coord,
+            BleedMode::Mirror if coord < 0 => -coord - 1,
+            BleedMode::Mirror if coord >= len => 2 * len - coord - 1,
+            BleedMode::Mirror => coord,
+        };
+        coord.clamp(0, len - 1) as u32
+    }
+
+    /// Extends the image by `bleed_px` on each side
+    fn add_bleed(&self, image: &::image::RgbaImage, bleed_px: u32) -> ::image::RgbaImage {
+        let (width, height) = image.dimensions();
+        ::image::RgbaImage::from_fn(width + bleed_px * 2, height + bleed_px * 2, |x, y| {
+            let src_x = self.source_coord(x as i64 - bleed_px as i64, width);
+            let src_y = self.source_coord(y as i64 - bleed_px as i64, height);
+            *image.get_pixel(src_x, src_y)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum PageMode {
+    /// As many cards as possible on the paper size
+    Sheets,
+    /// One card per page, the page is the card size with its bleed
+    SingleCard,
+}
+
+/// Encoding of the card images embedded in the PDF
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum PdfImageFormat {
+    Png,
+    /// With the quality, from 1 to 100
+    Jpeg(u8),
+}
+
+impl PdfImageFormat {
+    /// Rough compressed size of a card image pixel, used for the size estimate
+    fn bytes_per_pixel(&self) -> f32 {
+        match self {
+            PdfImageFormat::Png => 1.6,
+            PdfImageFormat::Jpeg(quality) => 0.03 + 0.35 * (*quality as f32 / 100.0).powi(3),
+        }
+    }
+
+    fn optimization(&self) -> ImageOptimizationOptions {
+        match self {
+            PdfImageFormat::Png => ImageOptimizationOptions {
+                // Don't resize, will lose image quality
+                max_image_size: None,
+                ..Default::default()
+            },
+            PdfImageFormat::Jpeg(quality) => ImageOptimizationOptions {
+                quality: Some(*quality as f32 / 100.0),
+                max_image_size: None,
+                auto_optimize: Some(false),
+                format: Some(ImageCompression::Jpeg),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Resolution and image encoding of the PDF, from small files to the best print quality
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+enum QualityPreset {
+    Small,
+    Balanced,
+    Lossless,
+    Maximum,
+}
+
+impl QualityPreset {
+    const ALL: [QualityPreset; 4] = [
+        QualityPreset::Small,
+        QualityPreset::Balanced,
+        QualityPreset::Lossless,
+        QualityPreset::Maximum,
+    ];
+
+    fn dpi(&self) -> f32 {
+        match self {
+            QualityPreset::Small => 150.0,
+            QualityPreset::Balanced | QualityPreset::Lossless => DPI,
+            QualityPreset::Maximum => 600.0,
+        }
+    }
+
+    fn image_format(&self) -> PdfImageFormat {
+        match self {
+            QualityPreset::Small => PdfImageFormat::Jpeg(75),
+            QualityPreset::Balanced => PdfImageFormat::Jpeg(JPEG_QUALITY),
+            QualityPreset::Lossless | QualityPreset::Maximum => PdfImageFormat::Png,
+        }
+    }
+
+    fn find(dpi: f32, image_format: PdfImageFormat) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.dpi() == dpi && p.image_format() == image_format)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            QualityPreset::Small => "Small file (150 DPI, JPEG)",
+            QualityPreset::Balanced => "Balanced (300 DPI, JPEG)",
+            QualityPreset::Lossless => "Lossless (300 DPI, PNG)",
+            QualityPreset::Maximum => "Maximum (600 DPI, PNG)",
+        }
+    }
+}
+
+/// Image processing to use less ink
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum InkSaver {
+    None,
+    Grayscale,
+    ReducedSaturation,
+    /// Only the edges of the artwork, on a white background
+    Outline,
+}
+
+impl InkSaver {
+    fn apply(&self, image: RgbaImage) -> RgbaImage {
+        match self {
+            InkSaver::None => image,
+            InkSaver::Grayscale => Self::desaturate(image, 0.0),
+            InkSaver::ReducedSaturation => Self::desaturate(image, 0.4),
+            InkSaver::Outline => {
+                let gray = DynamicImage::ImageRgba8(image.clone()).to_luma8();
+                let edges = canny(&gray, 50.0, 100.0);
+                RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+                    let value = 0xFF - edges.get_pixel(x, y).0[0];
+                    // keep the transparent corners
+                    Rgba([value, value, value, image.get_pixel(x, y).0[3]])
+                })
+            }
+        }
+    }
+
+    /// Blends each pixel with its gray value, 0.0 is fully gray
+    fn desaturate(mut image: RgbaImage, saturation: f32) -> RgbaImage {
+        for pixel in image.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            let gray = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            let blend = |c: u8| (gray + (c as f32 - gray) * saturation).round() as u8;
+            *pixel = Rgba([blend(r), blend(g), blend(b), a]);
+        }
+        image
+    }
+}
+
+/// Marks the cards as proxies
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum Watermark {
+    None,
+    /// Large translucent text across the card
+    Diagonal,
+    /// Small stamp in the bottom-right corner
+    CornerStamp,
+}
+
+impl Watermark {
+    /// The watermark of a card image of this size, drawn once for all the cards
+    fn layer(
+        &self,
+        (width, height): (u32, u32),
+        dpi: f32,
+    ) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let mm = |px: u32| Mm(px as f32 * 25.4 / dpi);
+        let page = || decklist::Page::with_dpi(mm(width), mm(height), dpi, None);
+
+        match self {
+            Watermark::None => Ok(None),
+            Watermark::Diagonal => {
+                let page = page()?;
+                let (w, h) = (page.width, page.height);
+                page.text_centered(
+                    WATERMARK_TEXT,
+                    (w / 2.0, h / 2.0),
+                    w * 0.3,
+                    true,
+                    "rgba(192, 32, 32, 0.44)",
+                    w * 0.85,
+                );
+                // from the bottom-left to the top-right corner
+                let angle = -(height as f32).atan2(width as f32);
+                let text = page.to_image()?.into_rgba8();
+                Ok(Some(rotate_about_center(
+                    &text,
+                    angle,
+                    Interpolation::Bilinear,
+                    Rgba([0, 0, 0, 0]),
+                )))
+            }
+            Watermark::CornerStamp => {
+                let page = page()?;
+                let (w, h) = (page.width, page.height);
+                let size = w * 0.09;
+                let padding = size * 0.3;
+                let inset = w * 0.06;
+                let box_w = page.text_width(WATERMARK_TEXT, size, true) + padding * 2.0;
+                let box_h = size + padding * 2.0;
+                let (box_x, box_y) = (w - inset - box_w, h - inset - box_h);
+                page.rect((box_x, box_y, box_w, box_h), Some("white"), None);
+                page.text(
+                    WATERMARK_TEXT,
+                    (box_x + padding, box_y + box_h / 2.0),
+                    size,
+                    true,
+                    "#B01010",
+                );
+                Ok(Some(page.to_image()?.into_rgba8()))
+            }
+        }
+    }
+}
+
+/// What is exported as images
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum ImageExport {
+    /// The proxy sheets, one image per page
+    Pages,
+    /// One image per card, named by card number
+    Cards,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RasterFormat {
+    Png,
+    Jpeg,
+}
+
+impl RasterFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            RasterFormat::Png => "png",
+            RasterFormat::Jpeg => "jpg",
+        }
+    }
+
+    fn encode(&self, image: &DynamicImage) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut bytes = Cursor::new(vec![]);
+        match self {
+            RasterFormat::Png => image.write_to(&mut bytes, ImageFormat::Png)?,
+            RasterFormat::Jpeg => {
+                // No transparency in JPEG, the rounded corners would be black
+                let mut flat = RgbaImage::from_pixel(
+                    image.width(),
+                    image.height(),
+                    Rgba([0xFF, 0xFF, 0xFF, 0xFF]),
+                );
+                ::image::imageops::overlay(&mut flat, image, 0, 0);
+                let flat = DynamicImage::ImageRgba8(flat).to_rgb8();
+                JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY).encode_image(&flat)?;
+            }
+        }
+        Ok(bytes.into_inner())
+    }
+}
+
+/// Order of the cards on the sheets
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+enum PrintOrder {
+    Deck,
+    /// By card number, the cards of a set follow each other
+    Set,
+    /// Oshi, members, supports then cheers, like sorting a binder
+    Sorting,
+}
+
+/// Amounts to print for each printing of the deck, edited from the deck amounts
+#[derive(Debug, Clone, PartialEq, Default)]
+struct PrintSelection {
+    amounts: HashMap<(String, Option<usize>), u32>,
+}
+
+impl PrintSelection {
+    fn amount(&self, card: &CommonCard) -> u32 {
+        self.amounts
+            .get(&(card.card_number.clone(), card.illustration_idx))
+            .copied()
+            .unwrap_or(card.amount)
+    }
+
+    fn set_amount(&mut self, card: &CommonCard, amount: u32) {
+        self.amounts
+            .insert((card.card_number.clone(), card.illustration_idx), amount);
+    }
+
+    fn is_whole_deck(&self, deck: &DeckOrPile) -> bool {
+        deck.all_cards().all(|c| self.amount(c) == c.amount)
+    }
+
+    fn one_of_each(deck: &DeckOrPile) -> Self {
+        let mut selection = Self::default();
+        for card in deck.all_cards() {
+            selection.set_amount(card, card.amount.min(1));
+        }
+        selection
+    }
+
+    /// Only the copies that are not in `owned`, matched by card number in any printing
+    fn missing_from(deck: &DeckOrPile, owned: &DeckOrPile) -> Self {
+        let mut owned_amounts: HashMap<&String, u32> = HashMap::new();
+        for card in owned.all_cards() {
+            *owned_amounts.entry(&card.card_number).or_default() += card.amount;
+        }
+
+        let mut selection = Self::default();
+        for card in deck.all_cards() {
+            let owned = owned_amounts.entry(&card.card_number).or_default();
+            let used = (*owned).min(card.amount);
+            *owned -= used;
+            selection.set_amount(card, card.amount - used);
+        }
+        selection
+    }
+
+    /// The cards to print, as a pile with the name of the deck
+    fn apply(&self, deck: &DeckOrPile, order: PrintOrder, db: &CardsDatabase) -> DeckOrPile {
+        if order == PrintOrder::Deck && self.is_whole_deck(deck) {
+            return deck.clone();
+        }
+
+        let mut cards = deck
+            .all_cards()
+            .map(|c| CommonCard {
+                amount: self.amount(c),
+                ..c.clone()
+            })
+            .filter(|c| c.amount > 0)
+            .collect_vec();
+        match order {
+            PrintOrder::Deck => {}
+            PrintOrder::Set => {
+                cards.sort_by(|a, b| {
+                    (&a.card_number, a.illustration_idx).cmp(&(&b.card_number, b.illustration_idx))
+                });
+            }
+            PrintOrder::Sorting => {
+                let sort_opt = CardOrderingOptions::member_first();
+                cards.sort_by_cached_key(|c| {
+                    (
+                        c.card_info(db).map(|c| sort_opt.for_card(c)),
+                        c.illustration_idx,
+                    )
+                });
+            }
+        }
+
+        DeckOrPile::Pile(PileOfCards {
+            name: Some(deck.required_deck_name(db)),
+            cards,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ProxySheetSettings {
     card_lang: CardLanguage,
-    paper_size: PaperSize,
+    page_setup: PageSetup,
     include_cheers: bool,
     crop_marks_size: CropMarksSize,
     crop_marks_position: CropMarksPosition,
     card_size: CardSize,
     gap: Mm,
+    bleed: Mm,
+    bleed_mode: BleedMode,
+    page_mode: PageMode,
+    duplex: bool,
+    /// Shifts the back pages (x, y) to correct the printer alignment
+    back_offset: (Mm, Mm),
+    /// For cutting machines, see [`ProxySheetSettings::cut_sheet`]
+    registration_marks: bool,
+    ink_saver: InkSaver,
+    watermark: Watermark,
+    /// Resolution of the card images in the PDF
+    pdf_dpi: f32,
+    pdf_image_format: PdfImageFormat,
+    /// Prints the card text for the cards without an image, instead of skipping them
+    text_proxies: bool,
+    /// English text over the Japanese card text
+    translation_overlay: bool,
+}
+
+impl ProxySheetSettings {
+    /// Default settings, with the saved page setup
+    pub fn with_defaults(card_lang: CardLanguage) -> Self {
+        ProxySheetSettings {
+            card_lang,
+            page_setup: PageSetup::load(),
+            include_cheers: DEFAULT_INCLUDE_CHEERS,
+            crop_marks_size: DEFAULT_CROP_MARK_SIZE,
+            crop_marks_position: DEFAULT_CROP_MARK_POSITION,
+            card_size: DEFAULT_CARD_SIZE,
+            gap: DEFAULT_GAP,
+            bleed: DEFAULT_BLEED,
+            bleed_mode: DEFAULT_BLEED_MODE,
+            page_mode: DEFAULT_PAGE_MODE,
+            duplex: DEFAULT_DUPLEX,
+            back_offset: DEFAULT_BACK_OFFSET,
+            registration_marks: DEFAULT_REGISTRATION_MARKS,
+            ink_saver: DEFAULT_INK_SAVER,
+            watermark: DEFAULT_WATERMARK,
+            pdf_dpi: DEFAULT_QUALITY.dpi(),
+            pdf_image_format: DEFAULT_QUALITY.image_format(),
+            text_proxies: DEFAULT_TEXT_PROXIES,
+            translation_overlay: DEFAULT_TRANSLATION_OVERLAY,
+        }
+    }
+
+    /// Number of advanced settings that are not the default
+    fn changed_count(&self) -> usize {
+        [
+            self.crop_marks_size != DEFAULT_CROP_MARK_SIZE,
+            self.crop_marks_position != DEFAULT_CROP_MARK_POSITION,
+            self.card_size != DEFAULT_CARD_SIZE,
+            self.gap != DEFAULT_GAP,
+            self.bleed != DEFAULT_BLEED,
+            self.bleed_mode != DEFAULT_BLEED_MODE,
+            self.back_offset != DEFAULT_BACK_OFFSET,
+            self.registration_marks != DEFAULT_REGISTRATION_MARKS,
+            self.ink_saver != DEFAULT_INK_SAVER,
+            self.watermark != DEFAULT_WATERMARK,
+            self.page_setup.margins != Margins::default(),
+            self.page_setup.scale != 1.0,
+            QualityPreset::find(self.pdf_dpi, self.pdf_image_format).is_none(),
+            self.text_proxies != DEFAULT_TEXT_PROXIES,
+        ]
+        .iter()
+        .filter(|&&x| x)
+        .count()
+    }
+
+    pub fn file_name(&self, deck: &DeckOrPile, db: &CardsDatabase) -> String {
+        self.pdf_file_name(&deck.file_name(db))
+    }
+
+    pub fn batch_file_name(&self, deck_count: usize) -> String {
+        self.pdf_file_name(&format!("{deck_count}_decks"))
+    }
+
+    fn pdf_file_name(&self, file_name: &str) -> String {
+        let lang = match self.card_lang {
+            CardLanguage::Japanese => "jp",
+            CardLanguage::English => "en",
+        };
+        let ps = match self.page_setup.paper_size {
+            PaperSize::A4 => "a4",
+            PaperSize::Letter => "letter",
+            PaperSize::Legal => "legal",
+            PaperSize::Custom => "custom",
+        };
+
+        let duplex = if self.duplex { "_duplex" } else { "" };
+
+        match self.page_mode {
+            PageMode::Sheets => format!("{file_name}.proxy_sheets.{lang}_{ps}{duplex}.pdf"),
+            PageMode::SingleCard => format!("{file_name}.proxy_cards.{lang}{duplex}.pdf"),
+        }
+    }
+
+    pub fn images_file_name(
+        &self,
+        deck: &DeckOrPile,
+        db: &CardsDatabase,
+        export: ImageExport,
+        format: RasterFormat,
+    ) -> String {
+        let settings = match export {
+            ImageExport::Pages => *self,
+            ImageExport::Cards => ProxySheetSettings {
+                page_mode: PageMode::SingleCard,
+                duplex: false,
+                ..*self
+            },
+        };
+        let file_name = settings.file_name(deck, db);
+        let file_name = file_name.trim_end_matches(".pdf");
+        format!("{file_name}.{}.zip", format.extension())
+    }
+
+    pub fn calibration_file_name(&self) -> String {
+        let ps = match self.page_setup.paper_size {
+            PaperSize::A4 => "a4",
+            PaperSize::Letter => "letter",
+            PaperSize::Legal => "legal",
+            PaperSize::Custom => "custom",
+        };
+        format!("proxy_sheets.calibration.{ps}.pdf")
+    }
+
+    pub fn cut_file_name(&self, deck: &DeckOrPile, db: &CardsDatabase, ext: &str) -> String {
+        let file_name = self.file_name(deck, db);
+        let file_name = file_name.trim_end_matches(".pdf");
+        format!("{file_name}.cut.{ext}")
+    }
+
+    /// Cut paths matching the pages of the PDF, the same for every page
+    pub fn cut_sheet(&self, rounded_corners: bool) -> Result<CutSheet, Box<dyn Error>> {
+        let layout = self.layout();
+        if layout.cards_per_page == 0 {
+            return Err("Paper size is too small to fit any card with current margins/gap".into());
+        }
+
+        Ok(CutSheet {
+            width: layout.page_width.0,
+            height: layout.page_height.0,
+            cuts: (0..layout.cards_per_page)
+                .map(|idx| layout.trim_rect(idx))
+                .collect(),
+            corner_radius: if rounded_corners {
+                CARD_CORNER_RADIUS.0
+            } else {
+                0.0
+            },
+            registration_marks: if self.registration_marks && self.page_mode == PageMode::Sheets {
+                layout.registration_marks()?
+            } else {
+                vec![]
+            },
+        })
+    }
+
+    fn layout(&self) -> Layout {
+        self.layout_at(self.pdf_dpi)
+    }
+
+    /// Estimated size of the PDF in bytes, each unique card image is embedded once
+    fn estimated_pdf_size(&self, deck: &DeckOrPile, db: &CardsDatabase) -> u64 {
+        let layout = self.layout();
+        let cards = printed_cards(deck, db, self);
+        let unique_images = cards
+            .iter()
+            .map(|c| (&c.card_number, c.illustration_idx))
+            .unique()
+            .count()
+            + if self.duplex { 2 } else { 0 };
+
+        let (card_w, card_h) = self.page_setup.card_dimensions(self.card_size);
+        let image_px = mm_to_px(card_w + self.bleed * 2.0, layout.dpi)
+            * mm_to_px(card_h + self.bleed * 2.0, layout.dpi);
+        let image_bytes = image_px as f32 * self.pdf_image_format.bytes_per_pixel();
+
+        // the overlay is mostly transparent, and the pages are a few operations
+        let pages = cards.len().div_ceil(layout.cards_per_page.max(1));
+        let overlay_bytes = (mm_to_px(layout.page_width, layout.dpi)
+            * mm_to_px(layout.page_height, layout.dpi)) as f32
+            * 0.02;
+
+        (unique_images as f32 * image_bytes + overlay_bytes) as u64 + pages as u64 * 1_000
+    }
+
+    fn layout_at(&self, dpi: f32) -> Layout {
+        let (card_w, card_h) = self.page_setup.card_dimensions(self.card_size);
+        match self.page_mode {
+            PageMode::Sheets => {
+                let (page_width, page_height) = self.page_setup.paper_dimensions();
+                Layout::compute(
+                    page_width,
+                    page_height,
+                    self.page_setup.margins,
+                    dpi,
+                    card_w,
+                    card_h,
+                    self.gap,
+                    self.bleed,
+                    self.crop_marks_position,
+                )
+            }
+            PageMode::SingleCard => Layout::single_card(dpi, card_w, card_h, self.bleed),
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 struct Layout {
     page_width: Mm,
     page_height: Mm,
@@ -108,6 +829,8 @@ struct Layout {
     margin_x: Mm,
     margin_y: Mm,
     gap: Mm,
+    /// Extra image around each card, the card size is the trim line
+    bleed: Mm,
     rotated: bool,
     card_w: Mm,
     card_h: Mm,
@@ -135,30 +858,29 @@ impl Layout {
     fn compute(
         page_width: Mm,
         page_height: Mm,
+        margins: Margins,
         dpi: f32,
         card_w: Mm,
         card_h: Mm,
         gap: Mm,
+        bleed: Mm,
         crop_marks_position: CropMarksPosition,
     ) -> Self {
-        let mut margin_x = DEFAULT_MARGIN;
-        let mut margin_y = DEFAULT_MARGIN;
+        let mut margin_x = Mm(margins.left);
+        let mut margin_y = Mm(margins.top);
+        let area_w = page_width - Mm(margins.left) - Mm(margins.right);
+        let area_h = page_height - Mm(margins.top) - Mm(margins.bottom);
+
+        // Each slot contains the card and its bleed
+        let (card_w, card_h) = (card_w + bleed * 2.0, card_h + bleed * 2.0);
 
         // Compute how many cards fit on the page vertically
-        let fit_w = ((page_width - margin_x - margin_x) / (card_w + gap))
-            .floor()
-            .max(0.0) as usize;
-        let fit_h = ((page_height - margin_y - margin_y) / (card_h + gap))
-            .floor()
-            .max(0.0) as usize;
+        let fit_w = (area_w / (card_w + gap)).floor().max(0.0) as usize;
+        let fit_h = (area_h / (card_h + gap)).floor().max(0.0) as usize;
 
         // Compute how many cards fit if we rotate the cards
-        let rot_fit_w = ((page_width - margin_x - margin_x) / (card_h + gap))
-            .floor()
-            .max(0.0) as usize;
-        let rot_fit_h = ((page_height - margin_y - margin_y) / (card_w + gap))
-            .floor()
-            .max(0.0) as usize;
+        let rot_fit_w = (area_w / (card_h + gap)).floor().max(0.0) as usize;
+        let rot_fit_h = (area_h / (card_w + gap)).floor().max(0.0) as usize;
 
         // Choose the orientation that fits more cards on the page
         let (rotated, card_w, card_h, fit_w, fit_h) = if fit_w * fit_h >= rot_fit_w * rot_fit_h {
@@ -167,10 +889,12 @@ impl Layout {
             (true, card_h, card_w, rot_fit_w, rot_fit_h)
         };
 
-        // Center the grid on the page
+        // Center the grid between the margins
         if fit_w > 0 && fit_h > 0 {
-            margin_x = (page_width - (card_w * (fit_w as f32) + gap * ((fit_w - 1) as f32))) / 2.0;
-            margin_y = (page_height - (card_h * (fit_h as f32) + gap * ((fit_h - 1) as f32))) / 2.0;
+            margin_x =
+                margin_x + (area_w - (card_w * (fit_w as f32) + gap * ((fit_w - 1) as f32))) / 2.0;
+            margin_y =
+                margin_y + (area_h - (card_h * (fit_h as f32) + gap * ((fit_h - 1) as f32))) / 2.0;
         }
 
         Self {
@@ -180,9 +904,10 @@ impl Layout {
             margin_x,
             margin_y,
             gap,
+            bleed,
             rotated,
-            card_w,
-            card_h,
+            card_w: card_w - bleed * 2.0,
+            card_h: card_h - bleed * 2.0,
             fit_w,
             fit_h,
             cards_per_page: fit_w * fit_h,
@@ -190,32 +915,128 @@ impl Layout {
         }
     }
 
-    /// Returns the bottom-left translation (Mm) for the card slot.
+    /// A page of the size of the card and its bleed, as expected by print shops
+    fn single_card(dpi: f32, card_w: Mm, card_h: Mm, bleed: Mm) -> Self {
+        Self {
+            page_width: card_w + bleed * 2.0,
+            page_height: card_h + bleed * 2.0,
+            dpi,
+            margin_x: Mm(0.0),
+            margin_y: Mm(0.0),
+            gap: Mm(0.0),
+            bleed,
+            rotated: false,
+            card_w,
+            card_h,
+            fit_w: 1,
+            fit_h: 1,
+            cards_per_page: 1,
+            crop_marks_position: CropMarksPosition::CardCorners,
+        }
+    }
+
+    /// Returns the bottom-left translation (Mm) for the card slot, at the trim line.
     fn card_translate(&self, idx_in_page: usize) -> (Mm, Mm) {
         let col = idx_in_page % self.fit_w;
         let row = idx_in_page / self.fit_w;
+        let (slot_w, slot_h) = (
+            self.card_w + self.bleed * 2.0,
+            self.card_h + self.bleed * 2.0,
+        );
 
-        let x = self.margin_x + (self.card_w + self.gap) * (col as f32);
+        let x = self.margin_x + (slot_w + self.gap) * (col as f32) + self.bleed;
 
         // PDF origin is bottom-left; place rows from top to bottom.
-        let y = self.page_height
-            - self.margin_y
-            - (self.card_h + self.gap) * (row as f32)
-            - self.card_h;
+        let y = self.page_height - self.margin_y - (slot_h + self.gap) * (row as f32) - slot_h
+            + self.bleed;
 
         (x, y)
     }
 
+    /// Returns the bottom-left translation (Mm) for the card image, including its bleed.
+    fn image_translate(&self, idx_in_page: usize) -> (Mm, Mm) {
+        let (x, y) = self.card_translate(idx_in_page);
+        (x - self.bleed, y - self.bleed)
+    }
+
+    /// Returns the bottom-left translation (Mm) for the card back image.
+    ///
+    /// The back page is mirrored horizontally, to line up with the front when flipped on the long edge.
+    fn back_image_translate(&self, idx_in_page: usize) -> (Mm, Mm) {
+        let (x, y) = self.image_translate(idx_in_page);
+        let image_w = self.card_w + self.bleed * 2.0;
+        (self.page_width - x - image_w, y)
+    }
+
+    /// Returns the trim line of the card slot, from the top-left corner of the page.
+    fn trim_rect(&self, idx_in_page: usize) -> cut_file::Rect {
+        let (x, y) = self.card_translate(idx_in_page);
+        cut_file::Rect {
+            x: x.0,
+            y: (self.page_height - y - self.card_h).0,
+            width: self.card_w.0,
+            height: self.card_h.0,
+        }
+    }
+
+    /// Returns the registration marks in the corners of the page: a square on the top-left,
+    /// and L shapes on the top-right and bottom-left.
+    fn registration_marks(&self) -> Result<Vec<cut_file::Rect>, String> {
+        let (inset, size) = (REGISTRATION_MARK_INSET.0, REGISTRATION_MARK_SIZE.0);
+        let (len, thick) = (REGISTRATION_MARK_LENGTH.0, REGISTRATION_MARK_THICKNESS.0);
+        let (w, h) = (self.page_width.0, self.page_height.0);
+        let rect = |x, y, width, height| cut_file::Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        let marks = vec![
+            rect(inset, inset, size, size),
+            rect(w - inset - len, inset, len, thick),
+            rect(w - inset - thick, inset, thick, len),
+            rect(inset, h - inset - thick, len, thick),
+            rect(inset, h - inset - len, thick, len),
+        ];
+
+        // The marks must not be printed over the cards
+        let bleed = self.bleed.0;
+        let overlaps = (0..self.cards_per_page)
+            .map(|idx| {
+                let trim = self.trim_rect(idx);
+                rect(
+                    trim.x - bleed,
+                    trim.y - bleed,
+                    trim.width + bleed * 2.0,
+                    trim.height + bleed * 2.0,
+                )
+            })
+            .any(|card| marks.iter().any(|mark| mark.intersects(&card)));
+        if overlaps {
+            return Err("Not enough margin on the page for the registration marks".into());
+        }
+
+        Ok(marks)
+    }
+
+    /// Whether the crop marks are shared between cards, in the middle of the gaps.
+    ///
+    /// If the gap is too small, force crop marks to be centered to avoid overly thick crop marks.
+    /// With a bleed, the middle of the gap is not the trim line, so the marks are on the card corners.
+    fn centered_crop_marks(&self) -> bool {
+        self.bleed <= Mm(0.0)
+            && (self.crop_marks_position == CropMarksPosition::Centered
+                || self.gap <= DEFAULT_CROP_MARK_THICKNESS)
+    }
+
     /// Returns the positions of crop marks (Mm) for all card slots.
     fn crop_marks_positions(&self) -> Vec<(Mm, Mm, CropMarkFlags)> {
         let half_gap = self.gap / 2.0;
 
         let mut positions = Vec::new();
 
-        // If the gap is too small, force crop marks to be centered to avoid overly thick crop marks
-        if self.crop_marks_position == CropMarksPosition::Centered
-            || self.gap <= DEFAULT_CROP_MARK_THICKNESS
-        {
+        if self.centered_crop_marks() {
             // Centered crop marks
             for row in 0..self.fit_h {
                 for col in 0..self.fit_w {
@@ -274,7 +1095,7 @@ impl Layout {
                     }
                 }
             }
-        } else if self.crop_marks_position == CropMarksPosition::CardCorners {
+        } else {
             // Card corners crop marks
             for idx in 0..self.cards_per_page {
                 let thickness = DEFAULT_CROP_MARK_THICKNESS;
@@ -305,8 +1126,6 @@ impl Layout {
                     flags,
                 ));
             }
-        } else {
-            unreachable!()
         }
 
         // from top-left top to bottom-right
@@ -372,697 +1191,3035 @@ fn draw_line_thick_mut(
     }
 }
 
-async fn generate_pdf(
-    deck: &DeckOrPile,
-    db: &CardsDatabase,
-    settings: ProxySheetSettings,
-) -> Result<Vec<u8>, Box<dyn Error>> {
-    let (card_w, card_h) = settings.card_size.dimensions();
-    let card_width_px: u32 = (DPI * INCH_PER_MM * card_w.0).ceil() as u32;
-    let card_height_px: u32 = (DPI * INCH_PER_MM * card_h.0).ceil() as u32;
-
-    let (page_width, page_height) = settings.paper_size.dimensions();
-
-    let layout = Layout::compute(
-        page_width,
-        page_height,
-        DPI,
-        card_w,
-        card_h,
-        settings.gap,
-        settings.crop_marks_position,
-    );
-    if layout.cards_per_page == 0 {
-        return Err("Paper size is too small to fit any card with current margins/gap".into());
+/// Downloads an image, retrying with an increasing delay
+async fn download_image(img_path: &str) -> Result<Vec<u8>, String> {
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let resp = match reqwest::get(img_path)
+            .await
+            .and_then(|r| r.error_for_status())
+        {
+            Ok(resp) => resp.bytes().await,
+            Err(e) => Err(e),
+        };
+        match resp {
+            Ok(bytes) => return Ok(bytes.to_vec()),
+            Err(e) if attempt >= DOWNLOAD_ATTEMPTS => return Err(e.to_string()),
+            Err(_) => {
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Size of a card image in pixels, without the bleed
+fn card_size_px(settings: &ProxySheetSettings, layout: &Layout) -> (u32, u32) {
+    let (card_w, card_h) = settings.page_setup.card_dimensions(settings.card_size);
+    (
+        (layout.dpi * INCH_PER_MM * card_w.0).ceil() as u32,
+        (layout.dpi * INCH_PER_MM * card_h.0).ceil() as u32,
+    )
+}
+
+/// The bytes of a card image, before the decoding in the worker
+enum CardBytes {
+    /// Already at the card size
+    Cached(Vec<u8>),
+    Downloaded(Vec<u8>),
+}
+
+/// The card image from the cache, or downloaded.
+/// The cache is optional, the browser may not allow it.
+async fn fetch_card_image(
+    img_path: &str,
+    size: (u32, u32),
+    cache: Option<&ImageCache>,
+) -> Result<CardBytes, String> {
+    let key = image_cache::cache_key(img_path, size);
+    if let Some(cache) = cache
+        && let Some(bytes) = cache.get(&key).await
+    {
+        return Ok(CardBytes::Cached(bytes));
+    }
+
+    Ok(CardBytes::Downloaded(download_image(img_path).await?))
+}
+
+/// Decodes the card image in the worker at the card size, and caches the downloaded ones
+async fn load_card_image(
+    worker: &mut ImageWorker,
+    img_path: &str,
+    size: (u32, u32),
+    cache: Option<&ImageCache>,
+    bytes: Result<CardBytes, String>,
+) -> Result<ImageId, String> {
+    let bytes = match bytes? {
+        CardBytes::Cached(bytes) => match worker.decode(bytes, size, false).await {
+            Ok((id, _)) => return Ok(id),
+            // a broken entry is a cache miss
+            Err(_) => download_image(img_path).await?,
+        },
+        CardBytes::Downloaded(bytes) => bytes,
+    };
+
+    let (id, webp) = worker.decode(bytes, size, cache.is_some()).await?;
+    if let (Some(cache), Some(webp)) = (cache, webp) {
+        // the image is still good without the cache
+        let _ = cache
+            .put(&image_cache::cache_key(img_path, size), &webp)
+            .await;
     }
+    Ok(id)
+}
 
-    // Build the list of cards to print
-    let cards: Box<dyn Iterator<Item = &crate::CommonCard>> = if settings.include_cheers {
-        Box::new(deck.all_cards())
+/// Resizes a card image to the card size with its bleed, and rotates it like the layout.
+/// The translation and the watermark are layers drawn over the card.
+fn process_card_image(
+    image: DynamicImage,
+    settings: &ProxySheetSettings,
+    layout: &Layout,
+    translation: Option<&RgbaImage>,
+    watermark: Option<&RgbaImage>,
+) -> DynamicImage {
+    let (card_width_px, card_height_px) = card_size_px(settings, layout);
+    let bleed_px: u32 = (layout.dpi * INCH_PER_MM * settings.bleed.0).round() as u32;
+
+    let image = if (image.width(), image.height()) != (card_width_px, card_height_px) {
+        image.resize_exact(card_width_px, card_height_px, FilterType::CatmullRom)
     } else {
-        Box::new(
-            deck.all_cards()
-                .filter(|c| c.card_type(db) != Some(crate::CardType::Cheer)),
-        )
+        image
+    };
+
+    let image = match translation {
+        Some(translation) => {
+            let mut image = image.to_rgba8();
+            ::image::imageops::overlay(&mut image, translation, 0, 0);
+            DynamicImage::ImageRgba8(image)
+        }
+        None => image,
+    };
+
+    let image = if settings.ink_saver != InkSaver::None || watermark.is_some() {
+        let mut image = settings.ink_saver.apply(image.to_rgba8());
+        if let Some(watermark) = watermark {
+            ::image::imageops::overlay(&mut image, watermark, 0, 0);
+        }
+        DynamicImage::ImageRgba8(image)
+    } else {
+        image
+    };
+
+    // Extend the image past the trim line
+    let image = if bleed_px > 0 {
+        DynamicImage::ImageRgba8(settings.bleed_mode.add_bleed(&image.to_rgba8(), bleed_px))
+    } else {
+        image
     };
-    let cards: Vec<_> = cards
+
+    // Rotate the image if needed
+    if layout.rotated {
+        image.rotate90()
+    } else {
+        image
+    }
+}
+
+/// Converts to PNG bytes, then decode into printpdf RawImage.
+pub(super) fn to_raw_image(image: &DynamicImage) -> Result<RawImage, Box<dyn Error>> {
+    let mut bytes = Cursor::new(vec![]);
+    ::image::write_buffer_with_format(
+        &mut bytes,
+        image.as_bytes(),
+        image.width(),
+        image.height(),
+        image.color(),
+        ImageFormat::Png,
+    )?;
+    Ok(RawImage::decode_from_bytes(
+        &bytes.into_inner(),
+        &mut vec![],
+    )?)
+}
+
+/// Same as the card images when missing
+fn card_back(card_type: CardType) -> &'static str {
+    match card_type {
+        CardType::Oshi | CardType::Cheer => "cheer-back.webp",
+        CardType::Main => "card-back.webp",
+    }
+}
+
+fn mm_to_px(mm: Mm, dpi: f32) -> i64 {
+    (mm.0 * INCH_PER_MM * dpi).round() as i64
+}
+
+type ImageKey<'a> = (&'a String, Option<usize>);
+
+/// The cards to print with their proxy, one entry per copy
+fn printed_cards<'a>(
+    deck: &'a DeckOrPile,
+    db: &CardsDatabase,
+    settings: &ProxySheetSettings,
+) -> Vec<&'a CommonCard> {
+    deck.all_cards()
+        .filter(|c| settings.include_cheers || c.card_type(db) != Some(CardType::Cheer))
         .filter(|c| {
-            c.image_path(db, settings.card_lang, ImageOptions::proxy_print())
-                .is_some()
+            settings.text_proxies
+                || c.image_path(db, settings.card_lang, ImageOptions::proxy_print())
+                    .is_some()
         })
-        .flat_map(|c| std::iter::repeat_n(c.clone(), c.amount as usize))
-        .collect();
+        .flat_map(|c| std::iter::repeat_n(c, c.amount as usize))
+        .collect()
+}
 
-    let pages_count = (cards.len() as f32 / layout.cards_per_page as f32).ceil() as usize;
+/// Card number and name, for the skipped cards report
+fn card_label(card: &CommonCard, db: &CardsDatabase) -> String {
+    let name = card
+        .card_info(db)
+        .and_then(|c| c.name.english.as_deref().or(c.name.japanese.as_deref()));
+    match name {
+        Some(name) => format!("{} {name}", card.card_number),
+        None => card.card_number.clone(),
+    }
+}
 
-    // Create PDF document
-    let title = format!("Proxy sheets for {}", deck.required_deck_name(db));
-    let mut doc = PdfDocument::new(&title);
-    doc.metadata.info.producer = "hololive OCG Deck Converter".to_string();
-    // no metadata date for wasm, printpdf can't do it
-
-    // Download and cache images once per unique card
-    let img_cache = Arc::new(Mutex::new(HashMap::with_capacity(cards.len())));
-
-    let download_tasks = deck.all_cards().map(|card| {
-        let img_cache = img_cache.clone();
-        async move {
-            let Some(img_path) =
-                card.image_path(db, settings.card_lang, ImageOptions::proxy_print())
-            else {
-                // Skip missing card
-                return Ok::<(), Box<dyn Error>>(());
-            };
+/// Human readable file size
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1_000_000 => format!("{:.0} kB", bytes as f64 / 1_000.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_000_000.0),
+    }
+}
 
-            let image_bytes = reqwest::get(&img_path).await?.bytes().await?;
-            let image = ::image::load_from_memory_with_format(&image_bytes, ImageFormat::WebP)?;
-            let image = image.resize_exact(card_width_px, card_height_px, FilterType::CatmullRom);
+/// Progress of a proxy job, in steps of one card image
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct JobProgress {
+    pub done: usize,
+    pub total: usize,
+}
 
-            // Rotate the image if needed
-            let (image, card_width_px, card_height_px) = if layout.rotated {
-                (image.rotate90(), card_height_px, card_width_px)
-            } else {
-                (image, card_width_px, card_height_px)
-            };
+/// A card left out of the proxies, after the download retries
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedCard {
+    pub name: String,
+    pub error: String,
+}
 
-            // Convert to PNG bytes, then decode into printpdf RawImage.
-            let mut bytes = Cursor::new(vec![]);
-            ::image::write_buffer_with_format(
-                &mut bytes,
-                image.as_bytes(),
-                card_width_px,
-                card_height_px,
-                image.color(),
-                ImageFormat::Png,
-            )?;
-            let raw = RawImage::decode_from_bytes_async(&bytes.into_inner(), &mut vec![]).await?;
+/// The generated file, with the cards that are missing from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobOutput {
+    pub file: Vec<u8>,
+    pub skipped: Vec<SkippedCard>,
+}
 
-            let key = Some((&card.card_number, card.illustration_idx));
-            img_cache.lock().await.insert(key, raw);
+/// Reports the progress, the heavy steps run in the [`ImageWorker`]
+struct Job<F> {
+    progress: JobProgress,
+    on_progress: F,
+    skipped: Vec<SkippedCard>,
+}
 
-            Ok(())
+impl<F: FnMut(JobProgress)> Job<F> {
+    fn new(on_progress: F) -> Self {
+        Self {
+            progress: JobProgress::default(),
+            on_progress,
+            skipped: vec![],
         }
-    });
-    try_join_all(download_tasks).await?;
+    }
 
-    let img_cache = Arc::try_unwrap(img_cache).unwrap().into_inner();
+    fn add_steps(&mut self, steps: usize) {
+        self.progress.total += steps;
+        (self.on_progress)(self.progress);
+    }
 
-    // Add the images to the document resources and get their IDs
-    let image_ids: HashMap<_, _> = img_cache
-        .into_iter()
-        .map(|(key, image)| (key, doc.add_image(&image)))
-        .collect();
+    fn step(&mut self) {
+        self.progress.done += 1;
+        (self.on_progress)(self.progress);
+    }
 
-    // Optional crop marks overlay
-    let overlay_id: Option<XObjectId> = if settings.crop_marks_size != CropMarksSize::None {
-        let page_w_px = layout.page_width.into_pt().into_px(layout.dpi).0;
-        let page_h_px = layout.page_height.into_pt().into_px(layout.dpi).0;
-        let crop_mark_thickness = DEFAULT_CROP_MARK_THICKNESS
-            .into_pt()
-            .into_px(layout.dpi)
-            .0
-            .max(1) as u32;
-        let crop_mark_color = ::image::Rgba([0x68, 0x68, 0x68, 0xFF]);
-        let crop_mark_len = if let CropMarksSize::Mm(mark_size) = settings.crop_marks_size {
-            Mm(mark_size).into_pt().into_px(layout.dpi).0 as u32
-        } else if let CropMarksSize::Mm(default_size) = DEFAULT_CROP_MARK_SIZE {
-            Mm(match layout.crop_marks_position {
-                CropMarksPosition::Centered => default_size,
-                CropMarksPosition::CardCorners => default_size * 2.0,
+    fn finish(self, file: Vec<u8>) -> JobOutput {
+        JobOutput {
+            file,
+            skipped: self.skipped,
+        }
+    }
+}
+
+/// Card images ready to be placed on the pages, shared by the PDF and the image exports.
+/// The images are kept by the worker.
+struct SheetImages<'a> {
+    layout: Layout,
+    /// One entry per copy, in print order
+    cards: Vec<&'a CommonCard>,
+    /// The cards of each deck, in `cards`
+    deck_ranges: Vec<Range<usize>>,
+    images: HashMap<ImageKey<'a>, ImageId>,
+    backs: HashMap<&'static str, ImageId>,
+}
+
+impl<'a> SheetImages<'a> {
+    /// Builds the list of cards to print, and downloads their images once per unique card,
+    /// even across decks. Cards that can't be downloaded are skipped.
+    async fn download(
+        decks: &[&'a DeckOrPile],
+        db: &CardsDatabase,
+        settings: &ProxySheetSettings,
+        layout: Layout,
+        worker: &mut ImageWorker,
+        job: &mut Job<impl FnMut(JobProgress)>,
+    ) -> Result<Self, Box<dyn Error>> {
+        if layout.cards_per_page == 0 {
+            return Err("Paper size is too small to fit any card with current margins/gap".into());
+        }
+
+        let deck_cards = decks
+            .iter()
+            .map(|deck| printed_cards(deck, db, settings))
+            .collect_vec();
+        let cards = deck_cards.iter().flatten().copied().collect_vec();
+
+        // Download images once per unique printing, shared by every copy
+        let mut printings = HashSet::with_capacity(cards.len());
+        let (unique_cards, text_cards): (Vec<_>, Vec<_>) = cards
+            .iter()
+            .copied()
+            .filter(|c| printings.insert((&c.card_number, c.illustration_idx)))
+            .map(|c| {
+                (
+                    c,
+                    c.image_path(db, settings.card_lang, ImageOptions::proxy_print()),
+                )
+            })
+            .partition(|(_, img_path)| img_path.is_some());
+        let backs = if settings.duplex {
+            vec![card_back(CardType::Main), card_back(CardType::Cheer)]
+        } else {
+            vec![]
+        };
+        job.add_steps(unique_cards.len() + text_cards.len() + backs.len());
+        let mut images = HashMap::with_capacity(printings.len());
+        worker.start(settings, &layout).await?;
+        let card_size = card_size_px(settings, &layout);
+        // the layers are drawn by the page, with the canvas
+        let watermark = match settings.watermark.layer(card_size, layout.dpi)? {
+            Some(layer) => Some(worker.add_pixels(layer).await?),
+            None => None,
+        };
+
+        // Cards without an image, printed with their text
+        for (card, _) in text_cards {
+            let image =
+                text_proxy::render(card.card_info(db), &card.card_number, card_size, layout.dpi)?;
+            let id = worker.add_pixels(image).await?;
+            worker.process_card(id, None, watermark).await?;
+            images.insert((&card.card_number, card.illustration_idx), id);
+            job.step();
+        }
+
+        // Previous exports are reused, at the same card size
+        let cache = ImageCache::open().await.ok();
+
+        let unique_cards = unique_cards
+            .into_iter()
+            .filter_map(|(card, img_path)| Some((card, img_path?)));
+        let cache_ref = cache.as_ref();
+        let mut downloads = stream::iter(unique_cards)
+            .map(|(card, img_path)| async move {
+                let bytes = fetch_card_image(&img_path, card_size, cache_ref).await;
+                (card, img_path, bytes)
+            })
+            .buffer_unordered(MAX_CONCURRENT_DOWNLOADS);
+        while let Some((card, img_path, bytes)) = downloads.next().await {
+            match load_card_image(worker, &img_path, card_size, cache_ref, bytes).await {
+                Ok(id) => {
+                    let translation = settings
+                        .translation_overlay
+                        .then(|| card.card_info(db))
+                        .flatten()
+                        // no overlay without an English text
+                        .filter(|card| text_proxy::has_translation(card))
+                        .map(|card| text_proxy::translation(card, card_size, layout.dpi))
+                        .transpose()?;
+                    let translation = match translation {
+                        Some(layer) => Some(worker.add_pixels(layer).await?),
+                        None => None,
+                    };
+                    worker.process_card(id, translation, watermark).await?;
+                    images.insert((&card.card_number, card.illustration_idx), id);
+                }
+                Err(error) => job.skipped.push(SkippedCard {
+                    name: card_label(card, db),
+                    error,
+                }),
+            }
+            job.step();
+        }
+
+        // Card backs, for double-sided printing
+        let origin = window().location().origin().unwrap_or_default();
+        let mut back_images = HashMap::with_capacity(backs.len());
+        for back in backs {
+            let img_path = format!("{origin}/hocg-deck-convert/assets/{back}");
+            let bytes = fetch_card_image(&img_path, card_size, cache_ref).await;
+            match load_card_image(worker, &img_path, card_size, cache_ref, bytes).await {
+                Ok(id) => {
+                    worker.process_card(id, None, None).await?;
+                    back_images.insert(back, id);
+                }
+                Err(error) => job.skipped.push(SkippedCard {
+                    name: format!("Card back ({back})"),
+                    error,
+                }),
+            }
+            job.step();
+        }
+
+        if let Some(cache) = &cache {
+            let _ = cache.evict().await;
+        }
+
+        // Leave no holes in the pages
+        let mut cards = Vec::with_capacity(cards.len());
+        let mut deck_ranges = Vec::with_capacity(deck_cards.len());
+        for deck_cards in deck_cards {
+            let start = cards.len();
+            cards.extend(
+                deck_cards
+                    .into_iter()
+                    .filter(|c| images.contains_key(&(&c.card_number, c.illustration_idx))),
+            );
+            deck_ranges.push(start..cards.len());
+        }
+        if cards.is_empty() && !job.skipped.is_empty() {
+            return Err("Could not download the card images, please try again.".into());
+        }
+        let backs = back_images;
+
+        Ok(Self {
+            layout,
+            cards,
+            deck_ranges,
+            images,
+            backs,
+        })
+    }
+
+    fn deck_cards(&self, deck_idx: usize) -> &[&'a CommonCard] {
+        &self.cards[self.deck_ranges[deck_idx].clone()]
+    }
+
+    fn card_image(&self, card: &CommonCard) -> Option<ImageId> {
+        self.images
+            .get(&(&card.card_number, card.illustration_idx))
+            .copied()
+    }
+
+    fn back_image(&self, card: &CommonCard, db: &CardsDatabase) -> Option<ImageId> {
+        self.backs
+            .get(card_back(card.card_type(db).unwrap_or(CardType::Main)))
+            .copied()
+    }
+
+    /// Places the cards of a page, with the card backs at the mirrored positions for the back side
+    fn page_plan(
+        &self,
+        page_cards: &[&CommonCard],
+        back_side: bool,
+        db: &CardsDatabase,
+        settings: &ProxySheetSettings,
+        overlay: Option<ImageId>,
+    ) -> PagePlan {
+        let layout = &self.layout;
+        let place = |id, (x, y)| PlacedImage {
+            id,
+            x,
+            y,
+            dpi: layout.dpi,
+        };
+
+        let mut images = page_cards
+            .iter()
+            .enumerate()
+            .filter_map(|(idx_in_page, card)| {
+                if back_side {
+                    let (offset_x, offset_y) = settings.back_offset;
+                    let (tx, ty) = layout.back_image_translate(idx_in_page);
+                    Some(place(
+                        self.back_image(card, db)?,
+                        (tx + offset_x, ty + offset_y),
+                    ))
+                } else {
+                    Some(place(
+                        self.card_image(card)?,
+                        layout.image_translate(idx_in_page),
+                    ))
+                }
             })
-            .into_pt()
-            .into_px(layout.dpi)
-            .0 as u32
+            .collect_vec();
+
+        // Overlay crop marks above everything
+        if let Some(overlay) = overlay.filter(|_| !back_side) {
+            images.push(place(overlay, (Mm(0.0), Mm(0.0))));
+        }
+
+        PagePlan {
+            width: layout.page_width,
+            height: layout.page_height,
+            images,
+        }
+    }
+}
+
+/// Draws the crop marks and registration marks, above the cards
+fn draw_overlay(
+    layout: &Layout,
+    settings: &ProxySheetSettings,
+) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+    let registration_marks =
+        if settings.registration_marks && settings.page_mode == PageMode::Sheets {
+            layout.registration_marks()?
         } else {
-            unreachable!()
+            vec![]
         };
 
-        let mut overlay = ::image::RgbaImage::from_pixel(
-            page_w_px as u32,
-            page_h_px as u32,
-            ::image::Rgba([0, 0, 0, 0]),
+    // Print shops cut single cards by themselves
+    let draw_crop_marks =
+        settings.crop_marks_size != CropMarksSize::None && settings.page_mode == PageMode::Sheets;
+    if !draw_crop_marks && registration_marks.is_empty() {
+        return Ok(None);
+    }
+
+    let page_w_px = layout.page_width.into_pt().into_px(layout.dpi).0;
+    let page_h_px = layout.page_height.into_pt().into_px(layout.dpi).0;
+    let crop_mark_thickness = DEFAULT_CROP_MARK_THICKNESS
+        .into_pt()
+        .into_px(layout.dpi)
+        .0
+        .max(1) as u32;
+    let crop_mark_color = Rgba([0x68, 0x68, 0x68, 0xFF]);
+    let crop_mark_len = if let CropMarksSize::Mm(mark_size) = settings.crop_marks_size {
+        Mm(mark_size).into_pt().into_px(layout.dpi).0 as u32
+    } else if let CropMarksSize::Mm(default_size) = DEFAULT_CROP_MARK_SIZE {
+        Mm(match layout.crop_marks_position {
+            CropMarksPosition::Centered => default_size,
+            CropMarksPosition::CardCorners => default_size * 2.0,
+        })
+        .into_pt()
+        .into_px(layout.dpi)
+        .0 as u32
+    } else {
+        unreachable!()
+    };
+
+    let mut overlay = RgbaImage::from_pixel(page_w_px as u32, page_h_px as u32, Rgba([0, 0, 0, 0]));
+
+    // Draw registration marks for cutting machines
+    for mark in &registration_marks {
+        let x_px = Mm(mark.x).into_pt().into_px(layout.dpi).0;
+        let y_px = Mm(mark.y).into_pt().into_px(layout.dpi).0;
+        let w_px = Mm(mark.width).into_pt().into_px(layout.dpi).0.max(1);
+        let h_px = Mm(mark.height).into_pt().into_px(layout.dpi).0.max(1);
+        draw_filled_rect_mut(
+            &mut overlay,
+            imageproc::rect::Rect::at(x_px as i32, y_px as i32).of_size(w_px as u32, h_px as u32),
+            Rgba([0, 0, 0, 0xFF]),
+        );
+    }
+
+    // Draw crop marks for each card slots
+    let marks_positions = if draw_crop_marks {
+        layout.crop_marks_positions()
+    } else {
+        vec![]
+    };
+    for (tx, ty, flags) in &marks_positions {
+        let x_px = tx.into_pt().into_px(layout.dpi).0;
+        let y_px = ty.into_pt().into_px(layout.dpi).0;
+
+        flags.draw_crop_mark(
+            &mut overlay,
+            x_px as u32,
+            y_px as u32,
+            crop_mark_len,
+            crop_mark_thickness,
+            crop_mark_color,
         );
+    }
+
+    // Draw lines between crop marks for easier cutting with scissors
+    if draw_crop_marks && settings.crop_marks_size == CropMarksSize::FullLength {
+        // Vertical lines
+        let max_col = if layout.centered_crop_marks() {
+            layout.fit_w + 1
+        } else {
+            layout.fit_w * 2
+        };
+        for col in 0..max_col {
+            let start = marks_positions[col];
+            let end = marks_positions[marks_positions.len() - 1 - (max_col - 1 - col)];
+
+            let x1_px = start.0.into_pt().into_px(layout.dpi).0;
+            let y1_px = start.1.into_pt().into_px(layout.dpi).0;
 
-        // Draw crop marks for each card slots
-        let marks_positions = layout.crop_marks_positions();
-        for (tx, ty, flags) in &marks_positions {
-            let x_px = tx.into_pt().into_px(layout.dpi).0;
-            let y_px = ty.into_pt().into_px(layout.dpi).0;
+            let x2_px = end.0.into_pt().into_px(layout.dpi).0;
+            let y2_px = end.1.into_pt().into_px(layout.dpi).0;
 
-            flags.draw_crop_mark(
+            draw_line_thick_mut(
                 &mut overlay,
-                x_px as u32,
-                y_px as u32,
-                crop_mark_len,
+                (x1_px as u32, y1_px as u32),
+                (x2_px as u32, y2_px as u32),
                 crop_mark_thickness,
                 crop_mark_color,
             );
         }
 
-        // Draw lines between crop marks for easier cutting with scissors
-        if settings.crop_marks_size == CropMarksSize::FullLength {
-            // Vertical lines
-            let max_col = if layout.crop_marks_position == CropMarksPosition::Centered
-                || layout.gap <= DEFAULT_CROP_MARK_THICKNESS
-            {
-                layout.fit_w + 1
-            } else if layout.crop_marks_position == CropMarksPosition::CardCorners {
-                layout.fit_w * 2
-            } else {
-                unreachable!()
-            };
-            for col in 0..max_col {
-                let start = marks_positions[col];
-                let end = marks_positions[marks_positions.len() - 1 - (max_col - 1 - col)];
-
-                let x1_px = start.0.into_pt().into_px(layout.dpi).0;
-                let y1_px = start.1.into_pt().into_px(layout.dpi).0;
-
-                let x2_px = end.0.into_pt().into_px(layout.dpi).0;
-                let y2_px = end.1.into_pt().into_px(layout.dpi).0;
-
-                draw_line_thick_mut(
-                    &mut overlay,
-                    (x1_px as u32, y1_px as u32),
-                    (x2_px as u32, y2_px as u32),
-                    crop_mark_thickness,
-                    crop_mark_color,
-                );
-            }
+        // Horizontal lines
+        let max_row = if layout.centered_crop_marks() {
+            layout.fit_h + 1
+        } else {
+            layout.fit_h * 2
+        };
+        for row in 0..max_row {
+            let start = marks_positions[row * max_col];
+            let end = marks_positions[row * max_col + max_col - 1];
 
-            // Horizontal lines
-            let max_row = if layout.crop_marks_position == CropMarksPosition::Centered
-                || layout.gap <= DEFAULT_CROP_MARK_THICKNESS
-            {
-                layout.fit_h + 1
-            } else if layout.crop_marks_position == CropMarksPosition::CardCorners {
-                layout.fit_h * 2
-            } else {
-                unreachable!()
-            };
-            for row in 0..max_row {
-                let start = marks_positions[row * max_col];
-                let end = marks_positions[row * max_col + max_col - 1];
-
-                let x1_px = start.0.into_pt().into_px(layout.dpi).0;
-                let y1_px = start.1.into_pt().into_px(layout.dpi).0;
-
-                let x2_px = end.0.into_pt().into_px(layout.dpi).0;
-                let y2_px = end.1.into_pt().into_px(layout.dpi).0;
-
-                draw_line_thick_mut(
-                    &mut overlay,
-                    (x1_px as u32, y1_px as u32),
-                    (x2_px as u32, y2_px as u32),
-                    crop_mark_thickness,
-                    crop_mark_color,
-                );
-            }
+            let x1_px = start.0.into_pt().into_px(layout.dpi).0;
+            let y1_px = start.1.into_pt().into_px(layout.dpi).0;
+
+            let x2_px = end.0.into_pt().into_px(layout.dpi).0;
+            let y2_px = end.1.into_pt().into_px(layout.dpi).0;
+
+            draw_line_thick_mut(
+                &mut overlay,
+                (x1_px as u32, y1_px as u32),
+                (x2_px as u32, y2_px as u32),
+                crop_mark_thickness,
+                crop_mark_color,
+            );
         }
+    }
 
-        let mut overlay_bytes = Cursor::new(vec![]);
-        ::image::DynamicImage::ImageRgba8(overlay)
-            .write_to(&mut overlay_bytes, ImageFormat::Png)?;
-        let overlay_raw =
-            RawImage::decode_from_bytes_async(&overlay_bytes.into_inner(), &mut vec![]).await?;
-        Some(doc.add_image(&overlay_raw))
-    } else {
-        None
-    };
+    Ok(Some(overlay))
+}
+
+/// Cards of one deck in a batch, and the sheets where they are printed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchDeckSummary {
+    pub name: String,
+    pub oshi: usize,
+    pub main: usize,
+    pub cheers: usize,
+    /// First and last card sheet of the deck, starting at 1. The separator pages are not
+    /// counted, and a duplex sheet counts its back page.
+    pub sheets: (usize, usize),
+}
 
-    // Build pages
-    let pages = (0..pages_count)
-        .map(|page_idx| {
-            // Create operations for our page
-            let mut ops = Vec::new();
+impl BatchDeckSummary {
+    pub fn cards(&self) -> usize {
+        self.oshi + self.main + self.cheers
+    }
 
-            for idx_in_page in 0..layout.cards_per_page {
-                let global_idx = page_idx * layout.cards_per_page + idx_in_page;
-                let Some(card) = cards.get(global_idx) else {
-                    break;
+    /// One summary per deck, with its cards in print order. Without separator pages,
+    /// a sheet can be shared by two decks.
+    fn compute(
+        decks: &[(&str, &[&CommonCard])],
+        db: &CardsDatabase,
+        cards_per_page: usize,
+        separator_pages: bool,
+        duplex: bool,
+    ) -> Vec<Self> {
+        let cards_per_page = cards_per_page.max(1);
+        let pages_per_sheet = if duplex { 2 } else { 1 };
+        let mut printed = 0;
+        let mut last_sheet = 0;
+        decks
+            .iter()
+            .map(|(name, cards)| {
+                let count = |card_type| {
+                    cards
+                        .iter()
+                        .filter(|c| c.card_type(db).unwrap_or(CardType::Main) == card_type)
+                        .count()
                 };
+                let (first, last) = if separator_pages {
+                    let first = last_sheet + 1;
+                    last_sheet += cards.len().div_ceil(cards_per_page);
+                    (first, last_sheet.max(first))
+                } else {
+                    let first = printed / cards_per_page + 1;
+                    printed += cards.len();
+                    (first, printed.div_ceil(cards_per_page).max(first))
+                };
+                // from the front of the first sheet to the back of the last one
+                let sheets = ((first - 1) * pages_per_sheet + 1, last * pages_per_sheet);
+                BatchDeckSummary {
+                    name: name.to_string(),
+                    oshi: count(CardType::Oshi),
+                    main: count(CardType::Main),
+                    cheers: count(CardType::Cheer),
+                    sheets,
+                }
+            })
+            .collect()
+    }
 
-                // Place the image on the page
-                let key = Some((&card.card_number, card.illustration_idx));
-                if let Some(image_id) = image_ids.get(&key) {
-                    let (tx, ty) = layout.card_translate(idx_in_page);
-                    ops.push(Op::UseXobject {
-                        id: image_id.clone(),
-                        transform: XObjectTransform {
-                            dpi: Some(DPI),
-                            translate_x: Some(tx.into()),
-                            translate_y: Some(ty.into()),
-                            ..Default::default()
-                        },
-                    });
+    /// Before the download, for the preview
+    pub fn preview(
+        decks: &[(String, DeckOrPile)],
+        db: &CardsDatabase,
+        settings: &ProxySheetSettings,
+        separator_pages: bool,
+    ) -> Vec<Self> {
+        let cards = decks
+            .iter()
+            .map(|(_, deck)| printed_cards(deck, db, settings))
+            .collect_vec();
+        let decks = decks
+            .iter()
+            .zip(&cards)
+            .map(|((name, _), cards)| (name.as_str(), cards.as_slice()))
+            .collect_vec();
+        Self::compute(
+            &decks,
+            db,
+            settings.layout().cards_per_page,
+            separator_pages,
+            settings.duplex,
+        )
+    }
+
+    fn description(&self) -> String {
+        let mut parts = vec![];
+        if self.oshi > 0 {
+            parts.push(format!("{} oshi", self.oshi));
+        }
+        if self.main > 0 {
+            parts.push(format!("{} main deck", self.main));
+        }
+        if self.cheers > 0 {
+            parts.push(format!("{} cheers", self.cheers));
+        }
+        parts.join(", ")
+    }
+
+    fn sheets_label(&self) -> String {
+        match self.sheets {
+            (first, last) if first == last => format!("Sheet {first}"),
+            (first, last) => format!("Sheets {first}-{last}"),
+        }
+    }
+}
+
+/// The page before the cards of a deck, to sort the printed sheets
+fn separator_page(
+    summary: &BatchDeckSummary,
+    deck_idx: usize,
+    deck_count: usize,
+    width: Mm,
+    height: Mm,
+) -> Result<DynamicImage, Box<dyn Error>> {
+    let page = decklist::Page::new(width, height)?;
+    let (w, h) = (page.width, page.height);
+    let max_width = w * 0.8;
+    page.text_centered(
+        &format!("Deck {} of {deck_count}", deck_idx + 1),
+        (w / 2.0, h * 0.35),
+        w * 0.04,
+        false,
+        "#555",
+        max_width,
+    );
+    page.text_centered(
+        &summary.name,
+        (w / 2.0, h * 0.42),
+        w * 0.07,
+        true,
+        "black",
+        max_width,
+    );
+    page.text_centered(
+        &format!("{} cards", summary.cards()),
+        (w / 2.0, h * 0.5),
+        w * 0.045,
+        false,
+        "black",
+        max_width,
+    );
+    page.text_centered(
+        &summary.description(),
+        (w / 2.0, h * 0.56),
+        w * 0.035,
+        false,
+        "#555",
+        max_width,
+    );
+    page.text_centered(
+        &summary.sheets_label(),
+        (w / 2.0, h * 0.62),
+        w * 0.035,
+        false,
+        "#555",
+        max_width,
+    );
+    page.to_image()
+}
+
+pub async fn generate_pdf(
+    deck: &DeckOrPile,
+    db: &CardsDatabase,
+    settings: ProxySheetSettings,
+    on_progress: impl FnMut(JobProgress),
+) -> Result<JobOutput, Box<dyn Error>> {
+    let title = format!("Proxy sheets for {}", deck.required_deck_name(db));
+    proxy_pdf(&title, &[deck], None, db, settings, on_progress).await
+}
+
+/// Several decks in one file, with their common cards downloaded once. The cards fill the
+/// sheets across the decks, unless each deck starts after a separator page.
+pub async fn generate_batch_pdf(
+    decks: &[(String, DeckOrPile)],
+    db: &CardsDatabase,
+    settings: ProxySheetSettings,
+    separator_pages: bool,
+    on_progress: impl FnMut(JobProgress),
+) -> Result<JobOutput, Box<dyn Error>> {
+    let title = format!("Proxy sheets for {} decks", decks.len());
+    let names = decks.iter().map(|(name, _)| name.as_str()).collect_vec();
+    let decks = decks.iter().map(|(_, deck)| deck).collect_vec();
+    let separators = separator_pages.then_some(names.as_slice());
+    proxy_pdf(&title, &decks, separators, db, settings, on_progress).await
+}
+
+/// `separators` has the deck names, for the separator pages
+async fn proxy_pdf(
+    title: &str,
+    decks: &[&DeckOrPile],
+    separators: Option<&[&str]>,
+    db: &CardsDatabase,
+    settings: ProxySheetSettings,
+    on_progress: impl FnMut(JobProgress),
+) -> Result<JobOutput, Box<dyn Error>> {
+    let mut job = Job::new(on_progress);
+    let mut worker = ImageWorker::new()?;
+    let sheets = SheetImages::download(
+        decks,
+        db,
+        &settings,
+        settings.layout(),
+        &mut worker,
+        &mut job,
+    )
+    .await?;
+    let layout = sheets.layout;
+    let (page_width, page_height) = (layout.page_width, layout.page_height);
+
+    // Optional crop marks overlay
+    let overlay = worker.overlay().await?;
+
+    // Build pages, each followed by its back
+    let card_pages = |cards: &[&CommonCard]| {
+        cards
+            .chunks(layout.cards_per_page)
+            .flat_map(|page_cards| {
+                let mut pages = vec![sheets.page_plan(page_cards, false, db, &settings, overlay)];
+                if settings.duplex {
+                    pages.push(sheets.page_plan(page_cards, true, db, &settings, None));
                 }
-            }
+                pages
+            })
+            .collect::<Vec<_>>()
+    };
+    let pages = match separators {
+        Some(names) => {
+            let decks = names
+                .iter()
+                .enumerate()
+                .map(|(deck_idx, name)| (*name, sheets.deck_cards(deck_idx)))
+                .collect_vec();
+            let summaries =
+                BatchDeckSummary::compute(&decks, db, layout.cards_per_page, true, settings.duplex);
 
-            // Overlay crop marks above everything (optional)
-            if let Some(oid) = overlay_id.clone() {
-                ops.push(Op::UseXobject {
-                    id: oid,
-                    transform: XObjectTransform {
-                        dpi: Some(DPI),
-                        translate_x: Some(Mm(0.0).into()),
-                        translate_y: Some(Mm(0.0).into()),
-                        ..Default::default()
-                    },
+            let mut pages = Vec::new();
+            for (deck_idx, summary) in summaries.iter().enumerate() {
+                let separator =
+                    separator_page(summary, deck_idx, summaries.len(), page_width, page_height)?;
+                let separator_id = worker.add_pixels(separator.into_rgba8()).await?;
+                pages.push(PagePlan {
+                    width: page_width,
+                    height: page_height,
+                    images: vec![PlacedImage {
+                        id: separator_id,
+                        x: Mm(0.0),
+                        y: Mm(0.0),
+                        dpi: decklist::DPI,
+                    }],
                 });
+                // keep the fronts and backs together
+                if settings.duplex {
+                    pages.push(PagePlan {
+                        width: page_width,
+                        height: page_height,
+                        images: vec![],
+                    });
+                }
+                pages.extend(card_pages(sheets.deck_cards(deck_idx)));
             }
+            pages
+        }
+        None => card_pages(&sheets.cards),
+    };
 
-            // Create a page with our operations
-            PdfPage::new(page_width, page_height, ops)
-        })
-        .collect::<Vec<_>>();
-
-    Ok(doc.with_pages(pages).save(
-        &PdfSaveOptions {
-            image_optimization: Some(ImageOptimizationOptions {
-                // Don't resize, will lose image quality
-                max_image_size: None,
-                ..Default::default()
-            }),
-            ..Default::default()
-        },
-        &mut Vec::new(),
-    ))
+    // One step per image added to the document
+    job.add_steps(
+        pages
+            .iter()
+            .flat_map(|page| &page.images)
+            .map(|image| image.id)
+            .unique()
+            .count(),
+    );
+    let file = worker
+        .pdf(title, pages, settings.pdf_image_format, || job.step())
+        .await?;
+    Ok(job.finish(file))
 }
 
-#[component]
-pub fn Export(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
-    #[derive(Serialize)]
-    struct EventData {
-        format: &'static str,
-        language: CardLanguage,
-        missing_proxies: bool,
-        paper_size: PaperSize,
-        include_cheers: bool,
-        default_settings: bool,
-        crop_marks_size: CropMarksSize,
-        crop_marks_position: CropMarksPosition,
-        card_size: CardSize,
-        gap: Mm,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        error: Option<String>,
+/// Printer test page with rulers, test squares and a card outline, to measure the corrections
+pub async fn generate_calibration_pdf(
+    settings: ProxySheetSettings,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let setup = settings.page_setup;
+    let (page_width, page_height) = setup.paper_dimensions();
+    let (card_w, card_h) = setup.card_dimensions(settings.card_size);
+    let Margins {
+        top,
+        right,
+        bottom,
+        left,
+    } = setup.margins;
+    let (w, h, s) = (page_width.0, page_height.0, setup.scale);
+    if w - left - right < CALIBRATION_TEST_SQUARE.0 || h - top - bottom < CALIBRATION_RULER.0 {
+        return Err("Paper size is too small for the calibration page".into());
     }
-    impl TrackEvent for EventData {
-        fn key(&self, event_name: &str) -> String {
-            let data_str = serde_json::to_string(&(
-                &self.format,
-                &self.language,
-                &self.missing_proxies,
-                &self.paper_size,
-                &self.include_cheers,
-                &self.default_settings,
-                &self.error,
-            ))
-            .unwrap_or_default();
-            format!("{event_name}:{data_str}")
+
+    let px = |mm: f32| mm_to_px(Mm(mm), DPI) as i32;
+    let black = Rgba([0, 0, 0, 0xFF]);
+    let gray = Rgba([0x68, 0x68, 0x68, 0xFF]);
+    let thickness = DEFAULT_CROP_MARK_THICKNESS.0;
+
+    let fill = |page: &mut RgbaImage, (x, y): (f32, f32), (w, h): (f32, f32), color| {
+        draw_filled_rect_mut(
+            page,
+            imageproc::rect::Rect::at(px(x), px(y))
+                .of_size(px(w).max(1) as u32, px(h).max(1) as u32),
+            color,
+        );
+    };
+    let outline = |page: &mut RgbaImage, (x, y): (f32, f32), (w, h): (f32, f32), color| {
+        fill(page, (x, y), (w, thickness), color);
+        fill(page, (x, y + h - thickness), (w, thickness), color);
+        fill(page, (x, y), (thickness, h), color);
+        fill(page, (x + w - thickness, y), (thickness, h), color);
+    };
+    // the labels are drawn with the canvas, then placed over the page
+    let labels = decklist::Page::with_dpi(page_width, page_height, DPI, None)?;
+    let text = |text: &str, (x, y): (f32, f32), height: f32| {
+        let y = (y + height / 2.0) as f64;
+        labels.text(text, (x as f64, y), height as f64 * 1.4, false, "black");
+    };
+
+    let mut page = RgbaImage::from_pixel(px(w) as u32, px(h) as u32, Rgba([0, 0, 0, 0]));
+
+    // Printable area, the squares must be complete
+    outline(
+        &mut page,
+        (left, top),
+        (w - left - right, h - top - bottom),
+        gray,
+    );
+    let sq = CALIBRATION_SQUARE.0;
+    for pos in [
+        (left, top),
+        (w - right - sq, top),
+        (left, h - bottom - sq),
+        (w - right - sq, h - bottom - sq),
+    ] {
+        fill(&mut page, pos, (sq, sq), black);
+    }
+
+    let (x0, y0) = (left + 15.0, top + 15.0);
+    text("Calibration page", (x0, y0), 5.0);
+    text("Print at actual size", (x0, y0 + 8.0), 3.0);
+    text(
+        &format!("Scale {s:.3}, margins {top} {right} {bottom} {left} mm"),
+        (x0, y0 + 13.0),
+        3.0,
+    );
+
+    // Rulers, in mm
+    let ruler = CALIBRATION_RULER.0;
+    let (rx, ry) = (x0 + 5.0, y0 + 30.0);
+    let (vx, vy) = (x0 + 5.0, y0 + 42.0);
+    fill(&mut page, (rx, ry), (ruler * s, thickness), black);
+    fill(&mut page, (vx, vy), (thickness, ruler * s), black);
+    for mm in 0..=ruler as u32 {
+        let len = match mm {
+            mm if mm % 10 == 0 => 5.0,
+            mm if mm % 5 == 0 => 3.5,
+            _ => 2.0,
+        };
+        let offset = mm as f32 * s - thickness / 2.0;
+        fill(&mut page, (rx + offset, ry), (thickness, len), black);
+        fill(&mut page, (vx, vy + offset), (len, thickness), black);
+        if mm % 10 == 0 {
+            text(&mm.to_string(), (rx + offset - 1.0, ry - 4.0), 2.5);
+        }
+    }
+    text(
+        &format!("{ruler} mm"),
+        (rx + ruler * s + 3.0, ry - 1.0),
+        3.0,
+    );
+
+    // Card outline and test square, placed next to the vertical ruler
+    let (cx, cy) = (vx + 10.0, vy);
+    outline(&mut page, (cx, cy), (card_w.0, card_h.0), black);
+    let (card_w_label, card_h_label) = settings.card_size.dimensions();
+    text(
+        &format!("Card {} x {} mm", card_w_label.0, card_h_label.0),
+        (cx, cy + card_h.0 + 3.0),
+        3.0,
+    );
+    let test_sq = CALIBRATION_TEST_SQUARE.0 * s;
+    let (tx, ty) = (cx + card_w.0 + 10.0, cy);
+    outline(&mut page, (tx, ty), (test_sq, test_sq), black);
+    text(
+        &format!("{} mm", CALIBRATION_TEST_SQUARE.0),
+        (tx, ty + test_sq + 3.0),
+        3.0,
+    );
+    ::image::imageops::overlay(&mut page, &labels.to_image()?.into_rgba8(), 0, 0);
+
+    let mut worker = ImageWorker::new()?;
+    let page_id = worker.add_pixels(page).await?;
+    let pages = vec![PagePlan {
+        width: page_width,
+        height: page_height,
+        images: vec![PlacedImage {
+            id: page_id,
+            x: Mm(0.0),
+            y: Mm(0.0),
+            dpi: DPI,
+        }],
+    }];
+    // lossless, the page is measured after the print
+    Ok(worker
+        .pdf(
+            "Proxy sheets calibration page",
+            pages,
+            PdfImageFormat::Png,
+            || {},
+        )
+        .await?)
+}
+
+/// Images packaged as a ZIP, for services that only print images
+pub async fn generate_images(
+    deck: &DeckOrPile,
+    db: &CardsDatabase,
+    settings: ProxySheetSettings,
+    export: ImageExport,
+    format: RasterFormat,
+    dpi: f32,
+    on_progress: impl FnMut(JobProgress),
+) -> Result<JobOutput, Box<dyn Error>> {
+    let mut job = Job::new(on_progress);
+    let mut worker = ImageWorker::new()?;
+    let ext = format.extension();
+
+    let files = match export {
+        ImageExport::Pages => {
+            let layout = settings.layout_at(dpi);
+            let sheets =
+                SheetImages::download(&[deck], db, &settings, layout, &mut worker, &mut job)
+                    .await?;
+            let overlay = worker.overlay().await?;
+
+            let page = |page_cards: &[&CommonCard], back_side, overlay| ZipImage::Page {
+                page: sheets.page_plan(page_cards, back_side, db, &settings, overlay),
+                dpi: layout.dpi,
+            };
+            let mut files = vec![];
+            for (page_idx, page_cards) in sheets.cards.chunks(layout.cards_per_page).enumerate() {
+                let page_no = page_idx + 1;
+                files.push((
+                    format!("page_{page_no:02}.{ext}"),
+                    page(page_cards, false, overlay),
+                ));
+                if settings.duplex {
+                    files.push((
+                        format!("page_{page_no:02}_back.{ext}"),
+                        page(page_cards, true, None),
+                    ));
+                }
+            }
+            files
+        }
+        ImageExport::Cards => {
+            let (card_w, card_h) = settings.page_setup.card_dimensions(settings.card_size);
+            let layout = Layout::single_card(dpi, card_w, card_h, settings.bleed);
+            let settings = ProxySheetSettings {
+                duplex: false,
+                ..settings
+            };
+            let sheets =
+                SheetImages::download(&[deck], db, &settings, layout, &mut worker, &mut job)
+                    .await?;
+
+            sheets
+                .cards
+                .iter()
+                .unique()
+                .filter_map(|card| {
+                    Some((
+                        format!("{}.{ext}", card.card_number),
+                        ZipImage::Image(sheets.card_image(card)?),
+                    ))
+                })
+                .collect()
+        }
+    };
+
+    // The images are rendered and encoded by the worker
+    job.add_steps(files.len());
+    let file = worker.zip(files, format, || job.step()).await?;
+    Ok(job.finish(file))
+}
+
+#[component]
+pub fn Export(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) -> Element {
+    #[derive(Serialize)]
+    struct EventData {
+        format: &'static str,
+        language: CardLanguage,
+        missing_proxies: bool,
+        paper_size: PaperSize,
+        include_cheers: bool,
+        default_settings: bool,
+        crop_marks_size: CropMarksSize,
+        crop_marks_position: CropMarksPosition,
+        card_size: CardSize,
+        gap: Mm,
+        bleed: Mm,
+        bleed_mode: BleedMode,
+        page_mode: PageMode,
+        duplex: bool,
+        back_offset: (Mm, Mm),
+        registration_marks: bool,
+        ink_saver: InkSaver,
+        watermark: Watermark,
+        custom_margins: bool,
+        scale: f32,
+        pdf_dpi: f32,
+        pdf_image_format: PdfImageFormat,
+        text_proxies: bool,
+        translation_overlay: bool,
+        card_selection: bool,
+        print_order: PrintOrder,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+    impl EventData {
+        fn new(
+            settings: &ProxySheetSettings,
+            missing_proxies: bool,
+            card_selection: bool,
+            print_order: PrintOrder,
+            error: Option<String>,
+        ) -> Self {
+            EventData {
+                format: "Proxy sheets",
+                language: settings.card_lang,
+                missing_proxies,
+                paper_size: settings.page_setup.paper_size,
+                include_cheers: settings.include_cheers,
+                default_settings: settings.changed_count() == 0,
+                crop_marks_size: settings.crop_marks_size,
+                crop_marks_position: settings.crop_marks_position,
+                card_size: settings.card_size,
+                gap: settings.gap,
+                bleed: settings.bleed,
+                bleed_mode: settings.bleed_mode,
+                page_mode: settings.page_mode,
+                duplex: settings.duplex,
+                back_offset: settings.back_offset,
+                registration_marks: settings.registration_marks,
+                ink_saver: settings.ink_saver,
+                watermark: settings.watermark,
+                custom_margins: settings.page_setup.margins != Margins::default(),
+                scale: settings.page_setup.scale,
+                pdf_dpi: settings.pdf_dpi,
+                pdf_image_format: settings.pdf_image_format,
+                text_proxies: settings.text_proxies,
+                translation_overlay: settings.translation_overlay,
+                card_selection,
+                print_order,
+                error,
+            }
+        }
+    }
+    impl TrackEvent for EventData {
+        fn key(&self, event_name: &str) -> String {
+            let data_str = serde_json::to_string(&(
+                &self.format,
+                &self.language,
+                &self.missing_proxies,
+                &self.paper_size,
+                &self.include_cheers,
+                &self.page_mode,
+                &self.duplex,
+                &self.default_settings,
+                &self.error,
+            ))
+            .unwrap_or_default();
+            format!("{event_name}:{data_str}")
+        }
+    }
+
+    let mut deck_error = use_signal(String::new);
+    let card_lang = PREVIEW_CARD_LANG.signal();
+    let mut options = use_signal(|| ProxySheetSettings::with_defaults(*PREVIEW_CARD_LANG.peek()));
+    let page_setup = use_memo(move || options.read().page_setup);
+    let quality = use_memo(move || {
+        let options = options.read();
+        QualityPreset::find(options.pdf_dpi, options.pdf_image_format)
+    });
+    let mut loading = use_signal(|| false);
+    let mut pdf_progress = use_signal(|| None::<JobProgress>);
+    let mut skipped_cards = use_signal(Vec::<SkippedCard>::new);
+    let mut show_advanced = use_signal(|| false);
+    let mut print_selection = use_signal(PrintSelection::default);
+    let mut print_order = use_signal(|| PrintOrder::Deck);
+
+    // keep the printer settings for the next time
+    use_effect(move || {
+        page_setup.read().store();
+    });
+
+    // a new deck is printed whole
+    use_effect(move || {
+        let _ = common_deck.read();
+        print_selection.set(PrintSelection::default());
+    });
+
+    let printed_deck = use_memo(move || {
+        print_selection
+            .read()
+            .apply(&common_deck.read(), *print_order.read(), &db.read())
+    });
+
+    let settings_count = use_memo(move || options.read().changed_count());
+
+    let settings = use_memo(move || {
+        let options = *options.read();
+        ProxySheetSettings {
+            card_lang: *card_lang.read(),
+            // only the Japanese cards need a translation
+            translation_overlay: options.translation_overlay
+                && *card_lang.read() == CardLanguage::Japanese,
+            ..options
+        }
+    });
+
+    let estimated_size = use_memo(move || {
+        settings
+            .read()
+            .estimated_pdf_size(&printed_deck.read(), &db.read())
+    });
+
+    let print_deck = move |_| async move {
+        let card_selection = !print_selection.read().is_whole_deck(&common_deck.read());
+        // the page stays responsive during the job, don't hold the deck
+        let common_deck = printed_deck.read().clone();
+
+        *loading.write() = true;
+        *deck_error.write() = String::new();
+        skipped_cards.write().clear();
+
+        let settings = *settings.read();
+        let file_name = settings.file_name(&common_deck, &db.read());
+
+        let missing_proxies = has_missing_proxies(&common_deck, &db.read(), *card_lang.read());
+
+        let on_progress = move |progress| *pdf_progress.write() = Some(progress);
+        let result = generate_pdf(&common_deck, &db.read(), settings, on_progress).await;
+        *pdf_progress.write() = None;
+        let error = match result {
+            Ok(output) => {
+                download_file(&file_name, &output.file[..]);
+                *skipped_cards.write() = output.skipped;
+                None
+            }
+            Err(e) => {
+                *deck_error.write() = e.to_string();
+                Some(e.to_string())
+            }
+        };
+
+        track_event(
+            EventType::Export("Proxy sheets".into()),
+            EventData::new(
+                &settings,
+                missing_proxies,
+                card_selection,
+                *print_order.read(),
+                error,
+            ),
+        );
+
+        *loading.write() = false;
+    };
+
+    rsx! {
+        DeckValidation {
+            deck_check: true,
+            proxy_check: true,
+            allow_unreleased: true,
+            allow_pile: true,
+            card_lang,
+            db,
+            common_deck,
+        }
+
+        div { class: "block",
+            div { class: "grid is-col-min-8",
+                // Card language
+                div { class: "cell",
+                    label { "for": "card_language", class: "label", "Card language" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "card_language",
+                                oninput: move |ev| {
+                                    *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
+                                        "jp" => CardLanguage::Japanese,
+                                        "en" => CardLanguage::English,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::English,
+                                    value: "en",
+                                    "English"
+                                }
+                                option {
+                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::Japanese,
+                                    value: "jp",
+                                    "Japanese"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Translation overlay
+                if *card_lang.read() == CardLanguage::Japanese {
+                    div { class: "cell",
+                        label { "for": "translation_overlay", class: "label", "English translation" }
+                        div { class: "control",
+                            div { class: "select",
+                                select {
+                                    id: "translation_overlay",
+                                    oninput: move |ev| {
+                                        options.write().translation_overlay = match ev.value().as_str() {
+                                            "none" => false,
+                                            "overlay" => true,
+                                            _ => unreachable!(),
+                                        };
+                                    },
+                                    option {
+                                        selected: !options.read().translation_overlay,
+                                        value: "none",
+                                        "None"
+                                    }
+                                    option {
+                                        selected: options.read().translation_overlay,
+                                        value: "overlay",
+                                        "Over the card text"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Include cheers
+                div { class: "cell",
+                    label { "for": "include_cheers", class: "label", "Include cheers" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "include_cheers",
+                                oninput: move |ev| {
+                                    options.write().include_cheers = match ev.value().as_str() {
+                                        "no" => false,
+                                        "yes" => true,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: !options.read().include_cheers,
+                                    value: "no",
+                                    "No"
+                                }
+                                option {
+                                    selected: options.read().include_cheers,
+                                    value: "yes",
+                                    "Yes"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Print order
+                div { class: "cell",
+                    label { "for": "print_order", class: "label", "Card order" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "print_order",
+                                oninput: move |ev| {
+                                    *print_order.write() = match ev.value().as_str() {
+                                        "deck" => PrintOrder::Deck,
+                                        "set" => PrintOrder::Set,
+                                        "sorting" => PrintOrder::Sorting,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: *print_order.read() == PrintOrder::Deck,
+                                    value: "deck",
+                                    "Deck order"
+                                }
+                                option {
+                                    selected: *print_order.read() == PrintOrder::Set,
+                                    value: "set",
+                                    "Grouped by set"
+                                }
+                                option {
+                                    selected: *print_order.read() == PrintOrder::Sorting,
+                                    value: "sorting",
+                                    "Grouped by card type"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Page mode
+                div { class: "cell",
+                    label { "for": "page_mode", class: "label", "Layout" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "page_mode",
+                                oninput: move |ev| {
+                                    options.write().page_mode = match ev.value().as_str() {
+                                        "sheets" => PageMode::Sheets,
+                                        "single_card" => PageMode::SingleCard,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: options.read().page_mode == PageMode::Sheets,
+                                    value: "sheets",
+                                    "Sheets"
+                                }
+                                option {
+                                    selected: options.read().page_mode == PageMode::SingleCard,
+                                    value: "single_card",
+                                    "One card per page (print shop)"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Card backs
+                div { class: "cell",
+                    label { "for": "duplex", class: "label", "Card backs" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "duplex",
+                                oninput: move |ev| {
+                                    options.write().duplex = match ev.value().as_str() {
+                                        "no" => false,
+                                        "yes" => true,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: !options.read().duplex,
+                                    value: "no",
+                                    "No"
+                                }
+                                option {
+                                    selected: options.read().duplex,
+                                    value: "yes",
+                                    "Yes (double-sided)"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Paper size
+                div { class: "cell",
+                    label { "for": "paper_size", class: "label", "Paper size" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "paper_size",
+                                disabled: options.read().page_mode == PageMode::SingleCard,
+                                oninput: move |ev| {
+                                    options.write().page_setup.paper_size = match ev.value().as_str() {
+                                        "a4" => PaperSize::A4,
+                                        "letter" => PaperSize::Letter,
+                                        "legal" => PaperSize::Legal,
+                                        "custom" => PaperSize::Custom,
+                                        _ => unreachable!(),
+                                    };
+                                },
+                                option {
+                                    selected: page_setup.read().paper_size == PaperSize::A4,
+                                    value: "a4",
+                                    "A4 (210x297 mm)"
+                                }
+                                option {
+                                    selected: page_setup.read().paper_size == PaperSize::Letter,
+                                    value: "letter",
+                                    "Letter (8.5x11 in)"
+                                }
+                                option {
+                                    selected: page_setup.read().paper_size == PaperSize::Legal,
+                                    value: "legal",
+                                    "Legal (8.5x14 in)"
+                                }
+                                option {
+                                    selected: page_setup.read().paper_size == PaperSize::Custom,
+                                    value: "custom",
+                                    "Custom"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Custom paper size
+                if page_setup.read().paper_size == PaperSize::Custom {
+                    div { class: "cell",
+                        label { "for": "custom_paper_width", class: "label", "Paper width x height (mm)" }
+                        div { class: "field has-addons",
+                            div { class: "control",
+                                input {
+                                    id: "custom_paper_width",
+                                    r#type: "number",
+                                    class: "input",
+                                    style: "width: 6em;",
+                                    min: "50",
+                                    max: "1000",
+                                    step: "0.1",
+                                    value: page_setup.read().custom_paper.0.to_string(),
+                                    oninput: move |ev| {
+                                        if let Ok(val) = ev.value().parse::<f32>() {
+                                            options.write().page_setup.custom_paper.0 = val.clamp(50.0, 1000.0);
+                                        }
+                                    },
+                                }
+                            }
+                            div { class: "control",
+                                input {
+                                    id: "custom_paper_height",
+                                    r#type: "number",
+                                    class: "input",
+                                    style: "width: 6em;",
+                                    min: "50",
+                                    max: "1000",
+                                    step: "0.1",
+                                    value: page_setup.read().custom_paper.1.to_string(),
+                                    oninput: move |ev| {
+                                        if let Ok(val) = ev.value().parse::<f32>() {
+                                            options.write().page_setup.custom_paper.1 = val.clamp(50.0, 1000.0);
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Quality
+                div { class: "cell",
+                    label { "for": "pdf_quality", class: "label", "Quality" }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                id: "pdf_quality",
+                                oninput: move |ev| {
+                                    if let Some(preset) = QualityPreset::ALL
+                                        .into_iter()
+                                        .find(|p| format!("{p:?}") == ev.value())
+                                    {
+                                        let mut options = options.write();
+                                        options.pdf_dpi = preset.dpi();
+                                        options.pdf_image_format = preset.image_format();
+                                    }
+                                },
+                                for preset in QualityPreset::ALL {
+                                    option {
+                                        selected: *quality.read() == Some(preset),
+                                        value: "{preset:?}",
+                                        {preset.name()}
+                                    }
+                                }
+                                if quality.read().is_none() {
+                                    option { selected: true, value: "custom", "Custom" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        CardSelection { common_deck, db, selection: print_selection }
+
+        // Advanced settings
+        div { class: if *show_advanced.read() { "field" } else { "block" },
+            a {
+                href: "#",
+                role: "button",
+                onclick: move |evt| {
+                    evt.prevent_default();
+                    let show = *show_advanced.read();
+                    *show_advanced.write() = !show;
+                },
+                span { class: "icon",
+                    i {
+                        class: "fa-solid",
+                        class: if *show_advanced.read() { "fa-chevron-down" } else { "fa-chevron-right" },
+                    }
+                }
+                "Advanced settings"
+                if *settings_count.read() > 0 {
+                    " ({settings_count})"
+                }
+            }
+        }
+
+        if *show_advanced.read() {
+            div { class: "block",
+                div { class: "grid is-col-min-8",
+                    PageSettings { options }
+                    ImageSettings { options }
+                }
+                Calibration { options }
+                ImageCacheSettings {}
+            }
+        }
+
+        div { class: "field",
+            div { class: "control",
+                button {
+                    r#type: "button",
+                    class: "button",
+                    class: if *loading.read() { "is-loading" },
+                    disabled: printed_deck.read().is_empty() || *loading.read(),
+                    onclick: print_deck,
+                    span { class: "icon",
+                        i { class: "fa-solid fa-print" }
+                    }
+                    span { "Print deck to PDF" }
+                }
+            }
+            if !printed_deck.read().is_empty() {
+                p { class: "help",
+                    "Estimated size: about {format_size(*estimated_size.read())}"
+                }
+            }
+            p { class: "help is-danger", "{deck_error}" }
+            JobStatus { progress: pdf_progress, skipped_cards }
+        }
+
+        BatchPrint { common_deck, db, settings }
+
+        CutFile { common_deck, db, settings }
+
+        ImagesExport { printed_deck, db, settings }
+    }
+}
+
+/// Crop marks, card placement and back alignment
+#[component]
+fn PageSettings(mut options: Signal<ProxySheetSettings>) -> Element {
+    let single_card = use_memo(move || options.read().page_mode == PageMode::SingleCard);
+
+    rsx! {
+        // Crop marks
+        div { class: "cell",
+            label { "for": "include_crop_marks", class: "label", "Crop marks" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "include_crop_marks",
+                        disabled: *single_card.read(),
+                        oninput: move |ev| {
+                            options.write().crop_marks_size = match (
+                                ev.value().as_str(),
+                                ev.value().parse::<f32>(),
+                            ) {
+                                ("none", _) => CropMarksSize::None,
+                                ("full", _) => CropMarksSize::FullLength,
+                                (_, Ok(val)) => CropMarksSize::Mm(val),
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().crop_marks_size == CropMarksSize::None,
+                            value: "none",
+                            "None"
+                        }
+                        for size in [3.0, 5.0, 10.0, 20.0, 30.0] {
+                            option {
+                                selected: options.read().crop_marks_size == CropMarksSize::Mm(size),
+                                value: "{size}",
+                                "{size}mm"
+                            }
+                        }
+                        option {
+                            selected: options.read().crop_marks_size == CropMarksSize::FullLength,
+                            value: "full",
+                            "Full length"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Crop marks position
+        div { class: "cell",
+            label { "for": "crop_marks_position", class: "label", "Crop marks position" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "crop_marks_position",
+                        disabled: options.read().crop_marks_size == CropMarksSize::None || *single_card.read()
+                            || options.read().bleed > Mm(0.0),
+                        oninput: move |ev| {
+                            options.write().crop_marks_position = match ev.value().as_str() {
+                                "centered" => CropMarksPosition::Centered,
+                                "card_corners" => CropMarksPosition::CardCorners,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().crop_marks_position == CropMarksPosition::Centered,
+                            value: "centered",
+                            "Centered"
+                        }
+                        option {
+                            selected: options.read().crop_marks_position == CropMarksPosition::CardCorners,
+                            value: "card_corners",
+                            "Card corners"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Card size
+        div { class: "cell",
+            label { "for": "card_size", class: "label", "Card size" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "card_size",
+                        oninput: move |ev| {
+                            options.write().card_size = match ev.value().as_str() {
+                                "metric" => CardSize::Metric,
+                                "imperial" => CardSize::Imperial,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().card_size == CardSize::Metric,
+                            value: "metric",
+                            "Metric (63x88 mm)"
+                        }
+                        option {
+                            selected: options.read().card_size == CardSize::Imperial,
+                            value: "imperial",
+                            "Imperial (2.5x3.5 in)"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Gap
+        div { class: "cell",
+            label { "for": "gap", class: "label", "Gap between cards (mm)" }
+            div { class: "control",
+                input {
+                    id: "gap",
+                    r#type: "number",
+                    class: "input",
+                    style: "width: auto;",
+                    disabled: *single_card.read(),
+                    min: "0",
+                    max: "10",
+                    step: "0.5",
+                    maxlength: "4",
+                    value: options.read().gap.0.to_string(),
+                    oninput: move |ev| {
+                        if let Ok(val) = ev.value().parse::<f32>() {
+                            options.write().gap = Mm(val.max(0.0));
+                        }
+                    },
+                }
+            }
+        }
+
+        // Bleed
+        div { class: "cell",
+            label { "for": "bleed", class: "label", "Bleed" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "bleed",
+                        oninput: move |ev| {
+                            if let Ok(val) = ev.value().parse::<f32>() {
+                                options.write().bleed = Mm(val);
+                            }
+                        },
+                        option {
+                            selected: options.read().bleed == Mm(0.0),
+                            value: "0",
+                            "None"
+                        }
+                        option {
+                            selected: options.read().bleed == Mm(2.0),
+                            value: "2",
+                            "2mm"
+                        }
+                        option {
+                            selected: options.read().bleed == Mm(3.0),
+                            value: "3",
+                            "3mm"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Bleed mode
+        div { class: "cell",
+            label { "for": "bleed_mode", class: "label", "Bleed fill" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "bleed_mode",
+                        disabled: options.read().bleed <= Mm(0.0),
+                        oninput: move |ev| {
+                            options.write().bleed_mode = match ev.value().as_str() {
+                                "mirror" => BleedMode::Mirror,
+                                "replicate" => BleedMode::Replicate,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().bleed_mode == BleedMode::Mirror,
+                            value: "mirror",
+                            "Mirror edges"
+                        }
+                        option {
+                            selected: options.read().bleed_mode == BleedMode::Replicate,
+                            value: "replicate",
+                            "Repeat edge pixels"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Margins
+        div { class: "cell",
+            label { "for": "margin_top", class: "label", "Margins (mm)" }
+            div { class: "field has-addons",
+                for (id , title , side) in [
+                    ("margin_top", "Top", 0),
+                    ("margin_right", "Right", 1),
+                    ("margin_bottom", "Bottom", 2),
+                    ("margin_left", "Left", 3),
+                ]
+                {
+                    div { class: "control",
+                        input {
+                            id,
+                            r#type: "number",
+                            class: "input",
+                            style: "width: 4.5em;",
+                            title,
+                            disabled: *single_card.read(),
+                            min: "0",
+                            max: "50",
+                            step: "0.5",
+                            value: {
+                                let margins = options.read().page_setup.margins;
+                                [margins.top, margins.right, margins.bottom, margins.left][side]
+                                    .to_string()
+                            },
+                            oninput: move |ev| {
+                                if let Ok(val) = ev.value().parse::<f32>() {
+                                    let val = val.clamp(0.0, 50.0);
+                                    let margins = &mut options.write().page_setup.margins;
+                                    match side {
+                                        0 => margins.top = val,
+                                        1 => margins.right = val,
+                                        2 => margins.bottom = val,
+                                        _ => margins.left = val,
+                                    }
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+        }
+
+        // Card scale
+        div { class: "cell",
+            label { "for": "card_scale", class: "label", "Card scale (%)" }
+            div { class: "control",
+                input {
+                    id: "card_scale",
+                    r#type: "number",
+                    class: "input",
+                    style: "width: auto;",
+                    min: "90",
+                    max: "110",
+                    step: "0.1",
+                    value: ((options.read().page_setup.scale * 1000.0).round() / 10.0).to_string(),
+                    oninput: move |ev| {
+                        if let Ok(val) = ev.value().parse::<f32>() {
+                            options.write().page_setup.scale = (val / 100.0).clamp(0.9, 1.1);
+                        }
+                    },
+                }
+            }
+        }
+
+        // Registration marks
+        div { class: "cell",
+            label { "for": "registration_marks", class: "label", "Registration marks" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "registration_marks",
+                        disabled: *single_card.read(),
+                        oninput: move |ev| {
+                            options.write().registration_marks = match ev.value().as_str() {
+                                "no" => false,
+                                "yes" => true,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: !options.read().registration_marks,
+                            value: "no",
+                            "No"
+                        }
+                        option {
+                            selected: options.read().registration_marks,
+                            value: "yes",
+                            "Yes"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Back offset
+        div { class: "cell",
+            label { "for": "back_offset_x", class: "label", "Back offset (mm)" }
+            div { class: "field has-addons",
+                div { class: "control",
+                    input {
+                        id: "back_offset_x",
+                        r#type: "number",
+                        class: "input",
+                        style: "width: 5em;",
+                        title: "Horizontal (right is positive)",
+                        disabled: !options.read().duplex,
+                        min: "-10",
+                        max: "10",
+                        step: "0.1",
+                        value: options.read().back_offset.0.0.to_string(),
+                        oninput: move |ev| {
+                            if let Ok(val) = ev.value().parse::<f32>() {
+                                options.write().back_offset.0 = Mm(val.clamp(-10.0, 10.0));
+                            }
+                        },
+                    }
+                }
+                div { class: "control",
+                    input {
+                        id: "back_offset_y",
+                        r#type: "number",
+                        class: "input",
+                        style: "width: 5em;",
+                        title: "Vertical (up is positive)",
+                        disabled: !options.read().duplex,
+                        min: "-10",
+                        max: "10",
+                        step: "0.1",
+                        value: options.read().back_offset.1.0.to_string(),
+                        oninput: move |ev| {
+                            if let Ok(val) = ev.value().parse::<f32>() {
+                                options.write().back_offset.1 = Mm(val.clamp(-10.0, 10.0));
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Resolution and look of the card images
+#[component]
+fn ImageSettings(mut options: Signal<ProxySheetSettings>) -> Element {
+    rsx! {
+        // PDF resolution
+        div { class: "cell",
+            label { "for": "pdf_dpi", class: "label", "PDF resolution" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "pdf_dpi",
+                        oninput: move |ev| {
+                            if let Ok(val) = ev.value().parse::<f32>() {
+                                options.write().pdf_dpi = val;
+                            }
+                        },
+                        for dpi in [150.0, 300.0, 600.0] {
+                            option {
+                                selected: options.read().pdf_dpi == dpi,
+                                value: "{dpi}",
+                                "{dpi} DPI"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // PDF images
+        div { class: "cell",
+            label { "for": "pdf_image_format", class: "label", "PDF images" }
+            div { class: "field has-addons",
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "pdf_image_format",
+                            oninput: move |ev| {
+                                options.write().pdf_image_format = match ev.value().as_str() {
+                                    "png" => PdfImageFormat::Png,
+                                    "jpeg" => PdfImageFormat::Jpeg(JPEG_QUALITY),
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: options.read().pdf_image_format == PdfImageFormat::Png,
+                                value: "png",
+                                "PNG (lossless)"
+                            }
+                            option {
+                                selected: matches!(options.read().pdf_image_format, PdfImageFormat::Jpeg(_)),
+                                value: "jpeg",
+                                "JPEG"
+                            }
+                        }
+                    }
+                }
+                if let PdfImageFormat::Jpeg(jpeg_quality) = options.read().pdf_image_format {
+                    div { class: "control",
+                        input {
+                            id: "pdf_jpeg_quality",
+                            r#type: "number",
+                            class: "input",
+                            style: "width: 5em;",
+                            title: "JPEG quality",
+                            min: "10",
+                            max: "100",
+                            step: "5",
+                            value: jpeg_quality.to_string(),
+                            oninput: move |ev| {
+                                if let Ok(val) = ev.value().parse::<u8>() {
+                                    options.write().pdf_image_format = PdfImageFormat::Jpeg(val.clamp(10, 100));
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+        }
+
+        // Text proxies
+        div { class: "cell",
+            label { "for": "text_proxies", class: "label", "Cards without image" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "text_proxies",
+                        oninput: move |ev| {
+                            options.write().text_proxies = match ev.value().as_str() {
+                                "text" => true,
+                                "skip" => false,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().text_proxies,
+                            value: "text",
+                            "Print the card text"
+                        }
+                        option {
+                            selected: !options.read().text_proxies,
+                            value: "skip",
+                            "Skip"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Ink saver
+        div { class: "cell",
+            label { "for": "ink_saver", class: "label", "Ink saver" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "ink_saver",
+                        oninput: move |ev| {
+                            options.write().ink_saver = match ev.value().as_str() {
+                                "none" => InkSaver::None,
+                                "grayscale" => InkSaver::Grayscale,
+                                "reduced_saturation" => InkSaver::ReducedSaturation,
+                                "outline" => InkSaver::Outline,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().ink_saver == InkSaver::None,
+                            value: "none",
+                            "None"
+                        }
+                        option {
+                            selected: options.read().ink_saver == InkSaver::Grayscale,
+                            value: "grayscale",
+                            "Grayscale"
+                        }
+                        option {
+                            selected: options.read().ink_saver == InkSaver::ReducedSaturation,
+                            value: "reduced_saturation",
+                            "Reduced colors"
+                        }
+                        option {
+                            selected: options.read().ink_saver == InkSaver::Outline,
+                            value: "outline",
+                            "Outline only"
+                        }
+                    }
+                }
+            }
+        }
+
+        // Watermark
+        div { class: "cell",
+            label { "for": "watermark", class: "label", "Proxy watermark" }
+            div { class: "control",
+                div { class: "select",
+                    select {
+                        id: "watermark",
+                        oninput: move |ev| {
+                            options.write().watermark = match ev.value().as_str() {
+                                "none" => Watermark::None,
+                                "diagonal" => Watermark::Diagonal,
+                                "corner_stamp" => Watermark::CornerStamp,
+                                _ => unreachable!(),
+                            };
+                        },
+                        option {
+                            selected: options.read().watermark == Watermark::None,
+                            value: "none",
+                            "None"
+                        }
+                        option {
+                            selected: options.read().watermark == Watermark::Diagonal,
+                            value: "diagonal",
+                            "Diagonal text"
+                        }
+                        option {
+                            selected: options.read().watermark == Watermark::CornerStamp,
+                            value: "corner_stamp",
+                            "Corner stamp"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Prints a ruler to adjust the card scale to the printer
+#[component]
+fn Calibration(mut options: Signal<ProxySheetSettings>) -> Element {
+    #[derive(Serialize)]
+    struct CalibrationEventData {
+        action: &'static str,
+        paper_size: PaperSize,
+        scale: f32,
+    }
+    impl TrackEvent for CalibrationEventData {}
+
+    let mut measured_ruler = use_signal(String::new);
+    let mut calibration_error = use_signal(String::new);
+
+    let download_calibration = move |_| async move {
+        *calibration_error.write() = String::new();
+
+        let settings = *options.read();
+        match generate_calibration_pdf(settings).await {
+            Ok(file) => download_file(&settings.calibration_file_name(), &file[..]),
+            Err(e) => *calibration_error.write() = e.to_string(),
+        }
+
+        track_event(
+            EventType::Export("Proxy calibration page".into()),
+            CalibrationEventData {
+                action: "Print calibration page",
+                paper_size: settings.page_setup.paper_size,
+                scale: settings.page_setup.scale,
+            },
+        );
+    };
+
+    let apply_calibration = move |_| {
+        *calibration_error.write() = String::new();
+
+        let ruler = CALIBRATION_RULER.0;
+        match measured_ruler.read().trim().parse::<f32>() {
+            Ok(measured) if measured > ruler / 2.0 && measured < ruler * 2.0 => {
+                // the ruler was printed with the current scale
+                let setup = &mut options.write().page_setup;
+                setup.scale = (setup.scale * ruler / measured * 1000.0).round() / 1000.0;
+            }
+            _ => {
+                *calibration_error.write() =
+                    format!("Enter the measured length of the {ruler} mm ruler, in mm.");
+                return;
+            }
+        }
+        measured_ruler.write().clear();
+
+        let page_setup = options.read().page_setup;
+        track_event(
+            EventType::Export("Proxy calibration page".into()),
+            CalibrationEventData {
+                action: "Apply calibration",
+                paper_size: page_setup.paper_size,
+                scale: page_setup.scale,
+            },
+        );
+    };
+
+    rsx! {
+        div { class: "field",
+            label { "for": "measured_ruler", class: "label", "Printer calibration" }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        onclick: download_calibration,
+                        span { class: "icon",
+                            i { class: "fa-solid fa-ruler-combined" }
+                        }
+                        span { "Print calibration page" }
+                    }
+                }
+                div { class: "control",
+                    div { class: "field has-addons",
+                        div { class: "control",
+                            input {
+                                id: "measured_ruler",
+                                r#type: "number",
+                                class: "input",
+                                style: "width: 12em;",
+                                placeholder: "Measured ruler (mm)",
+                                min: "50",
+                                max: "200",
+                                step: "0.1",
+                                value: "{measured_ruler}",
+                                oninput: move |ev| *measured_ruler.write() = ev.value(),
+                            }
+                        }
+                        div { class: "control",
+                            button {
+                                r#type: "button",
+                                class: "button",
+                                disabled: measured_ruler.read().trim().is_empty(),
+                                onclick: apply_calibration,
+                                "Apply"
+                            }
+                        }
+                    }
+                }
+            }
+            p { class: "help",
+                "Print the calibration page at actual size, then measure the 100 mm ruler to adjust the card scale. Increase the margins if a corner square is cut off."
+            }
+            p { class: "help is-danger", "{calibration_error}" }
+        }
+    }
+}
+
+/// Size of the resized card images, and a way to remove them
+#[component]
+fn ImageCacheSettings() -> Element {
+    let mut cache_usage = use_signal(|| None::<CacheUsage>);
+    let mut cache_error = use_signal(String::new);
+
+    // refreshed when the advanced settings are opened
+    use_effect(move || {
+        spawn(async move {
+            let usage = match ImageCache::open().await {
+                Ok(cache) => cache.usage().await,
+                Err(err) => Err(err),
+            };
+            match usage {
+                Ok(usage) => cache_usage.set(Some(usage)),
+                Err(err) => cache_error.set(err),
+            }
+        });
+    });
+
+    let clear_image_cache = move |_| async move {
+        *cache_error.write() = String::new();
+        let result = match ImageCache::open().await {
+            Ok(cache) => cache.clear().await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => cache_usage.set(Some(CacheUsage::default())),
+            Err(err) => cache_error.set(err),
+        }
+    };
+
+    rsx! {
+        div { class: "field",
+            label { class: "label", "Image cache" }
+            div { class: "control",
+                button {
+                    r#type: "button",
+                    class: "button",
+                    disabled: cache_usage.read().is_none_or(|usage| usage.images == 0),
+                    onclick: clear_image_cache,
+                    span { class: "icon",
+                        i { class: "fa-solid fa-trash" }
+                    }
+                    span { "Clear image cache" }
+                }
+            }
+            p { class: "help",
+                if let Some(usage) = *cache_usage.read() {
+                    "{usage.images} images ({format_size(usage.size)}). "
+                }
+                "The resized card images are kept for the next exports, and to print without a connection."
+            }
+            p { class: "help is-danger", "{cache_error}" }
+        }
+    }
+}
+
+/// Cut paths of the sheets, for cutting machines
+#[component]
+fn CutFile(
+    common_deck: Signal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    settings: ReadSignal<ProxySheetSettings>,
+) -> Element {
+    #[derive(Serialize)]
+    struct CutFileEventData {
+        format: &'static str,
+        file_format: &'static str,
+        rounded_corners: bool,
+        registration_marks: bool,
+        paper_size: PaperSize,
+        page_mode: PageMode,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+    impl TrackEvent for CutFileEventData {}
+
+    let mut cut_file_format = use_signal(|| "svg");
+    let mut rounded_corners = use_signal(|| true);
+    let mut cut_file_error = use_signal(String::new);
+
+    let download_cut_file = move |_| {
+        *cut_file_error.write() = String::new();
+
+        let settings = *settings.read();
+        let format = *cut_file_format.read();
+        let file_name = settings.cut_file_name(&common_deck.read(), &db.read(), format);
+
+        let error = match settings.cut_sheet(*rounded_corners.read()) {
+            Ok(sheet) => {
+                let file = match format {
+                    "dxf" => sheet.to_dxf(),
+                    _ => sheet.to_svg(),
+                };
+                download_file(&file_name, file.as_bytes());
+                None
+            }
+            Err(e) => {
+                *cut_file_error.write() = e.to_string();
+                Some(e.to_string())
+            }
+        };
+
+        track_event(
+            EventType::Export("Cut file".into()),
+            CutFileEventData {
+                format: "Cut file",
+                file_format: format,
+                rounded_corners: *rounded_corners.read(),
+                registration_marks: settings.registration_marks,
+                paper_size: settings.page_setup.paper_size,
+                page_mode: settings.page_mode,
+                error,
+            },
+        );
+    };
+
+    rsx! {
+        div { class: "field",
+            label { "for": "cut_file_format", class: "label", "Cut file" }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "cut_file_format",
+                            oninput: move |ev| {
+                                *cut_file_format.write() = match ev.value().as_str() {
+                                    "svg" => "svg",
+                                    "dxf" => "dxf",
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: *cut_file_format.read() == "svg",
+                                value: "svg",
+                                "SVG"
+                            }
+                            option {
+                                selected: *cut_file_format.read() == "dxf",
+                                value: "dxf",
+                                "DXF"
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "cut_file_corners",
+                            oninput: move |ev| {
+                                *rounded_corners.write() = match ev.value().as_str() {
+                                    "rounded" => true,
+                                    "square" => false,
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: *rounded_corners.read(),
+                                value: "rounded",
+                                "Rounded corners"
+                            }
+                            option {
+                                selected: !*rounded_corners.read(),
+                                value: "square",
+                                "Square corners"
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        disabled: common_deck.read().is_empty(),
+                        onclick: download_cut_file,
+                        span { class: "icon",
+                            i { class: "fa-solid fa-scissors" }
+                        }
+                        span { "Download cut file" }
+                    }
+                }
+            }
+            p { class: "help",
+                "Cut paths for cutting machines, the same file is used for every page. Use the registration marks from the advanced settings if your machine needs them."
+            }
+            p { class: "help is-danger", "{cut_file_error}" }
+        }
+    }
+}
+
+/// The pages or the cards as images, in a ZIP file
+#[component]
+fn ImagesExport(
+    printed_deck: ReadSignal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    settings: ReadSignal<ProxySheetSettings>,
+) -> Element {
+    #[derive(Serialize)]
+    struct ImagesEventData {
+        format: &'static str,
+        language: CardLanguage,
+        export: ImageExport,
+        file_format: RasterFormat,
+        dpi: f32,
+        default_settings: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+    impl TrackEvent for ImagesEventData {}
+
+    let mut image_export = use_signal(|| ImageExport::Pages);
+    let mut raster_format = use_signal(|| RasterFormat::Png);
+    let mut raster_dpi = use_signal(|| DPI);
+    let mut images_error = use_signal(String::new);
+    let mut images_loading = use_signal(|| false);
+    let mut images_progress = use_signal(|| None::<JobProgress>);
+    let mut images_skipped_cards = use_signal(Vec::<SkippedCard>::new);
+
+    let download_images = move |_| async move {
+        let common_deck = printed_deck.read().clone();
+
+        *images_loading.write() = true;
+        *images_error.write() = String::new();
+        images_skipped_cards.write().clear();
+
+        let settings = *settings.read();
+        let export = *image_export.read();
+        let format = *raster_format.read();
+        let dpi = *raster_dpi.read();
+        let file_name = settings.images_file_name(&common_deck, &db.read(), export, format);
+
+        let on_progress = move |progress| *images_progress.write() = Some(progress);
+        let result = generate_images(
+            &common_deck,
+            &db.read(),
+            settings,
+            export,
+            format,
+            dpi,
+            on_progress,
+        )
+        .await;
+        *images_progress.write() = None;
+        let error = match result {
+            Ok(output) => {
+                download_file(&file_name, &output.file[..]);
+                *images_skipped_cards.write() = output.skipped;
+                None
+            }
+            Err(e) => {
+                *images_error.write() = e.to_string();
+                Some(e.to_string())
+            }
+        };
+
+        track_event(
+            EventType::Export("Proxy images".into()),
+            ImagesEventData {
+                format: "Proxy images",
+                language: settings.card_lang,
+                export,
+                file_format: format,
+                dpi,
+                default_settings: settings.changed_count() == 0,
+                error,
+            },
+        );
+
+        *images_loading.write() = false;
+    };
+
+    rsx! {
+        div { class: "field",
+            label { "for": "image_export", class: "label", "Images" }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "image_export",
+                            oninput: move |ev| {
+                                *image_export.write() = match ev.value().as_str() {
+                                    "pages" => ImageExport::Pages,
+                                    "cards" => ImageExport::Cards,
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: *image_export.read() == ImageExport::Pages,
+                                value: "pages",
+                                "Pages"
+                            }
+                            option {
+                                selected: *image_export.read() == ImageExport::Cards,
+                                value: "cards",
+                                "Individual cards"
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "raster_format",
+                            oninput: move |ev| {
+                                *raster_format.write() = match ev.value().as_str() {
+                                    "png" => RasterFormat::Png,
+                                    "jpeg" => RasterFormat::Jpeg,
+                                    _ => unreachable!(),
+                                };
+                            },
+                            option {
+                                selected: *raster_format.read() == RasterFormat::Png,
+                                value: "png",
+                                "PNG"
+                            }
+                            option {
+                                selected: *raster_format.read() == RasterFormat::Jpeg,
+                                value: "jpeg",
+                                "JPEG"
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    div { class: "select",
+                        select {
+                            id: "raster_dpi",
+                            oninput: move |ev| {
+                                if let Ok(val) = ev.value().parse::<f32>() {
+                                    *raster_dpi.write() = val;
+                                }
+                            },
+                            for dpi in [150.0, 300.0, 600.0] {
+                                option {
+                                    selected: *raster_dpi.read() == dpi,
+                                    value: "{dpi}",
+                                    "{dpi} DPI"
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        class: if *images_loading.read() { "is-loading" },
+                        disabled: printed_deck.read().is_empty() || *images_loading.read(),
+                        onclick: download_images,
+                        span { class: "icon",
+                            i { class: "fa-solid fa-images" }
+                        }
+                        span { "Download images (ZIP)" }
+                    }
+                }
+            }
+            p { class: "help", "For print services that only accept images." }
+            p { class: "help is-danger", "{images_error}" }
+            JobStatus { progress: images_progress, skipped_cards: images_skipped_cards }
+        }
+    }
+}
+
+/// Amounts to print for each card, with shortcuts for the common selections
+#[component]
+fn CardSelection(
+    common_deck: Signal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    selection: Signal<PrintSelection>,
+) -> Element {
+    let card_lang = PREVIEW_CARD_LANG.signal();
+    let mut library = use_signal(Vec::<LibraryDeck>::new);
+    let mut owned_deck = use_signal(String::new);
+    let mut show_cards = use_signal(|| false);
+
+    use_effect(move || {
+        spawn(async move {
+            if let Ok(decks) = list_library_decks().await {
+                library.set(decks);
+            }
+        });
+    });
+
+    // the selection is reset with a new deck
+    use_effect(move || {
+        let _ = common_deck.read();
+        owned_deck.set(String::new());
+    });
+
+    let selected_count = use_memo(move || {
+        let selection = selection.read();
+        let deck = common_deck.read();
+        (
+            deck.all_cards().map(|c| selection.amount(c)).sum::<u32>(),
+            deck.all_cards().map(|c| c.amount).sum::<u32>(),
+        )
+    });
+
+    let select_missing = move |ev: Event<FormData>| {
+        let id = ev.value();
+        let owned = library
+            .read()
+            .iter()
+            .find(|save| save.id == id)
+            .map(|save| save.deck.to_deck_or_pile(&db.read()));
+        if let Some(owned) = owned {
+            *selection.write() = PrintSelection::missing_from(&common_deck.read(), &owned);
+        }
+        owned_deck.set(id);
+    };
+
+    let (selected, total) = *selected_count.read();
+
+    rsx! {
+        div { class: "field",
+            label { "for": "owned_cards", class: "label", "Cards to print" }
+            div { class: "field is-grouped is-grouped-multiline",
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        disabled: selected == total,
+                        onclick: move |_| {
+                            selection.set(PrintSelection::default());
+                            owned_deck.set(String::new());
+                        },
+                        "Whole deck"
+                    }
+                }
+                div { class: "control",
+                    button {
+                        r#type: "button",
+                        class: "button",
+                        onclick: move |_| {
+                            selection.set(PrintSelection::one_of_each(&common_deck.read()));
+                            owned_deck.set(String::new());
+                        },
+                        "One of each"
+                    }
+                }
+                div { class: "control",
+                    div { class: "select",
+                        select { id: "owned_cards", oninput: select_missing,
+                            option {
+                                selected: owned_deck.read().is_empty(),
+                                disabled: true,
+                                value: "",
+                                "Missing from my cards..."
+                            }
+                            for save in library.read().iter() {
+                                option {
+                                    key: "{save.id}",
+                                    selected: *owned_deck.read() == save.id,
+                                    value: "{save.id}",
+                                    "{save.name}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            p { class: "help",
+                "{selected} of {total} cards. "
+                "To print only the cards you don't own, save your cards as a pile and pick it in the list. "
+                a {
+                    href: "#",
+                    role: "button",
+                    onclick: move |evt| {
+                        evt.prevent_default();
+                        *show_cards.write() ^= true;
+                    },
+                    if *show_cards.read() {
+                        "Hide the cards"
+                    } else {
+                        "Choose the cards"
+                    }
+                }
+            }
+            if *show_cards.read() {
+                div { class: "table-container mt-2",
+                    table { class: "table is-narrow is-striped",
+                        thead {
+                            tr {
+                                th { "Card" }
+                                th { "Name" }
+                                th { "In deck" }
+                                th { "Print" }
+                            }
+                        }
+                        tbody {
+                            for card in common_deck.read().all_cards().cloned() {
+                                tr {
+                                    td { "{card.card_number}" }
+                                    td { {bundle::card_name(&card, &db.read(), *card_lang.read())} }
+                                    td { "{card.amount}" }
+                                    td {
+                                        input {
+                                            r#type: "number",
+                                            class: "input is-small",
+                                            style: "width: 5em;",
+                                            min: "0",
+                                            max: "50",
+                                            value: "{selection.read().amount(&card)}",
+                                            oninput: move |ev| {
+                                                if let Ok(amount) = ev.value().parse::<u32>() {
+                                                    selection.write().set_amount(&card, amount.min(50));
+                                                }
+                                            },
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
         }
     }
+}
 
-    let mut deck_error = use_signal(String::new);
-    let card_lang = PREVIEW_CARD_LANG.signal();
-    let mut paper_size = use_signal(|| match get_local_country().as_deref() {
-        Some("US" | "CA" | "MX" | "CR" | "PA" | "DO" | "GT" | "CL" | "CO" | "VE" | "PE") => {
-            PaperSize::Letter
-        }
-        _ => PaperSize::A4,
-    });
-    let mut include_cheers = use_signal(|| DEFAULT_INCLUDE_CHEERS);
-    let mut crop_marks_size = use_signal(|| DEFAULT_CROP_MARK_SIZE);
-    let mut crop_marks_position = use_signal(|| DEFAULT_CROP_MARK_POSITION);
-    let mut card_size = use_signal(|| DEFAULT_CARD_SIZE);
-    let mut gap = use_signal(|| DEFAULT_GAP);
+/// Prints the current deck with saved decks and piles of the library, in one PDF
+#[component]
+fn BatchPrint(
+    common_deck: Signal<DeckOrPile>,
+    db: Signal<CardsDatabase>,
+    settings: ReadSignal<ProxySheetSettings>,
+) -> Element {
+    #[derive(Serialize)]
+    struct BatchEventData {
+        format: &'static str,
+        language: CardLanguage,
+        decks: usize,
+        separator_pages: bool,
+        duplex: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+    impl TrackEvent for BatchEventData {}
+
+    let mut library = use_signal(Vec::<LibraryDeck>::new);
+    let mut library_error = use_signal(String::new);
+    let mut selected = use_signal(Vec::<String>::new);
+    let mut include_current = use_signal(|| true);
+    let mut separator_pages = use_signal(|| false);
+    let mut error = use_signal(String::new);
     let mut loading = use_signal(|| false);
-    let mut show_advanced = use_signal(|| false);
+    let mut progress = use_signal(|| None::<JobProgress>);
+    let mut skipped_cards = use_signal(Vec::<SkippedCard>::new);
 
-    let settings_count = use_memo(move || {
-        [
-            *crop_marks_size.read() != DEFAULT_CROP_MARK_SIZE,
-            *crop_marks_position.read() != DEFAULT_CROP_MARK_POSITION,
-            *card_size.read() != DEFAULT_CARD_SIZE,
-            *gap.read() != DEFAULT_GAP,
-        ]
-        .iter()
-        .filter(|&&x| x)
-        .count()
+    use_effect(move || {
+        spawn(async move {
+            match list_library_decks().await {
+                Ok(decks) => library.set(decks),
+                Err(err) => library_error.set(err),
+            }
+        });
     });
 
-    let print_deck = move |_| async move {
-        let common_deck = common_deck.read();
+    let decks = use_memo(move || {
+        let db = db.read();
+        let mut decks = vec![];
+        if *include_current.read() && !common_deck.read().is_empty() {
+            let deck = common_deck.read().clone();
+            decks.push((deck.required_deck_name(&db), deck));
+        }
+        for save in library.read().iter() {
+            if selected.read().contains(&save.id) {
+                decks.push((save.name.clone(), save.deck.to_deck_or_pile(&db)));
+            }
+        }
+        decks
+    });
 
-        *loading.write() = true;
-        *deck_error.write() = String::new();
+    let summaries = use_memo(move || {
+        BatchDeckSummary::preview(
+            &decks.read(),
+            &db.read(),
+            &settings.read(),
+            *separator_pages.read(),
+        )
+    });
 
-        let lang = match *card_lang.read() {
-            CardLanguage::Japanese => "jp",
-            CardLanguage::English => "en",
-        };
-        let ps = match *paper_size.read() {
-            PaperSize::A4 => "a4",
-            PaperSize::Letter => "letter",
-            PaperSize::Legal => "legal",
-        };
+    let print_decks = move |_| async move {
+        let decks = decks.read().clone();
 
-        let file_name = common_deck.file_name(&db.read());
-        let file_name = format!("{file_name}.proxy_sheets.{lang}_{ps}.pdf");
+        *loading.write() = true;
+        *error.write() = String::new();
+        skipped_cards.write().clear();
 
-        let missing_proxies = has_missing_proxies(&common_deck, &db.read(), *card_lang.read());
+        let settings = *settings.read();
+        let separator_pages = *separator_pages.read();
+        let file_name = settings.batch_file_name(decks.len());
 
-        match generate_pdf(
-            &common_deck,
-            &db.read(),
-            ProxySheetSettings {
-                card_lang: *card_lang.read(),
-                paper_size: *paper_size.read(),
-                include_cheers: *include_cheers.read(),
-                crop_marks_size: *crop_marks_size.read(),
-                crop_marks_position: *crop_marks_position.read(),
-                card_size: *card_size.read(),
-                gap: *gap.read(),
-            },
-        )
-        .await
-        {
-            Ok(file) => {
-                download_file(&file_name, &file[..]);
-                track_event(
-                    EventType::Export("Proxy sheets".into()),
-                    EventData {
-                        format: "Proxy sheets",
-                        language: *card_lang.read(),
-                        missing_proxies,
-                        paper_size: *paper_size.read(),
-                        include_cheers: *include_cheers.read(),
-                        default_settings: *settings_count.read() == 0,
-                        crop_marks_size: *crop_marks_size.read(),
-                        crop_marks_position: *crop_marks_position.read(),
-                        card_size: *card_size.read(),
-                        gap: *gap.read(),
-                        error: None,
-                    },
-                );
+        let on_progress = move |p| *progress.write() = Some(p);
+        let result =
+            generate_batch_pdf(&decks, &db.read(), settings, separator_pages, on_progress).await;
+        *progress.write() = None;
+        let event_error = match result {
+            Ok(output) => {
+                download_file(&file_name, &output.file[..]);
+                *skipped_cards.write() = output.skipped;
+                None
             }
             Err(e) => {
-                *deck_error.write() = e.to_string();
-                track_event(
-                    EventType::Export("Proxy sheets".into()),
-                    EventData {
-                        format: "Proxy sheets",
-                        language: *card_lang.read(),
-                        missing_proxies,
-                        paper_size: *paper_size.read(),
-                        include_cheers: *include_cheers.read(),
-                        default_settings: *settings_count.read() == 0,
-                        crop_marks_size: *crop_marks_size.read(),
-                        crop_marks_position: *crop_marks_position.read(),
-                        card_size: *card_size.read(),
-                        gap: *gap.read(),
-                        error: Some(e.to_string()),
-                    },
-                );
+                *error.write() = e.to_string();
+                Some(e.to_string())
             }
-        }
+        };
+        track_event(
+            EventType::Export("Proxy sheets".into()),
+            BatchEventData {
+                format: "Proxy sheets (batch)",
+                language: settings.card_lang,
+                decks: decks.len(),
+                separator_pages,
+                duplex: settings.duplex,
+                error: event_error,
+            },
+        );
 
         *loading.write() = false;
     };
 
     rsx! {
-        DeckValidation {
-            deck_check: true,
-            proxy_check: true,
-            allow_unreleased: true,
-            allow_pile: true,
-            card_lang,
-            db,
-            common_deck,
-        }
-
-        div { class: "block",
-            div { class: "grid is-col-min-8",
-                // Card language
-                div { class: "cell",
-                    label { "for": "card_language", class: "label", "Card language" }
-                    div { class: "control",
-                        div { class: "select",
-                            select {
-                                id: "card_language",
-                                oninput: move |ev| {
-                                    *PREVIEW_CARD_LANG.write() = match ev.value().as_str() {
-                                        "jp" => CardLanguage::Japanese,
-                                        "en" => CardLanguage::English,
-                                        _ => unreachable!(),
-                                    };
-                                },
-                                option {
-                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::English,
-                                    value: "en",
-                                    "English"
-                                }
-                                option {
-                                    selected: *PREVIEW_CARD_LANG.read() == CardLanguage::Japanese,
-                                    value: "jp",
-                                    "Japanese"
-                                }
-                            }
-                        }
+        div { class: "field",
+            label { class: "label", "Multiple decks" }
+            div { class: "control",
+                label { class: "checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: *include_current.read(),
+                        disabled: common_deck.read().is_empty(),
+                        onclick: move |_| {
+                            *include_current.write() ^= true;
+                        },
                     }
+                    " Current deck"
                 }
-
-                // Include cheers
-                div { class: "cell",
-                    label { "for": "include_cheers", class: "label", "Include cheers" }
-                    div { class: "control",
-                        div { class: "select",
-                            select {
-                                id: "include_cheers",
-                                oninput: move |ev| {
-                                    *include_cheers.write() = match ev.value().as_str() {
-                                        "no" => false,
-                                        "yes" => true,
-                                        _ => unreachable!(),
-                                    };
-                                },
-                                option {
-                                    selected: !*include_cheers.read(),
-                                    value: "no",
-                                    "No"
-                                }
-                                option {
-                                    selected: *include_cheers.read(),
-                                    value: "yes",
-                                    "Yes"
-                                }
-                            }
-                        }
-                    }
+            }
+            div {
+                class: "box p-2 mt-2",
+                style: "max-height: 12em; overflow-y: auto;",
+                if library.read().is_empty() {
+                    p { class: "help", "No saved decks, save decks or piles to print them together." }
                 }
-
-                // Paper size
-                div { class: "cell",
-                    label { "for": "paper_size", class: "label", "Paper size" }
-                    div { class: "control",
-                        div { class: "select",
-                            select {
-                                id: "paper_size",
-                                oninput: move |ev| {
-                                    *paper_size.write() = match ev.value().as_str() {
-                                        "a4" => PaperSize::A4,
-                                        "letter" => PaperSize::Letter,
-                                        "legal" => PaperSize::Legal,
-                                        _ => unreachable!(),
-                                    };
+                for save in library.read().iter() {
+                    div { key: "{save.id}", class: "control",
+                        label { class: "checkbox",
+                            input {
+                                r#type: "checkbox",
+                                checked: selected.read().contains(&save.id),
+                                onclick: {
+                                    let id = save.id.clone();
+                                    move |_| {
+                                        let mut selected = selected.write();
+                                        if let Some(idx) = selected.iter().position(|s| *s == id) {
+                                            selected.remove(idx);
+                                        } else {
+                                            selected.push(id.clone());
+                                        }
+                                    }
                                 },
-                                option {
-                                    selected: *paper_size.read() == PaperSize::A4,
-                                    value: "a4",
-                                    "A4 (210x297 mm)"
-                                }
-                                option {
-                                    selected: *paper_size.read() == PaperSize::Letter,
-                                    value: "letter",
-                                    "Letter (8.5x11 in)"
-                                }
-                                option {
-                                    selected: *paper_size.read() == PaperSize::Legal,
-                                    value: "legal",
-                                    "Legal (8.5x14 in)"
-                                }
                             }
+                            " {save.name}"
                         }
                     }
                 }
-
             }
+            p { class: "help is-danger", "{library_error}" }
         }
 
-        // Advanced settings
-        div { class: if *show_advanced.read() { "field" } else { "block" },
-            a {
-                href: "#",
-                role: "button",
-                onclick: move |evt| {
-                    evt.prevent_default();
-                    let show = *show_advanced.read();
-                    *show_advanced.write() = !show;
-                },
-                span { class: "icon",
-                    i {
-                        class: "fa-solid",
-                        class: if *show_advanced.read() { "fa-chevron-down" } else { "fa-chevron-right" },
+        div { class: "field",
+            div { class: "control",
+                label { class: "checkbox",
+                    input {
+                        r#type: "checkbox",
+                        checked: *separator_pages.read(),
+                        onclick: move |_| {
+                            *separator_pages.write() ^= true;
+                        },
                     }
+                    " Separator page before each deck"
                 }
-                "Advanced settings"
-                if *settings_count.read() > 0 {
-                    " ({settings_count})"
+            }
+            p { class: "help",
+                if *separator_pages.read() {
+                    "Each deck starts on a new sheet, after a page with its name."
+                } else {
+                    "The cards fill the sheets across the decks, a sheet can hold the cards of two decks."
                 }
             }
         }
 
-        if *show_advanced.read() {
-            div { class: "block",
-                div { class: "grid is-col-min-8",
-                    // Crop marks
-                    div { class: "cell",
-                        label { "for": "include_crop_marks", class: "label", "Crop marks" }
-                        div { class: "control",
-                            div { class: "select",
-                                select {
-                                    id: "include_crop_marks",
-                                    oninput: move |ev| {
-                                        *crop_marks_size.write() = match (
-                                            ev.value().as_str(),
-                                            ev.value().parse::<f32>(),
-                                        ) {
-                                            ("none", _) => CropMarksSize::None,
-                                            ("full", _) => CropMarksSize::FullLength,
-                                            (_, Ok(val)) => CropMarksSize::Mm(val),
-                                            _ => unreachable!(),
-                                        };
-                                    },
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::None,
-                                        value: "none",
-                                        "None"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::Mm(3.0),
-                                        value: "3",
-                                        "3mm"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::Mm(5.0),
-                                        value: "5",
-                                        "5mm"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::Mm(10.0),
-                                        value: "10",
-                                        "10mm"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::Mm(20.0),
-                                        value: "20",
-                                        "20mm"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::Mm(30.0),
-                                        value: "30",
-                                        "30mm"
-                                    }
-                                    option {
-                                        selected: *crop_marks_size.read() == CropMarksSize::FullLength,
-                                        value: "full",
-                                        "Full length"
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    // Crop marks position
-                    div { class: "cell",
-                        label { "for": "crop_marks_position", class: "label", "Crop marks position" }
-                        div { class: "control",
-                            div { class: "select",
-                                select {
-                                    id: "crop_marks_position",
-                                    disabled: *crop_marks_size.read() == CropMarksSize::None,
-                                    oninput: move |ev| {
-                                        *crop_marks_position.write() = match ev.value().as_str() {
-                                            "centered" => CropMarksPosition::Centered,
-                                            "card_corners" => CropMarksPosition::CardCorners,
-                                            _ => unreachable!(),
-                                        };
-                                    },
-                                    option {
-                                        selected: *crop_marks_position.read() == CropMarksPosition::Centered,
-                                        value: "centered",
-                                        "Centered"
-                                    }
-                                    option {
-                                        selected: *crop_marks_position.read() == CropMarksPosition::CardCorners,
-                                        value: "card_corners",
-                                        "Card corners"
-                                    }
-                                }
-                            }
+        if !summaries.read().is_empty() {
+            div { class: "table-container",
+                table { class: "table is-narrow is-striped",
+                    thead {
+                        tr {
+                            th { "Deck" }
+                            th { "Cards" }
+                            th { "Sheets" }
                         }
                     }
-
-                    // Card size
-                    div { class: "cell",
-                        label { "for": "card_size", class: "label", "Card size" }
-                        div { class: "control",
-                            div { class: "select",
-                                select {
-                                    id: "card_size",
-                                    oninput: move |ev| {
-                                        *card_size.write() = match ev.value().as_str() {
-                                            "metric" => CardSize::Metric,
-                                            "imperial" => CardSize::Imperial,
-                                            _ => unreachable!(),
-                                        };
-                                    },
-                                    option {
-                                        selected: *card_size.read() == CardSize::Metric,
-                                        value: "metric",
-                                        "Metric (63x88 mm)"
-                                    }
-                                    option {
-                                        selected: *card_size.read() == CardSize::Imperial,
-                                        value: "imperial",
-                                        "Imperial (2.5x3.5 in)"
+                    tbody {
+                        for summary in summaries.read().iter() {
+                            tr {
+                                td { "{summary.name}" }
+                                td { title: "{summary.description()}", "{summary.cards()}" }
+                                td {
+                                    if summary.sheets.0 == summary.sheets.1 {
+                                        "{summary.sheets.0}"
+                                    } else {
+                                        "{summary.sheets.0}-{summary.sheets.1}"
                                     }
                                 }
                             }
                         }
                     }
-
-                    // Gap
-                    div { class: "cell",
-                        label { "for": "gap", class: "label", "Gap between cards (mm)" }
-                        div { class: "control",
-                            input {
-                                id: "gap",
-                                r#type: "number",
-                                class: "input",
-                                style: "width: auto;",
-                                min: "0",
-                                max: "10",
-                                step: "0.5",
-                                maxlength: "4",
-                                value: gap.read().0.to_string(),
-                                oninput: move |ev| {
-                                    if let Ok(val) = ev.value().parse::<f32>() {
-                                        *gap.write() = Mm(val.max(0.0));
-                                    }
-                                },
-                            }
-                        }
-                    }
                 }
             }
         }
@@ -1073,15 +4230,45 @@ pub fn Export(mut common_deck: Signal<DeckOrPile>, db: Signal<CardsDatabase>) ->
                     r#type: "button",
                     class: "button",
                     class: if *loading.read() { "is-loading" },
-                    disabled: common_deck.read().is_empty() || *loading.read(),
-                    onclick: print_deck,
+                    disabled: decks.read().len() < 2 || *loading.read(),
+                    onclick: print_decks,
                     span { class: "icon",
-                        i { class: "fa-solid fa-print" }
+                        i { class: "fa-solid fa-layer-group" }
+                    }
+                    span { "Print decks to PDF" }
+                }
+            }
+            p { class: "help", "Select at least two decks. The cards they share are downloaded once." }
+            p { class: "help is-danger", "{error}" }
+            JobStatus { progress, skipped_cards }
+        }
+    }
+}
+
+/// Progress bar while the job runs, then the cards that were left out
+#[component]
+fn JobStatus(
+    progress: Signal<Option<JobProgress>>,
+    skipped_cards: Signal<Vec<SkippedCard>>,
+) -> Element {
+    rsx! {
+        if let Some(JobProgress { done, total }) = *progress.read() {
+            progress {
+                class: "progress is-small is-info mt-2 mb-1",
+                value: "{done}",
+                max: "{total}",
+            }
+            p { class: "help", "Preparing the card images ({done}/{total})" }
+        }
+        if !skipped_cards.read().is_empty() {
+            div { class: "content help has-text-warning-dark mt-2",
+                "Some cards could not be downloaded and are missing from the file:"
+                ul {
+                    for skipped in skipped_cards.read().iter() {
+                        li { "{skipped.name}: {skipped.error}" }
                     }
-                    span { "Print deck to PDF" }
                 }
             }
-            p { class: "help is-danger", "{deck_error}" }
         }
     }
 }
@@ -0,0 +1,497 @@
+//! The image work of the proxies runs in a web worker, the page stays responsive.
+//! The worker loads the app module a second time and keeps the images of a job,
+//! the page only downloads the images and draws with the canvas.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::io::Cursor;
+
+use ::image::imageops::FilterType;
+use ::image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use futures::StreamExt;
+use futures::channel::mpsc;
+use gloo::events::EventListener;
+use js_sys::{Array, Function, Object, Reflect, Uint8Array};
+use printpdf::*;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::prelude::*;
+use web_sys::{MessageEvent, Worker, WorkerOptions, WorkerType};
+
+use super::{
+    Layout, PdfImageFormat, ProxySheetSettings, RasterFormat, draw_overlay, mm_to_px,
+    process_card_image, to_raw_image,
+};
+use crate::archive::ZipArchive;
+
+const WORKER_PATH: &str = "/hocg-deck-convert/image-worker.js";
+
+#[wasm_bindgen]
+extern "C" {
+    /// The app module, loaded again by the worker
+    #[wasm_bindgen(thread_local_v2, js_namespace = ["import", "meta"], js_name = url)]
+    static APP_MODULE_URL: String;
+}
+
+/// An image kept by the worker until the end of the job
+pub type ImageId = u32;
+
+/// An image on a page, placed from the bottom-left corner like in the PDF
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlacedImage {
+    pub id: ImageId,
+    pub x: Mm,
+    pub y: Mm,
+    pub dpi: f32,
+}
+
+/// A page of the PDF, or of the images export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PagePlan {
+    pub width: Mm,
+    pub height: Mm,
+    /// From the bottom to the top
+    pub images: Vec<PlacedImage>,
+}
+
+/// A file of the images export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ZipImage {
+    Page { page: PagePlan, dpi: f32 },
+    Image(ImageId),
+}
+
+#[derive(Serialize, Deserialize)]
+enum Request {
+    /// The settings of the job, for the card images and the overlay
+    Start {
+        settings: ProxySheetSettings,
+        layout: Layout,
+    },
+    /// RGBA pixels drawn by the page
+    Pixels {
+        id: ImageId,
+        width: u32,
+        height: u32,
+    },
+    /// A WebP card image, at the card size. Returns the lossless WebP to cache when `encode`.
+    Decode {
+        id: ImageId,
+        size: (u32, u32),
+        encode: bool,
+    },
+    /// Prepares the card image for the print, the translation is dropped after
+    Card {
+        id: ImageId,
+        translation: Option<ImageId>,
+        watermark: Option<ImageId>,
+    },
+    /// Crop marks and registration marks, see [`Response::Overlay`]
+    Overlay { id: ImageId },
+    /// Places the images on the pages and saves the PDF
+    Pdf {
+        title: String,
+        pages: Vec<PagePlan>,
+        format: PdfImageFormat,
+    },
+    /// Renders and encodes the images, in a ZIP archive
+    Zip {
+        files: Vec<(String, ZipImage)>,
+        format: RasterFormat,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Response {
+    Done,
+    /// `false` when there is no mark to draw
+    Overlay(bool),
+}
+
+/// The worker of a proxy job, stopped when dropped.
+/// The requests are sent one at a time, they are handled in order.
+pub struct ImageWorker {
+    worker: Worker,
+    replies: mpsc::UnboundedReceiver<Result<JsValue, String>>,
+    next_id: ImageId,
+    _listeners: [EventListener; 2],
+}
+
+impl ImageWorker {
+    pub fn new() -> Result<Self, String> {
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        let worker = Worker::new_with_options(WORKER_PATH, &options)
+            .map_err(|err| format!("Could not start the image worker: {err:?}"))?;
+
+        let (sender, replies) = mpsc::unbounded();
+        let on_message = EventListener::new(&worker, "message", {
+            let sender = sender.clone();
+            move |event| {
+                let event = event.unchecked_ref::<MessageEvent>();
+                let _ = sender.unbounded_send(Ok(event.data()));
+            }
+        });
+        // the script could not be loaded
+        let on_error = EventListener::new(&worker, "error", move |_| {
+            let _ = sender.unbounded_send(Err("The image worker could not be loaded".into()));
+        });
+
+        // the module is already compiled by the page
+        let url = APP_MODULE_URL.with(JsValue::from);
+        let init = object(&[("url", &url), ("module", &wasm_bindgen::module())]);
+        worker
+            .post_message(&object(&[("init", &init)]))
+            .map_err(|err| format!("Could not start the image worker: {err:?}"))?;
+
+        Ok(Self {
+            worker,
+            replies,
+            next_id: 0,
+            _listeners: [on_message, on_error],
+        })
+    }
+
+    pub async fn start(
+        &mut self,
+        settings: &ProxySheetSettings,
+        layout: &Layout,
+    ) -> Result<(), String> {
+        let request = Request::Start {
+            settings: *settings,
+            layout: *layout,
+        };
+        self.request(&request, None, || {}).await?;
+        Ok(())
+    }
+
+    /// An image drawn by the page
+    pub async fn add_pixels(&mut self, image: RgbaImage) -> Result<ImageId, String> {
+        let id = self.new_id();
+        let (width, height) = image.dimensions();
+        let request = Request::Pixels { id, width, height };
+        self.request(&request, Some(image.into_raw()), || {})
+            .await?;
+        Ok(id)
+    }
+
+    /// Decodes a card image at the card size, with its lossless WebP when `encode`
+    pub async fn decode(
+        &mut self,
+        bytes: Vec<u8>,
+        size: (u32, u32),
+        encode: bool,
+    ) -> Result<(ImageId, Option<Vec<u8>>), String> {
+        let id = self.new_id();
+        let request = Request::Decode { id, size, encode };
+        let (_, webp) = self.request(&request, Some(bytes), || {}).await?;
+        Ok((id, webp))
+    }
+
+    /// See [`process_card_image`]
+    pub async fn process_card(
+        &mut self,
+        id: ImageId,
+        translation: Option<ImageId>,
+        watermark: Option<ImageId>,
+    ) -> Result<(), String> {
+        let request = Request::Card {
+            id,
+            translation,
+            watermark,
+        };
+        self.request(&request, None, || {}).await?;
+        Ok(())
+    }
+
+    /// See [`draw_overlay`]
+    pub async fn overlay(&mut self) -> Result<Option<ImageId>, String> {
+        let id = self.new_id();
+        match self.request(&Request::Overlay { id }, None, || {}).await? {
+            (Response::Overlay(true), _) => Ok(Some(id)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The PDF file, `on_step` is called for each image added to the document
+    pub async fn pdf(
+        &mut self,
+        title: &str,
+        pages: Vec<PagePlan>,
+        format: PdfImageFormat,
+        on_step: impl FnMut(),
+    ) -> Result<Vec<u8>, String> {
+        let request = Request::Pdf {
+            title: title.into(),
+            pages,
+            format,
+        };
+        let (_, file) = self.request(&request, None, on_step).await?;
+        file.ok_or_else(|| "The image worker did not return the PDF".into())
+    }
+
+    /// The ZIP file, `on_step` is called for each image
+    pub async fn zip(
+        &mut self,
+        files: Vec<(String, ZipImage)>,
+        format: RasterFormat,
+        on_step: impl FnMut(),
+    ) -> Result<Vec<u8>, String> {
+        let request = Request::Zip { files, format };
+        let (_, file) = self.request(&request, None, on_step).await?;
+        file.ok_or_else(|| "The image worker did not return the images".into())
+    }
+
+    fn new_id(&mut self) -> ImageId {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Sends the request, the bytes are moved to the worker without a copy
+    async fn request(
+        &mut self,
+        request: &Request,
+        bytes: Option<Vec<u8>>,
+        mut on_step: impl FnMut(),
+    ) -> Result<(Response, Option<Vec<u8>>), String> {
+        let request =
+            to_value(request).map_err(|err| format!("Could not encode image request: {err}"))?;
+        let transfer = Array::new();
+        let bytes = match bytes {
+            Some(bytes) => {
+                let bytes = Uint8Array::from(&bytes[..]);
+                transfer.push(&bytes.buffer());
+                bytes.into()
+            }
+            None => JsValue::UNDEFINED,
+        };
+        self.worker
+            .post_message_with_transfer(
+                &object(&[("request", &request), ("bytes", &bytes)]),
+                &transfer,
+            )
+            .map_err(|err| format!("Could not send image request: {err:?}"))?;
+
+        while let Some(reply) = self.replies.next().await {
+            let reply = reply?;
+            let field = |key: &str| Reflect::get(&reply, &key.into()).unwrap_or_default();
+            if let Some(error) = field("error").as_string() {
+                return Err(error);
+            }
+            if field("progress").is_truthy() {
+                on_step();
+                continue;
+            }
+
+            let response = from_value(field("response"))
+                .map_err(|err| format!("Could not decode image response: {err}"))?;
+            let bytes = field("bytes")
+                .dyn_into::<Uint8Array>()
+                .ok()
+                .map(|bytes| bytes.to_vec());
+            return Ok((response, bytes));
+        }
+        Err("The image worker stopped".into())
+    }
+}
+
+impl Drop for ImageWorker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+/// A plain JS object
+fn object(entries: &[(&str, &JsValue)]) -> Object {
+    let object = Object::new();
+    for (key, value) in entries {
+        // can't fail on a plain object
+        let _ = Reflect::set(&object, &(*key).into(), value);
+    }
+    object
+}
+
+/// The job of the worker, see [`Request::Start`]
+#[derive(Default)]
+struct WorkerJob {
+    settings: Option<(ProxySheetSettings, Layout)>,
+    images: HashMap<ImageId, DynamicImage>,
+}
+
+thread_local! {
+    static JOB: RefCell<WorkerJob> = RefCell::new(WorkerJob::default());
+}
+
+/// Handles a request in the worker, called by `image-worker.js`
+#[wasm_bindgen]
+pub fn proxy_image_worker(message: JsValue, on_progress: &Function) -> Result<JsValue, JsValue> {
+    let request = from_value(Reflect::get(&message, &"request".into())?)?;
+    let bytes = Reflect::get(&message, &"bytes".into())?
+        .dyn_into::<Uint8Array>()
+        .ok()
+        .map(|bytes| bytes.to_vec());
+    let on_step = || {
+        let _ = on_progress.call0(&JsValue::NULL);
+    };
+
+    let (response, bytes) = JOB
+        .with_borrow_mut(|job| job.handle(request, bytes, on_step))
+        .map_err(|err| JsValue::from(err.to_string()))?;
+    let bytes = match bytes {
+        Some(bytes) => Uint8Array::from(&bytes[..]).into(),
+        None => JsValue::UNDEFINED,
+    };
+    Ok(object(&[("response", &to_value(&response)?), ("bytes", &bytes)]).into())
+}
+
+impl WorkerJob {
+    fn handle(
+        &mut self,
+        request: Request,
+        bytes: Option<Vec<u8>>,
+        mut on_step: impl FnMut(),
+    ) -> Result<(Response, Option<Vec<u8>>), Box<dyn Error>> {
+        match request {
+            Request::Start { settings, layout } => {
+                self.settings = Some((settings, layout));
+                self.images.clear();
+            }
+            Request::Pixels { id, width, height } => {
+                let pixels = bytes.ok_or("Missing image pixels")?;
+                let image =
+                    RgbaImage::from_raw(width, height, pixels).ok_or("Invalid image size")?;
+                self.images.insert(id, DynamicImage::ImageRgba8(image));
+            }
+            Request::Decode {
+                id,
+                size: (width, height),
+                encode,
+            } => {
+                let bytes = bytes.ok_or("Missing image bytes")?;
+                let image = ::image::load_from_memory_with_format(&bytes, ImageFormat::WebP)?;
+                let image = if (image.width(), image.height()) != (width, height) {
+                    image.resize_exact(width, height, FilterType::CatmullRom)
+                } else {
+                    image
+                };
+                let webp = if encode {
+                    let mut bytes = Cursor::new(vec![]);
+                    image.write_to(&mut bytes, ImageFormat::WebP)?;
+                    Some(bytes.into_inner())
+                } else {
+                    None
+                };
+                self.images.insert(id, image);
+                return Ok((Response::Done, webp));
+            }
+            Request::Card {
+                id,
+                translation,
+                watermark,
+            } => {
+                let (settings, layout) = self.settings.as_ref().ok_or("The job is not started")?;
+                let image = self.images.remove(&id).ok_or("Missing card image")?;
+                let translation = translation
+                    .and_then(|id| self.images.remove(&id))
+                    .map(DynamicImage::into_rgba8);
+                let watermark = watermark
+                    .and_then(|id| self.images.get(&id))
+                    .and_then(DynamicImage::as_rgba8);
+                let image =
+                    process_card_image(image, settings, layout, translation.as_ref(), watermark);
+                self.images.insert(id, image);
+            }
+            Request::Overlay { id } => {
+                let (settings, layout) = self.settings.as_ref().ok_or("The job is not started")?;
+                let overlay = draw_overlay(layout, settings)?;
+                let marks = overlay.is_some();
+                if let Some(overlay) = overlay {
+                    self.images.insert(id, DynamicImage::ImageRgba8(overlay));
+                }
+                return Ok((Response::Overlay(marks), None));
+            }
+            Request::Pdf {
+                title,
+                pages,
+                format,
+            } => {
+                let mut doc = PdfDocument::new(&title);
+                doc.metadata.info.producer = "hololive OCG Deck Converter".to_string();
+                // no metadata date for wasm, printpdf can't do it
+
+                // Add each image once to the document resources
+                let mut image_ids = HashMap::new();
+                let mut pdf_pages = Vec::with_capacity(pages.len());
+                for page in pages {
+                    let mut ops = Vec::with_capacity(page.images.len());
+                    for placed in page.images {
+                        let image_id = match image_ids.entry(placed.id) {
+                            Entry::Occupied(entry) => entry.get().clone(),
+                            Entry::Vacant(entry) => {
+                                let image = self.images.get(&placed.id).ok_or("Missing image")?;
+                                let image_id = doc.add_image(&to_raw_image(image)?);
+                                on_step();
+                                entry.insert(image_id).clone()
+                            }
+                        };
+                        ops.push(Op::UseXobject {
+                            id: image_id,
+                            transform: XObjectTransform {
+                                dpi: Some(placed.dpi),
+                                translate_x: Some(placed.x.into()),
+                                translate_y: Some(placed.y.into()),
+                                ..Default::default()
+                            },
+                        });
+                    }
+                    pdf_pages.push(PdfPage::new(page.width, page.height, ops));
+                }
+
+                let file = doc.with_pages(pdf_pages).save(
+                    &PdfSaveOptions {
+                        image_optimization: Some(format.optimization()),
+                        ..Default::default()
+                    },
+                    &mut Vec::new(),
+                );
+                return Ok((Response::Done, Some(file)));
+            }
+            Request::Zip { files, format } => {
+                let mut archive = ZipArchive::new();
+                for (name, file) in files {
+                    let bytes = match file {
+                        ZipImage::Page { page, dpi } => {
+                            format.encode(&self.render_page(&page, dpi)?)?
+                        }
+                        ZipImage::Image(id) => {
+                            format.encode(self.images.get(&id).ok_or("Missing card image")?)?
+                        }
+                    };
+                    archive.add_file(&name, &bytes);
+                    on_step();
+                }
+                return Ok((Response::Done, Some(archive.finish())));
+            }
+        }
+        Ok((Response::Done, None))
+    }
+
+    /// Draws the images of the page over a white page
+    fn render_page(&self, page: &PagePlan, dpi: f32) -> Result<DynamicImage, Box<dyn Error>> {
+        let mut image = RgbaImage::from_pixel(
+            mm_to_px(page.width, dpi) as u32,
+            mm_to_px(page.height, dpi) as u32,
+            Rgba([0xFF, 0xFF, 0xFF, 0xFF]),
+        );
+        for placed in &page.images {
+            let card = self.images.get(&placed.id).ok_or("Missing image")?;
+            // Convert to origin top-left
+            let x = mm_to_px(placed.x, dpi);
+            let y = mm_to_px(page.height - placed.y, dpi) - card.height() as i64;
+            ::image::imageops::overlay(&mut image, card, x, y);
+        }
+        Ok(DynamicImage::ImageRgba8(image))
+    }
+}
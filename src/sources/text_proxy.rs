@@ -0,0 +1,467 @@
+use std::error::Error;
+
+use hocg_fan_sim_assets_model::{self as hocg, KeywordEffect};
+use image::RgbaImage;
+use printpdf::Mm;
+
+use super::decklist::Page;
+
+const BLACK: &str = "#202020";
+const GRAY: &str = "#606060";
+const FOOTER_TEXT: &str = "Text proxy";
+/// Font sizes in mm, the canvas draws with the browser fonts
+const TEXT_SIZE: f64 = 2.1;
+const TITLE_SIZE: f64 = 3.2;
+const LINE_SPACING: f64 = 1.3;
+
+fn color_rgb(color: hocg::Color) -> [u8; 3] {
+    match color {
+        hocg::Color::White => [0xE8, 0xE8, 0xE8],
+        hocg::Color::Green => [0x4C, 0xAF, 0x50],
+        hocg::Color::Red => [0xE5, 0x39, 0x35],
+        hocg::Color::Blue => [0x1E, 0x88, 0xE5],
+        hocg::Color::Purple => [0x8E, 0x24, 0xAA],
+        hocg::Color::Yellow => [0xFD, 0xD8, 0x35],
+        hocg::Color::Colorless => [0x9E, 0x9E, 0x9E],
+    }
+}
+
+fn color_name(color: hocg::Color) -> &'static str {
+    match color {
+        hocg::Color::White => "White",
+        hocg::Color::Green => "Green",
+        hocg::Color::Red => "Red",
+        hocg::Color::Blue => "Blue",
+        hocg::Color::Purple => "Purple",
+        hocg::Color::Yellow => "Yellow",
+        hocg::Color::Colorless => "Colorless",
+    }
+}
+
+fn css([r, g, b]: [u8; 3]) -> String {
+    format!("rgb({r}, {g}, {b})")
+}
+
+/// Lighter version of the color, for the backgrounds
+fn tint(rgb: [u8; 3]) -> [u8; 3] {
+    rgb.map(|c| ((c as u16 + 0xFF * 2) / 3) as u8)
+}
+
+/// The English text when available, or the Japanese text
+fn text(english: &Option<String>, japanese: &Option<String>) -> String {
+    english
+        .as_deref()
+        .or(japanese.as_deref())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn card_type(card: &hocg::Card) -> String {
+    let mut card_type = match card.card_type {
+        hocg::CardType::OshiHoloMember => "Oshi Holo Member",
+        hocg::CardType::HoloMember => match card.bloom_level {
+            Some(hocg::BloomLevel::Debut) => "Debut Holo Member",
+            Some(hocg::BloomLevel::First) => "1st Holo Member",
+            Some(hocg::BloomLevel::Second) => "2nd Holo Member",
+            Some(hocg::BloomLevel::Spot) => "Spot Holo Member",
+            None => "Holo Member",
+        },
+        hocg::CardType::Support(support_type) => match support_type {
+            hocg::SupportType::Staff => "Support - Staff",
+            hocg::SupportType::Item => "Support - Item",
+            hocg::SupportType::Event => "Support - Event",
+            hocg::SupportType::Tool => "Support - Tool",
+            hocg::SupportType::Mascot => "Support - Mascot",
+            hocg::SupportType::Fan => "Support - Fan",
+        },
+        hocg::CardType::Cheer => "Cheer",
+        hocg::CardType::Other => "Other",
+    }
+    .to_string();
+    if card.buzz {
+        card_type.push_str(" - Buzz");
+    }
+    if card.limited {
+        card_type.push_str(" - Limited");
+    }
+    card_type
+}
+
+/// Splits the text in lines that fit in `max_width`. Japanese has no spaces, so long words
+/// are split between any characters.
+fn wrap_text(page: &Page, text: &str, max_width: f64, size: f64, bold: bool) -> Vec<String> {
+    let fits = |line: &str| page.text_width(line, size, bold) <= max_width;
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            if fits(&candidate) {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if !fits(&line) && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// A canvas of the size of the card image
+fn card_page(
+    (width, height): (u32, u32),
+    dpi: f32,
+    background: Option<&str>,
+) -> Result<Page, Box<dyn Error>> {
+    let mm = |px: u32| Mm(px as f32 * 25.4 / dpi);
+    Page::with_dpi(mm(width), mm(height), dpi, background)
+}
+
+/// Draws the lines from top to bottom, and stops at the bottom of the text area. Units are in mm.
+struct TextArea<'a> {
+    page: &'a Page,
+    x: f64,
+    top: f64,
+    y: f64,
+    width: f64,
+    bottom: f64,
+    /// No Japanese text, for the translations
+    english_only: bool,
+    overflow: bool,
+}
+
+impl TextArea<'_> {
+    fn text(&self, english: &Option<String>, japanese: &Option<String>) -> String {
+        if self.english_only {
+            english.clone().unwrap_or_default()
+        } else {
+            text(english, japanese)
+        }
+    }
+
+    fn has_room(&mut self, height: f64) -> bool {
+        if self.y + height > self.bottom {
+            self.overflow = true;
+        }
+        !self.overflow
+    }
+
+    /// Draws the text, wrapped to the width of the area
+    fn paragraph(&mut self, text: &str, color: &str) {
+        if text.is_empty() {
+            return;
+        }
+        for line in wrap_text(self.page, text, self.width, TEXT_SIZE, false) {
+            self.prefixed_line(&[], &line, color);
+        }
+    }
+
+    /// Draws a line, after a few color squares like the cheer costs
+    fn prefixed_line(&mut self, squares: &[hocg::Color], text: &str, color: &str) {
+        let height = TEXT_SIZE * LINE_SPACING;
+        if !self.has_room(height) {
+            return;
+        }
+
+        let size = TEXT_SIZE * 0.8;
+        let mut x = self.x;
+        for square in squares {
+            let rect = (x, self.y + (height - size) / 2.0, size, size);
+            self.page
+                .rect(rect, Some(&css(color_rgb(*square))), Some(BLACK));
+            x += size * 1.5;
+        }
+        self.page
+            .text(text, (x, self.y + height / 2.0), TEXT_SIZE, false, color);
+        self.y += height;
+    }
+
+    fn separator(&mut self) {
+        let gap = TEXT_SIZE * 0.4;
+        // nothing to separate yet
+        if self.y == self.top || !self.has_room(2.0 * gap) {
+            return;
+        }
+        self.y += gap;
+        self.page
+            .rect((self.x, self.y, self.width, 0.2), Some(GRAY), None);
+        self.y += gap;
+    }
+
+    /// Oshi skills, keywords, arts and effects of the card
+    fn card_text(&mut self, card: &hocg::Card) {
+        for skill in &card.oshi_skills {
+            self.separator();
+            let kind = match skill.kind {
+                hocg::OshiSkillKind::Normal => "Oshi Skill",
+                hocg::OshiSkillKind::Special => "SP Oshi Skill",
+                hocg::OshiSkillKind::Stage => "Oshi Stage Skill",
+            };
+            let holo_power = skill
+                .holo_power
+                .map(|hp| format!(" [holo Power: -{}]", String::from(hp).to_uppercase()))
+                .unwrap_or_default();
+            self.paragraph(&format!("{kind}{holo_power}"), GRAY);
+            self.paragraph(&self.text(&skill.name.english, &skill.name.japanese), BLACK);
+            self.paragraph(
+                &self.text(&skill.ability_text.english, &skill.ability_text.japanese),
+                BLACK,
+            );
+        }
+
+        for keyword in &card.keywords {
+            self.separator();
+            let effect = match keyword.effect {
+                KeywordEffect::Collab => "Collab Effect",
+                KeywordEffect::Bloom => "Bloom Effect",
+                KeywordEffect::Gift => "Gift",
+                KeywordEffect::Other => "Other",
+            };
+            let name = self.text(&keyword.name.english, &keyword.name.japanese);
+            self.paragraph(&format!("{effect}: {name}"), GRAY);
+            self.paragraph(
+                &self.text(
+                    &keyword.ability_text.english,
+                    &keyword.ability_text.japanese,
+                ),
+                BLACK,
+            );
+        }
+
+        for art in &card.arts {
+            self.separator();
+            let name = self.text(&art.name.english, &art.name.japanese);
+            let advantage = art
+                .advantage
+                .map(|(color, amount)| format!(" (+{amount} vs {})", color_name(color)))
+                .unwrap_or_default();
+            let power = String::from(art.power);
+            let line = format!("{name} {power}{advantage}");
+            self.prefixed_line(&art.cheers, line.trim_start(), BLACK);
+            if let Some(ability_text) = &art.ability_text {
+                self.paragraph(
+                    &self.text(&ability_text.english, &ability_text.japanese),
+                    BLACK,
+                );
+            }
+        }
+
+        let ability_text = self.text(&card.ability_text.english, &card.ability_text.japanese);
+        if !ability_text.is_empty() {
+            self.separator();
+            self.paragraph(&ability_text, BLACK);
+        }
+
+        if let Some(extra) = &card.extra {
+            self.separator();
+            self.paragraph(&self.text(&extra.english, &extra.japanese), GRAY);
+        }
+    }
+
+    /// Marks the text that didn't fit
+    fn finish(&mut self) {
+        if self.overflow {
+            // there is always a bit of room below the text area
+            let y = self.bottom + TEXT_SIZE * LINE_SPACING / 2.0;
+            self.page.text("...", (self.x, y), TEXT_SIZE, false, BLACK);
+        }
+    }
+}
+
+/// Renders a card without artwork as a card-sized frame with its text.
+/// Unknown cards only show their card number.
+pub fn render(
+    card: Option<&hocg::Card>,
+    card_number: &str,
+    size: (u32, u32),
+    dpi: f32,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let page = card_page(size, dpi, Some("white"))?;
+    let (width, height) = (page.width, page.height);
+    let border = 1.0;
+    let padding = 3.0;
+
+    let colors = card.map(|c| c.colors.clone()).unwrap_or_default();
+    let main_color = colors
+        .first()
+        .copied()
+        .map(color_rgb)
+        .unwrap_or([0xC0, 0xC0, 0xC0]);
+
+    // frame
+    page.rect((0.0, 0.0, width, height), Some(&css(main_color)), None);
+    let inner_width = width - 2.0 * border;
+    page.rect(
+        (border, border, inner_width, height - 2.0 * border),
+        Some("white"),
+        None,
+    );
+
+    // header with the name, and the HP or life on the right
+    let header_height = TITLE_SIZE * LINE_SPACING + padding;
+    page.rect(
+        (border, border, inner_width, header_height),
+        Some(&css(tint(main_color))),
+        None,
+    );
+    let header_y = border + header_height / 2.0;
+    let hp_label = match card {
+        Some(card) if card.life > 0 => Some(format!("LIFE {}", card.life)),
+        Some(card) if card.hp > 0 => Some(format!("HP {}", card.hp)),
+        _ => None,
+    };
+    let text_x = border + padding;
+    let text_width = width - 2.0 * (border + padding);
+    let mut hp_width = 0.0;
+    if let Some(hp_label) = &hp_label {
+        hp_width = page.text_width(hp_label, TITLE_SIZE, true);
+        let x = width - border - padding;
+        page.text_right(hp_label, (x, header_y), TITLE_SIZE, true, BLACK);
+    }
+
+    let name = card
+        .map(|c| text(&c.name.english, &c.name.japanese))
+        .unwrap_or_else(|| "Unknown card".into());
+    let name_width = text_width - hp_width - padding;
+    // shrink long names to fit next to the HP
+    let name_size = if page.text_width(&name, TITLE_SIZE, true) <= name_width {
+        TITLE_SIZE
+    } else {
+        TEXT_SIZE
+    };
+    let name = wrap_text(&page, &name, name_width, name_size, true)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    page.text(&name, (text_x, header_y), name_size, true, BLACK);
+
+    // footer with the card number
+    let footer_height = TEXT_SIZE * LINE_SPACING + padding;
+    let footer_y = height - border - footer_height / 2.0;
+    page.text(card_number, (text_x, footer_y), TEXT_SIZE, false, GRAY);
+    page.text_right(
+        FOOTER_TEXT,
+        (width - border - padding, footer_y),
+        TEXT_SIZE,
+        false,
+        GRAY,
+    );
+
+    let text_y = border + header_height + padding / 2.0;
+    let mut area = TextArea {
+        page: &page,
+        x: text_x,
+        top: text_y,
+        y: text_y,
+        width: text_width,
+        bottom: height - border - footer_height,
+        english_only: false,
+        overflow: false,
+    };
+
+    let Some(card) = card else {
+        area.paragraph("This card is not in the cards database yet.", GRAY);
+        return Ok(page.to_image()?.into_rgba8());
+    };
+
+    // type and colors
+    let colors = colors
+        .iter()
+        .filter(|c| **c != hocg::Color::Colorless)
+        .map(|c| color_name(*c))
+        .collect::<Vec<_>>()
+        .join("/");
+    let card_type = if colors.is_empty() {
+        card_type(card)
+    } else {
+        format!("{colors} - {}", card_type(card))
+    };
+    area.paragraph(&card_type, GRAY);
+
+    area.card_text(card);
+
+    if card.card_type == hocg::CardType::HoloMember {
+        area.separator();
+        area.prefixed_line(&card.baton_pass, "Baton Pass", GRAY);
+    }
+
+    let tags = card
+        .tags
+        .iter()
+        .map(|t| text(&t.english, &t.japanese))
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !tags.is_empty() {
+        area.paragraph(&tags, GRAY);
+    }
+
+    area.finish();
+
+    Ok(page.to_image()?.into_rgba8())
+}
+
+/// Whether the card has an English text to draw over the Japanese card
+pub fn has_translation(card: &hocg::Card) -> bool {
+    card.oshi_skills
+        .iter()
+        .any(|s| s.ability_text.english.is_some())
+        || card
+            .keywords
+            .iter()
+            .any(|k| k.ability_text.english.is_some())
+        || card.arts.iter().any(|a| {
+            a.name.english.is_some() || a.ability_text.as_ref().is_some_and(|t| t.english.is_some())
+        })
+        || card.ability_text.english.is_some()
+}
+
+/// A translucent box over the text area of a Japanese card image, with its English text only.
+/// The layer is drawn over the card image, see [`has_translation`].
+pub fn translation(
+    card: &hocg::Card,
+    size: (u32, u32),
+    dpi: f32,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let page = card_page(size, dpi, None)?;
+    let (width, height) = (page.width, page.height);
+    let inset = 3.0;
+    let padding = 1.5;
+
+    // the lower part of the card, below the artwork. Mostly white, with a hint of the art.
+    let box_top = height * 0.48;
+    let box_bottom = height - inset - 4.0;
+    page.rect(
+        (inset, box_top, width - 2.0 * inset, box_bottom - box_top),
+        Some("rgba(255, 255, 255, 0.8)"),
+        None,
+    );
+
+    let mut area = TextArea {
+        page: &page,
+        x: inset + padding,
+        top: box_top + padding,
+        y: box_top + padding,
+        width: width - 2.0 * (inset + padding),
+        bottom: box_bottom - padding - TEXT_SIZE * LINE_SPACING,
+        english_only: true,
+        overflow: false,
+    };
+    area.card_text(card);
+    area.finish();
+
+    Ok(page.to_image()?.into_rgba8())
+}
@@ -0,0 +1,125 @@
+use std::sync::OnceLock;
+
+use reqwest::{
+    Client, ClientBuilder, StatusCode,
+    header::{CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH},
+};
+
+/// A file downloaded from the sync backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteFile {
+    pub contents: Vec<u8>,
+    /// Used to detect changes made by another device during the sync
+    pub etag: Option<String>,
+}
+
+/// Remote storage used to sync the saved decks between devices
+pub trait SyncBackend {
+    /// Where the remote file is, the state of the last sync belongs to it
+    fn location(&self) -> &str;
+
+    /// Downloads the remote file, `None` if it does not exist yet
+    async fn pull(&self) -> Result<Option<RemoteFile>, String>;
+
+    /// Uploads the file, failing if the remote file changed since `previous` was pulled
+    async fn push(&self, contents: Vec<u8>, previous: Option<&RemoteFile>) -> Result<(), String>;
+}
+
+fn http_client() -> &'static Client {
+    static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+    HTTP_CLIENT.get_or_init(|| ClientBuilder::new().build().unwrap())
+}
+
+/// WebDAV, or any server that supports GET and PUT on a single file
+pub struct HttpSyncBackend {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl HttpSyncBackend {
+    pub fn new(url: String, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            url,
+            username,
+            password,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method) -> reqwest::RequestBuilder {
+        let request = http_client().request(method, &self.url);
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+}
+
+impl SyncBackend for HttpSyncBackend {
+    fn location(&self) -> &str {
+        &self.url
+    }
+
+    async fn pull(&self) -> Result<Option<RemoteFile>, String> {
+        let resp = self
+            .request(reqwest::Method::GET)
+            .send()
+            .await
+            .map_err(|err| format!("Could not reach the sync server: {err}"))?;
+
+        match resp.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err("The sync server refused the credentials.".into())
+            }
+            status if status.is_success() => {
+                let etag = resp
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(String::from);
+                let contents = resp
+                    .bytes()
+                    .await
+                    .map_err(|err| format!("Could not download the library: {err}"))?;
+                Ok(Some(RemoteFile {
+                    contents: contents.to_vec(),
+                    etag,
+                }))
+            }
+            status => Err(format!("The sync server returned an error: {status}")),
+        }
+    }
+
+    async fn push(&self, contents: Vec<u8>, previous: Option<&RemoteFile>) -> Result<(), String> {
+        let request = self
+            .request(reqwest::Method::PUT)
+            .header(CONTENT_TYPE, "application/json");
+        // don't overwrite changes from another device
+        let request = match previous {
+            Some(RemoteFile {
+                etag: Some(etag), ..
+            }) => request.header(IF_MATCH, etag),
+            Some(_) => request,
+            None => request.header(IF_NONE_MATCH, "*"),
+        };
+
+        let resp = request
+            .body(contents)
+            .send()
+            .await
+            .map_err(|err| format!("Could not reach the sync server: {err}"))?;
+
+        match resp.status() {
+            StatusCode::PRECONDITION_FAILED => Err(
+                "The library was changed by another device during the sync, please sync again."
+                    .into(),
+            ),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err("The sync server refused the credentials.".into())
+            }
+            status if status.is_success() => Ok(()),
+            status => Err(format!("The sync server returned an error: {status}")),
+        }
+    }
+}